@@ -0,0 +1,106 @@
+// Placement of the LED display inside the native window, kept free of
+// Android types so that rendering and touch hit-testing share one
+// computation and can be unit tested.
+
+use tetris_lib::common::{SCREEN_HEIGHT, SCREEN_WIDTH};
+
+/// Height of the on-screen controls strip at the bottom of the window
+pub const CONTROLS_HEIGHT: usize = 150;
+
+/// Largest integer scale of one LED, to prevent extreme sizes
+const MAX_SCALE: usize = 100;
+
+/// Pixel rectangle the LED display is rendered to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DisplayGeometry {
+    /// Window pixels per LED, always a whole number so LEDs have no gaps
+    pub scale: usize,
+    pub offset_x: usize,
+    pub offset_y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl DisplayGeometry {
+    /// Fit the 8x32 display into the window above the controls, centered.
+    /// A display larger than the window starts at its top-left edge and is
+    /// clipped.
+    pub fn for_window(window_width: usize, window_height: usize) -> Self {
+        let scale_x = window_width / SCREEN_WIDTH;
+        let scale_y = window_height / SCREEN_HEIGHT;
+        let scale = scale_x.min(scale_y).clamp(1, MAX_SCALE);
+
+        let width = SCREEN_WIDTH * scale;
+        let height = SCREEN_HEIGHT * scale;
+        let game_area_height = window_height.saturating_sub(CONTROLS_HEIGHT);
+
+        Self {
+            scale,
+            offset_x: window_width.saturating_sub(width) / 2,
+            offset_y: game_area_height.saturating_sub(height) / 2,
+            width,
+            height,
+        }
+    }
+
+    /// True if the window point lies on the rendered display, edges included
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        let left = self.offset_x as f32;
+        let right = (self.offset_x + self.width) as f32;
+        let top = self.offset_y as f32;
+        let bottom = (self.offset_y + self.height) as f32;
+
+        x >= left && x <= right && y >= top && y <= bottom
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_is_centered_above_controls_at_whole_scale() {
+        // (window, expected scale, offset)
+        let table = [
+            ((1080, 2400), 75, (240, 0)),
+            ((1081, 2401), 75, (240, 0)),
+            ((720, 1280), 40, (200, 0)),
+            ((1920, 1080), 33, (828, 0)),
+            ((64, 256), 8, (0, 0)),
+            ((10_000, 40_000), 100, (4600, 18325)),
+        ];
+        for ((w, h), scale, (x, y)) in table {
+            let geometry = DisplayGeometry::for_window(w, h);
+            assert_eq!(geometry.scale, scale, "{}x{}", w, h);
+            assert_eq!(
+                (geometry.offset_x, geometry.offset_y),
+                (x, y),
+                "{}x{}",
+                w,
+                h
+            );
+            assert_eq!(geometry.width, SCREEN_WIDTH * scale);
+            assert_eq!(geometry.height, SCREEN_HEIGHT * scale);
+            assert!(geometry.offset_x + geometry.width <= w);
+        }
+    }
+
+    #[test]
+    fn tiny_window_keeps_unit_scale_and_clips() {
+        let geometry = DisplayGeometry::for_window(4, 10);
+        assert_eq!(geometry.scale, 1);
+        assert_eq!((geometry.offset_x, geometry.offset_y), (0, 0));
+    }
+
+    #[test]
+    fn hit_test_matches_rendered_rectangle() {
+        let geometry = DisplayGeometry::for_window(1080, 2400);
+        let right = (geometry.offset_x + geometry.width) as f32;
+        let bottom = (geometry.offset_y + geometry.height) as f32;
+
+        assert!(geometry.contains(geometry.offset_x as f32, geometry.offset_y as f32));
+        assert!(geometry.contains(right, bottom));
+        assert!(!geometry.contains(geometry.offset_x as f32 - 1.0, 1000.0));
+        assert!(!geometry.contains(500.0, bottom + 1.0));
+    }
+}
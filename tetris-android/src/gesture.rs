@@ -0,0 +1,313 @@
+// Gesture recognition helpers kept free of Android types so they can be
+// unit tested: tap vs double-tap disambiguation and the gesture to game
+// action mapping.
+
+use std::time::Instant;
+
+use crate::TouchPoint;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GestureType {
+    SwipeLeft,
+    SwipeRight,
+    SwipeUp,
+    SwipeDown,
+    Tap,
+    DoubleTap,
+    LongPress,
+}
+
+const GESTURE_COUNT: usize = 7;
+
+/// Game input a gesture is translated into
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GameAction {
+    Left,
+    Right,
+    Up,
+    Down,
+    Joystick,
+    A,
+    B,
+    Nothing,
+}
+
+impl GameAction {
+    fn code(self) -> char {
+        match self {
+            GameAction::Left => 'L',
+            GameAction::Right => 'R',
+            GameAction::Up => 'U',
+            GameAction::Down => 'D',
+            GameAction::Joystick => 'J',
+            GameAction::A => 'A',
+            GameAction::B => 'B',
+            GameAction::Nothing => '-',
+        }
+    }
+
+    fn from_code(code: char) -> Option<Self> {
+        match code {
+            'L' => Some(GameAction::Left),
+            'R' => Some(GameAction::Right),
+            'U' => Some(GameAction::Up),
+            'D' => Some(GameAction::Down),
+            'J' => Some(GameAction::Joystick),
+            'A' => Some(GameAction::A),
+            'B' => Some(GameAction::B),
+            '-' => Some(GameAction::Nothing),
+            _ => None,
+        }
+    }
+}
+
+/// Which game action each gesture triggers.
+///
+/// Persisted as one action code per gesture in `GestureType` order, e.g. the
+/// default mapping is `LRUDJBA`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GestureMapping {
+    actions: [GameAction; GESTURE_COUNT],
+}
+
+impl GestureMapping {
+    pub const DEFAULT: Self = Self {
+        actions: [
+            GameAction::Left,     // SwipeLeft
+            GameAction::Right,    // SwipeRight
+            GameAction::Up,       // SwipeUp
+            GameAction::Down,     // SwipeDown
+            GameAction::Joystick, // Tap
+            GameAction::B,        // DoubleTap
+            GameAction::A,        // LongPress
+        ],
+    };
+
+    /// Mappings the remap button steps through, the default one first
+    pub const PRESETS: [Self; 3] = [
+        Self::DEFAULT,
+        // Tap confirms with A, long press for the joystick button
+        Self::DEFAULT
+            .with(GestureType::Tap, GameAction::A)
+            .with(GestureType::LongPress, GameAction::Joystick),
+        // Double tap for A, long press for B
+        Self::DEFAULT
+            .with(GestureType::DoubleTap, GameAction::A)
+            .with(GestureType::LongPress, GameAction::B),
+    ];
+
+    pub fn action(&self, gesture: GestureType) -> GameAction {
+        self.actions[gesture as usize]
+    }
+
+    const fn with(mut self, gesture: GestureType, action: GameAction) -> Self {
+        self.actions[gesture as usize] = action;
+        self
+    }
+
+    /// Preset after this mapping, the first one for a mapping that isn't a
+    /// preset, e.g. edited by hand in the settings file
+    pub fn next_preset(&self) -> Self {
+        let next = Self::PRESETS
+            .iter()
+            .position(|preset| preset == self)
+            .map_or(0, |idx| (idx + 1) % Self::PRESETS.len());
+        Self::PRESETS[next]
+    }
+
+    pub fn encode(&self) -> String {
+        self.actions.iter().map(|action| action.code()).collect()
+    }
+
+    pub fn decode(text: &str) -> Option<Self> {
+        let mut actions = [GameAction::Nothing; GESTURE_COUNT];
+        let mut codes = text.trim().chars();
+        for action in actions.iter_mut() {
+            *action = GameAction::from_code(codes.next()?)?;
+        }
+        if codes.next().is_some() {
+            return None;
+        }
+        Some(Self { actions })
+    }
+}
+
+impl Default for GestureMapping {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TapEvent {
+    Tap,
+    DoubleTap,
+}
+
+pub struct TapDisambiguator {
+    pending: Option<TouchPoint>,
+    max_time_ms: u64,
+    max_distance: f32,
+}
+
+impl TapDisambiguator {
+    pub const fn new(max_time_ms: u64, max_distance: f32) -> Self {
+        Self {
+            pending: None,
+            max_time_ms,
+            max_distance,
+        }
+    }
+
+    /// Register a finished tap.
+    ///
+    /// Returns `DoubleTap` when it completes a double tap. Otherwise the tap
+    /// is held pending, and a previous pending tap that can no longer become
+    /// a double tap is released as `Tap`.
+    pub fn tap(&mut self, tap: TouchPoint) -> Option<TapEvent> {
+        match self.pending.take() {
+            Some(previous) if self.is_double(&previous, &tap) => Some(TapEvent::DoubleTap),
+            Some(_) => {
+                self.pending = Some(tap);
+                Some(TapEvent::Tap)
+            }
+            None => {
+                self.pending = Some(tap);
+                None
+            }
+        }
+    }
+
+    /// Release the pending tap as `Tap` once the double-tap window expired
+    pub fn poll(&mut self, now: Instant) -> Option<TapEvent> {
+        let pending = self.pending?;
+        let elapsed = now.saturating_duration_since(pending.timestamp).as_millis() as u64;
+        if elapsed > self.max_time_ms {
+            self.pending = None;
+            Some(TapEvent::Tap)
+        } else {
+            None
+        }
+    }
+
+    fn is_double(&self, first: &TouchPoint, second: &TouchPoint) -> bool {
+        let elapsed = second
+            .timestamp
+            .saturating_duration_since(first.timestamp)
+            .as_millis() as u64;
+        let distance = ((second.x - first.x).powi(2) + (second.y - first.y).powi(2)).sqrt();
+        elapsed <= self.max_time_ms && distance <= self.max_distance
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn default_mapping_matches_classic_controls() {
+        let mapping = GestureMapping::default();
+        let table = [
+            (GestureType::SwipeLeft, GameAction::Left),
+            (GestureType::SwipeRight, GameAction::Right),
+            (GestureType::SwipeUp, GameAction::Up),
+            (GestureType::SwipeDown, GameAction::Down),
+            (GestureType::Tap, GameAction::Joystick),
+            (GestureType::DoubleTap, GameAction::B),
+            (GestureType::LongPress, GameAction::A),
+        ];
+        for (gesture, action) in table {
+            assert_eq!(mapping.action(gesture), action, "{:?}", gesture);
+        }
+    }
+
+    #[test]
+    fn remapped_gestures_round_trip_through_settings() {
+        let mapping = GestureMapping::default()
+            .with(GestureType::Tap, GameAction::A)
+            .with(GestureType::DoubleTap, GameAction::Joystick)
+            .with(GestureType::LongPress, GameAction::Nothing);
+
+        assert_eq!(mapping.action(GestureType::Tap), GameAction::A);
+        assert_eq!(mapping.encode(), "LRUDAJ-");
+        assert_eq!(GestureMapping::decode("LRUDAJ-\n"), Some(mapping));
+        assert_eq!(
+            GestureMapping::decode(&GestureMapping::DEFAULT.encode()),
+            Some(GestureMapping::DEFAULT)
+        );
+
+        assert_eq!(GestureMapping::decode("LRUD"), None);
+        assert_eq!(GestureMapping::decode("LRUDJBAX"), None);
+        assert_eq!(GestureMapping::decode("LRUDJBZ"), None);
+    }
+
+    #[test]
+    fn remap_button_cycles_through_presets() {
+        let mut mapping = GestureMapping::DEFAULT;
+        let mut seen = Vec::new();
+        for _ in 0..GestureMapping::PRESETS.len() {
+            seen.push(mapping.encode());
+            mapping = mapping.next_preset();
+        }
+        assert_eq!(mapping, GestureMapping::DEFAULT);
+        assert_eq!(seen, ["LRUDJBA", "LRUDABJ", "LRUDJAB"]);
+
+        // A hand edited mapping starts over from the default
+        let custom = GestureMapping::decode("RLUDJBA").unwrap();
+        assert_eq!(custom.next_preset(), GestureMapping::DEFAULT);
+    }
+
+    fn touch(x: f32, y: f32, at: Instant) -> TouchPoint {
+        TouchPoint {
+            x,
+            y,
+            timestamp: at,
+        }
+    }
+
+    #[test]
+    fn double_tap_yields_only_double_tap() {
+        let start = Instant::now();
+        let mut taps = TapDisambiguator::new(300, 100.0);
+
+        assert_eq!(taps.tap(touch(10.0, 10.0, start)), None);
+        assert_eq!(taps.poll(start + Duration::from_millis(100)), None);
+        assert_eq!(
+            taps.tap(touch(12.0, 11.0, start + Duration::from_millis(200))),
+            Some(TapEvent::DoubleTap)
+        );
+        // Nothing left to release afterwards
+        assert_eq!(taps.poll(start + Duration::from_secs(1)), None);
+    }
+
+    #[test]
+    fn single_tap_is_released_after_window() {
+        let start = Instant::now();
+        let mut taps = TapDisambiguator::new(300, 100.0);
+
+        assert_eq!(taps.tap(touch(10.0, 10.0, start)), None);
+        assert_eq!(taps.poll(start + Duration::from_millis(300)), None);
+        assert_eq!(
+            taps.poll(start + Duration::from_millis(301)),
+            Some(TapEvent::Tap)
+        );
+        assert_eq!(taps.poll(start + Duration::from_millis(400)), None);
+    }
+
+    #[test]
+    fn distant_second_tap_releases_first_as_tap() {
+        let start = Instant::now();
+        let mut taps = TapDisambiguator::new(300, 100.0);
+
+        assert_eq!(taps.tap(touch(10.0, 10.0, start)), None);
+        assert_eq!(
+            taps.tap(touch(500.0, 10.0, start + Duration::from_millis(100))),
+            Some(TapEvent::Tap)
+        );
+        assert_eq!(
+            taps.poll(start + Duration::from_millis(500)),
+            Some(TapEvent::Tap)
+        );
+    }
+}
@@ -13,13 +13,25 @@ use std::sync::{
 };
 use std::time::Duration;
 use tetris_lib::{
-    common::{GameController, LedDisplay, Timer, SCREEN_HEIGHT, SCREEN_WIDTH},
+    common::{
+        Focus, GameController, LedDisplay, MinFrameTimer, PauseOnFocusLoss, Timer, SCREEN_HEIGHT,
+        SCREEN_WIDTH,
+    },
+    coords::led_index,
     games::run_game_menu,
 };
 
+mod geometry;
+mod gesture;
+
+use geometry::{DisplayGeometry, CONTROLS_HEIGHT};
+use gesture::{GameAction, GestureMapping, GestureType, TapDisambiguator, TapEvent};
+
 // Global state for the game display and input
 static LEDS: Mutex<[RGB8; 256]> = Mutex::new([RGB8::new(0, 0, 0); 256]);
 static SHOULD_UPDATE_DISPLAY: AtomicBool = AtomicBool::new(false);
+// Cleared while the app is paused, the game thread holds until it resumes
+static FOCUS: Focus = Focus::new();
 
 // Gesture detection state
 #[derive(Debug, Clone, Copy)]
@@ -42,7 +54,7 @@ impl Default for TouchPoint {
 struct GestureState {
     touch_start: std::sync::RwLock<Option<TouchPoint>>,
     last_touch: std::sync::RwLock<Option<TouchPoint>>,
-    last_tap: std::sync::RwLock<Option<TouchPoint>>, // Track last tap for double tap detection
+    taps: Mutex<TapDisambiguator>, // Holds single taps until the double tap window expires
     started_in_game_area: std::sync::RwLock<bool>, // Track if current gesture started in LED display
 }
 
@@ -51,7 +63,10 @@ impl Default for GestureState {
         Self {
             touch_start: std::sync::RwLock::new(None),
             last_touch: std::sync::RwLock::new(None),
-            last_tap: std::sync::RwLock::new(None), // Initialize last tap tracking
+            taps: Mutex::new(TapDisambiguator::new(
+                DOUBLE_TAP_MAX_TIME_MS,
+                DOUBLE_TAP_MAX_DISTANCE,
+            )),
             started_in_game_area: std::sync::RwLock::new(false),
         }
     }
@@ -60,7 +75,10 @@ impl Default for GestureState {
 static GESTURE_STATE: GestureState = GestureState {
     touch_start: std::sync::RwLock::new(None),
     last_touch: std::sync::RwLock::new(None),
-    last_tap: std::sync::RwLock::new(None), // Initialize last tap tracking in static
+    taps: Mutex::new(TapDisambiguator::new(
+        DOUBLE_TAP_MAX_TIME_MS,
+        DOUBLE_TAP_MAX_DISTANCE,
+    )),
     started_in_game_area: std::sync::RwLock::new(false),
 };
 
@@ -72,15 +90,47 @@ const LONG_PRESS_TIME_MS: u64 = 500; // Time for long press detection
 const DOUBLE_TAP_MAX_TIME_MS: u64 = 300; // Maximum time between taps for double tap
 const DOUBLE_TAP_MAX_DISTANCE: f32 = 100.0; // Maximum distance between taps for double tap
 
-#[derive(Debug, Clone, Copy)]
-enum GestureType {
-    SwipeLeft,
-    SwipeRight,
-    SwipeUp,
-    SwipeDown,
-    Tap,
-    DoubleTap,
-    LongPress,
+// Gesture to game action mapping, loaded from GESTURE_MAPPING_FILE at startup
+// and saved there whenever the remap button changes it
+static GESTURE_MAPPING: std::sync::RwLock<GestureMapping> =
+    std::sync::RwLock::new(GestureMapping::DEFAULT);
+const GESTURE_MAPPING_FILE: &str = "gesture_mapping";
+
+fn load_gesture_mapping(app: &AndroidApp) {
+    let Some(path) = app.internal_data_path() else {
+        return;
+    };
+    let Ok(text) = std::fs::read_to_string(path.join(GESTURE_MAPPING_FILE)) else {
+        return;
+    };
+    match GestureMapping::decode(&text) {
+        Some(mapping) => {
+            info!("🎛️ Loaded gesture mapping {}", mapping.encode());
+            if let Ok(mut current) = GESTURE_MAPPING.write() {
+                *current = mapping;
+            }
+        }
+        None => log::warn!("Ignoring invalid gesture mapping {:?}", text),
+    }
+}
+
+fn save_gesture_mapping(app: &AndroidApp, mapping: &GestureMapping) {
+    let Some(path) = app.internal_data_path() else {
+        return;
+    };
+    if let Err(err) = std::fs::write(path.join(GESTURE_MAPPING_FILE), mapping.encode()) {
+        log::warn!("Failed to save gesture mapping: {}", err);
+    }
+}
+
+/// Switch to the next gesture mapping preset and keep it for later runs
+fn cycle_gesture_mapping(app: &AndroidApp) {
+    let Ok(mut current) = GESTURE_MAPPING.write() else {
+        return;
+    };
+    *current = current.next_preset();
+    info!("🎛️ Gesture mapping set to {}", current.encode());
+    save_gesture_mapping(app, &current);
 }
 
 #[derive(Default)]
@@ -372,6 +422,18 @@ impl AndroidDisplay {
             stride,
             "B",
         );
+        // Gesture mapping button, steps through the presets
+        self.draw_button_with_text(
+            pixels,
+            ButtonRect::new(
+                right_button_x,
+                button_start_y + 3 * (side_button_height + button_gap),
+                side_button_width,
+                side_button_height,
+            ),
+            stride,
+            "G",
+        );
     }
 
     fn draw_button_with_text(
@@ -500,39 +562,16 @@ impl AndroidDisplay {
                     let window_height = buffer.height();
                     let stride = buffer.stride();
 
-                    // Calculate scaling to fit the 8x32 display in the window
-                    let scale_x = window_width / SCREEN_WIDTH;
-                    let scale_y = window_height / SCREEN_HEIGHT;
-                    let base_scale = scale_x.min(scale_y).max(1);
-                    // Use base_scale but limit it to prevent extreme sizes
-                    let scale = base_scale.min(100); // Cap at 100x scale to prevent issues
-                                                     // debug!(
-                                                     //     "📱 Window: {}x{}, stride: {}, scale: {}, base_scale: {}",
-                                                     //     window_width, window_height, stride, scale, base_scale
-                                                     // );
-
-                    // Center the display in the upper portion, leaving space for controls
-                    let display_width = SCREEN_WIDTH * scale;
-                    let display_height = SCREEN_HEIGHT * scale;
-                    let controls_height = 150; // Smaller controls area to give more space to game
-                    let game_area_height = window_height.saturating_sub(controls_height);
-
-                    // Ensure display fits on screen - if too large, it will be clipped but positioned correctly
-                    let offset_x = if display_width <= window_width {
-                        (window_width - display_width) / 2
-                    } else {
-                        0 // If display is larger than window, start at left edge
-                    };
-
-                    let offset_y = if display_height <= game_area_height {
-                        (game_area_height - display_height) / 2
-                    } else {
-                        0 // If display is larger than available height, start at top
-                    };
-
+                    // Fit the 8x32 display in the window, leaving space for controls
+                    let DisplayGeometry {
+                        scale,
+                        offset_x,
+                        offset_y,
+                        ..
+                    } = DisplayGeometry::for_window(window_width, window_height);
                     // debug!(
-                    //     "🎮 Display: {}x{} at ({}, {}), window: {}x{}, game_area_height: {}",
-                    //     display_width, display_height, offset_x, offset_y, window_width, window_height, game_area_height
+                    //     "🎮 Display: scale {} at ({}, {}), window: {}x{}",
+                    //     scale, offset_x, offset_y, window_width, window_height
                     // );
 
                     // Get buffer as slice of pixels
@@ -558,13 +597,7 @@ impl AndroidDisplay {
                     // Draw each LED pixel as a scaled block
                     for led_y in 0..SCREEN_HEIGHT {
                         for led_x in 0..SCREEN_WIDTH {
-                            let led_idx = if led_y % 2 == 0 {
-                                led_y * SCREEN_WIDTH + (SCREEN_WIDTH - 1 - led_x)
-                            } else {
-                                led_y * SCREEN_WIDTH + led_x
-                            };
-
-                            let led = leds[led_idx];
+                            let led = leds[led_index(led_x, led_y)];
 
                             // Check if this is a black pixel (background) and convert to dark gray
                             let (r, g, b) = if led.r == 0 && led.g == 0 && led.b == 0 {
@@ -610,7 +643,7 @@ impl AndroidDisplay {
                         window_width,
                         window_height,
                         stride,
-                        controls_height,
+                        CONTROLS_HEIGHT,
                     );
                     // Unlock buffer to present to screen
                     drop(buffer);
@@ -673,68 +706,51 @@ impl AndroidController {
             self.process_input_events();
             LAST_INPUT_PROCESS_TIME.store(now, Ordering::Relaxed);
         }
+
+        self.release_pending_tap();
     }
 
-    fn detect_gesture(&self, start: TouchPoint, end: TouchPoint) -> Option<GestureType> {
-        // Clear stale last_tap entries that are too old to be part of a double tap
-        if let Ok(mut last_tap_guard) = GESTURE_STATE.last_tap.write() {
-            if let Some(last_tap) = *last_tap_guard {
-                let time_since_last_tap =
-                    end.timestamp.duration_since(last_tap.timestamp).as_millis() as u64;
-                if time_since_last_tap > DOUBLE_TAP_MAX_TIME_MS * 2 {
-                    *last_tap_guard = None;
-                    info!("🧹 Cleared stale last_tap ({}ms old)", time_since_last_tap);
-                }
-            }
+    // Emit a held single tap once it can no longer become a double tap
+    fn release_pending_tap(&self) {
+        let released = GESTURE_STATE
+            .taps
+            .lock()
+            .ok()
+            .and_then(|mut taps| taps.poll(std::time::Instant::now()));
+        if released == Some(TapEvent::Tap) {
+            info!("👆 Double tap window expired, releasing single tap");
+            self.handle_gesture(GestureType::Tap);
         }
+    }
 
+    fn detect_gesture(&self, start: TouchPoint, end: TouchPoint) -> Option<GestureType> {
         let dx = end.x - start.x;
         let dy = end.y - start.y;
         let distance = (dx * dx + dy * dy).sqrt();
         let time_diff = end.timestamp.duration_since(start.timestamp).as_millis() as u64;
 
-        // Check for tap (short press with minimal movement)
+        // Check for tap (short press with minimal movement). Single taps are
+        // deferred until the double tap window expires, see release_pending_tap
         if distance < TAP_MAX_DISTANCE && time_diff < MAX_SWIPE_TIME_MS {
-            // Check for double tap by comparing with last tap
-            if let Ok(last_tap_guard) = GESTURE_STATE.last_tap.read() {
-                if let Some(last_tap) = *last_tap_guard {
-                    let time_since_last_tap =
-                        end.timestamp.duration_since(last_tap.timestamp).as_millis() as u64;
-                    let distance_from_last_tap =
-                        ((end.x - last_tap.x).powi(2) + (end.y - last_tap.y).powi(2)).sqrt();
-
-                    info!("🔍 Checking double tap: time_diff={}ms (max={}ms), distance={:.1}px (max={:.1}px)", 
-                          time_since_last_tap, DOUBLE_TAP_MAX_TIME_MS, distance_from_last_tap, DOUBLE_TAP_MAX_DISTANCE);
-
-                    // If this tap is close enough in time and space to the last tap, it's a double tap
-                    if time_since_last_tap <= DOUBLE_TAP_MAX_TIME_MS
-                        && distance_from_last_tap <= DOUBLE_TAP_MAX_DISTANCE
-                    {
-                        info!("✅ Double tap detected! Clearing last_tap state.");
-                        // Clear the last tap to prevent triple taps from being detected as double taps
-                        drop(last_tap_guard);
-                        if let Ok(mut last_tap_write) = GESTURE_STATE.last_tap.write() {
-                            *last_tap_write = None;
-                        }
-                        return Some(GestureType::DoubleTap);
-                    } else {
-                        info!("❌ Not a double tap - conditions not met");
-                    }
-                } else {
-                    info!("🔍 No previous tap found for double tap check");
+            let event = GESTURE_STATE
+                .taps
+                .lock()
+                .ok()
+                .and_then(|mut taps| taps.tap(end));
+            return match event {
+                Some(TapEvent::DoubleTap) => {
+                    info!("✅ Double tap detected");
+                    Some(GestureType::DoubleTap)
                 }
-            }
-
-            // Store this tap as the last tap for potential double tap detection
-            if let Ok(mut last_tap_guard) = GESTURE_STATE.last_tap.write() {
-                *last_tap_guard = Some(end);
-                info!(
-                    "💾 Stored tap at ({:.1}, {:.1}) for double tap detection",
-                    end.x, end.y
-                );
-            }
-
-            return Some(GestureType::Tap);
+                Some(TapEvent::Tap) => Some(GestureType::Tap),
+                None => {
+                    info!(
+                        "💾 Holding tap at ({:.1}, {:.1}) for double tap detection",
+                        end.x, end.y
+                    );
+                    None
+                }
+            };
         }
 
         // Check for long press
@@ -769,46 +785,44 @@ impl AndroidController {
     }
 
     fn handle_gesture(&self, gesture: GestureType) {
-        info!("🎯 Setting gesture input: {:?}", gesture);
-
-        match gesture {
-            GestureType::SwipeLeft => {
+        let action = GESTURE_MAPPING
+            .read()
+            .map(|mapping| mapping.action(gesture))
+            .unwrap_or_else(|_| GestureMapping::DEFAULT.action(gesture));
+        info!("🎯 Setting gesture input: {:?} -> {:?}", gesture, action);
+
+        match action {
+            GameAction::Left => {
                 INPUT_STATE.x_input.store(-1, Ordering::Relaxed);
                 INPUT_STATE.gesture_x_pending.store(true, Ordering::Relaxed);
-                info!("✅ Set x_input=-1, gesture_x_pending=true");
             }
-            GestureType::SwipeRight => {
+            GameAction::Right => {
                 INPUT_STATE.x_input.store(1, Ordering::Relaxed);
                 INPUT_STATE.gesture_x_pending.store(true, Ordering::Relaxed);
-                info!("✅ Set x_input=1, gesture_x_pending=true");
             }
-            GestureType::SwipeUp => {
+            GameAction::Up => {
                 INPUT_STATE.y_input.store(-1, Ordering::Relaxed);
                 INPUT_STATE.gesture_y_pending.store(true, Ordering::Relaxed);
-                info!("✅ Set y_input=-1, gesture_y_pending=true");
             }
-            GestureType::SwipeDown => {
+            GameAction::Down => {
                 INPUT_STATE.y_input.store(1, Ordering::Relaxed);
                 INPUT_STATE.gesture_y_pending.store(true, Ordering::Relaxed);
-                info!("✅ Set y_input=1, gesture_y_pending=true");
             }
-            GestureType::Tap => {
+            GameAction::Joystick => {
                 INPUT_STATE.joystick_pressed.store(true, Ordering::Relaxed);
                 INPUT_STATE
                     .gesture_joystick_pending
                     .store(true, Ordering::Relaxed);
-                info!("✅ Set joystick_pressed=true, gesture_joystick_pending=true");
             }
-            GestureType::LongPress => {
+            GameAction::A => {
                 INPUT_STATE.a_pressed.store(true, Ordering::Relaxed);
                 INPUT_STATE.gesture_a_pending.store(true, Ordering::Relaxed);
-                info!("✅ Set a_pressed=true, gesture_a_pending=true");
             }
-            GestureType::DoubleTap => {
+            GameAction::B => {
                 INPUT_STATE.b_pressed.store(true, Ordering::Relaxed);
                 INPUT_STATE.gesture_b_pending.store(true, Ordering::Relaxed);
-                info!("✅ Set b_pressed=true, gesture_b_pending=true");
             }
+            GameAction::Nothing => {}
         }
     }
 
@@ -818,48 +832,21 @@ impl AndroidController {
             let window_width = native_window.width() as usize;
             let window_height = native_window.height() as usize;
 
-            // Calculate the same scaling and positioning as in render_to_native_window
-            let scale_x = window_width / SCREEN_WIDTH;
-            let scale_y = window_height / SCREEN_HEIGHT;
-            let base_scale = scale_x.min(scale_y).max(1);
-            let scale = base_scale.min(100);
-
-            let display_width = SCREEN_WIDTH * scale;
-            let display_height = SCREEN_HEIGHT * scale;
-            let controls_height = 150;
-            let game_area_height = window_height.saturating_sub(controls_height);
-
-            let offset_x = if display_width <= window_width {
-                (window_width - display_width) / 2
-            } else {
-                0
-            };
-
-            let offset_y = if display_height <= game_area_height {
-                (game_area_height - display_height) / 2
-            } else {
-                0
-            };
-
             // Check if touch is within the actual LED display bounds
-            let display_left = offset_x as f32;
-            let display_right = (offset_x + display_width) as f32;
-            let display_top = offset_y as f32;
-            let display_bottom = (offset_y + display_height) as f32;
-
-            x >= display_left && x <= display_right && y >= display_top && y <= display_bottom
+            DisplayGeometry::for_window(window_width, window_height).contains(x, y)
         } else {
             false
         }
     }
 
-    fn handle_touch_input(&self, x: usize, y: usize) {
+    /// `first_touch` is false while the finger moves, buttons that toggle
+    /// something only react to the touch that starts on them
+    fn handle_touch_input(&self, x: usize, y: usize, first_touch: bool) {
         // Get window dimensions to calculate button positions
         if let Some(native_window) = self.app.native_window() {
             let window_width = native_window.width() as usize;
             let window_height = native_window.height() as usize;
-            let controls_height = 150;
-            let controls_y_start = window_height - controls_height;
+            let controls_y_start = window_height - CONTROLS_HEIGHT;
 
             // Clear previous inputs first
             INPUT_STATE.x_input.store(0, Ordering::Relaxed);
@@ -935,6 +922,15 @@ impl AndroidController {
             {
                 INPUT_STATE.b_pressed.store(true, Ordering::Relaxed); // B
             }
+            // Gesture mapping button (4th button on right side)
+            if first_touch
+                && x >= right_button_x
+                && x < right_button_x + side_button_width
+                && y >= button_start_y + 3 * (side_button_height + button_gap)
+                && y < button_start_y + 3 * (side_button_height + button_gap) + side_button_height
+            {
+                cycle_gesture_mapping(&self.app);
+            }
         }
     }
 
@@ -1014,7 +1010,9 @@ impl AndroidController {
 
                                             // Handle button presses only if touch started outside LED display area
                                             if !started_in_led {
-                                                self.handle_touch_input(x as usize, y as usize);
+                                                self.handle_touch_input(
+                                                    x as usize, y as usize, true,
+                                                );
                                             }
                                             true
                                         }
@@ -1035,7 +1033,9 @@ impl AndroidController {
                                                 GESTURE_STATE.started_in_game_area.read()
                                             {
                                                 if !*started {
-                                                    self.handle_touch_input(x as usize, y as usize);
+                                                    self.handle_touch_input(
+                                                        x as usize, y as usize, false,
+                                                    );
                                                 }
                                             }
                                             true
@@ -1056,16 +1056,16 @@ impl AndroidController {
                                                 false
                                             };
 
-                                            // Always try to detect gesture everywhere
+                                            // Only detect gestures that started in LED display area,
+                                            // so taps on buttons never become pending taps
                                             if let Ok(start_guard) =
                                                 GESTURE_STATE.touch_start.read()
                                             {
                                                 if let Some(start) = *start_guard {
-                                                    if let Some(gesture) =
-                                                        self.detect_gesture(start, touch_point)
-                                                    {
-                                                        // Only apply gesture if it started in LED display area
-                                                        if started_in_led {
+                                                    if started_in_led {
+                                                        if let Some(gesture) =
+                                                            self.detect_gesture(start, touch_point)
+                                                        {
                                                             self.handle_gesture(gesture);
                                                         }
                                                     }
@@ -1216,6 +1216,7 @@ impl GameController for AndroidController {
 fn android_main(app: AndroidApp) {
     android_logger::init_once(Config::default().with_max_level(log::LevelFilter::Info));
     info!("Tetris Android app starting with android-activity");
+    load_gesture_mapping(&app);
 
     // Start the game in a separate thread
     let game_app = app.clone();
@@ -1223,7 +1224,7 @@ fn android_main(app: AndroidApp) {
         // Create a simple async runtime using futures-executor
         let mut display = AndroidDisplay::new(game_app.clone());
         let mut controller = AndroidController::new(game_app);
-        let timer = AndroidTimer;
+        let timer = PauseOnFocusLoss::new(MinFrameTimer::new(AndroidTimer), &FOCUS);
 
         let seed_fn = || {
             std::time::SystemTime::now()
@@ -1261,9 +1262,11 @@ fn android_main(app: AndroidApp) {
                     }
                     MainEvent::Resume { .. } => {
                         info!("▶️  App resumed");
+                        FOCUS.set_focused(true);
                     }
                     MainEvent::Pause => {
                         info!("⏸️  App paused");
+                        FOCUS.set_focused(false);
                     }
                     MainEvent::Stop => {
                         info!("⏹️  App stopped");
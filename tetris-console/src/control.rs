@@ -64,28 +64,80 @@ enum KeyEvent {
     Right,
     Up,
     Down,
-    Space,
-    Enter,
+    Confirm,
     Quit,
     ButtonA,
     ButtonB,
     None,
 }
 
+/// Keys bound to each control, any of the listed bytes triggers it.
+/// Arrow keys always move and a lone ESC always quits.
+#[derive(Debug, Clone, Copy)]
+pub struct KeyBindings {
+    pub left: &'static [u8],
+    pub right: &'static [u8],
+    pub up: &'static [u8],
+    pub down: &'static [u8],
+    /// The joystick press: selects in the menu, rotates or fires in games
+    pub confirm: &'static [u8],
+    pub button_a: &'static [u8],
+    pub button_b: &'static [u8],
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            left: b"aA",
+            right: b"dD",
+            up: b"wW",
+            down: b"sS",
+            confirm: b"\n\r",
+            button_a: b" zZqQ",
+            button_b: b"xXeE",
+        }
+    }
+}
+
+impl KeyBindings {
+    fn key_event(&self, ch: u8) -> KeyEvent {
+        let bindings = [
+            (self.left, KeyEvent::Left),
+            (self.right, KeyEvent::Right),
+            (self.up, KeyEvent::Up),
+            (self.down, KeyEvent::Down),
+            (self.confirm, KeyEvent::Confirm),
+            (self.button_a, KeyEvent::ButtonA),
+            (self.button_b, KeyEvent::ButtonB),
+        ];
+        match bindings.into_iter().find(|(keys, _)| keys.contains(&ch)) {
+            Some((_, event)) => event,
+            None => {
+                debug!("Unknown key code {}", ch);
+                KeyEvent::None
+            }
+        }
+    }
+}
+
 pub struct SimpleConsoleController {
     current_key: Arc<Mutex<KeyEvent>>,
-    _input_thread: std::thread::JoinHandle<()>,
+    _input_thread: Option<std::thread::JoinHandle<()>>,
 }
 
 impl SimpleConsoleController {
     pub fn new() -> Self {
+        Self::with_bindings(KeyBindings::default())
+    }
+
+    pub fn with_bindings(bindings: KeyBindings) -> Self {
         let current_key = Arc::new(Mutex::new(KeyEvent::None));
         let current_key_clone = current_key.clone();
 
         // Input processing thread (like machine.py)
         let input_thread = std::thread::spawn(move || {
             loop {
-                let key = Self::read_key();
+                let key = Self::read_key(&bindings);
                 if key != KeyEvent::None {
                     {
                         let mut current = current_key_clone.lock().unwrap();
@@ -105,11 +157,11 @@ impl SimpleConsoleController {
 
         Self {
             current_key,
-            _input_thread: input_thread,
+            _input_thread: Some(input_thread),
         }
     }
 
-    fn read_key() -> KeyEvent {
+    fn read_key(bindings: &KeyBindings) -> KeyEvent {
         let mut buffer = [0; 1];
         let mut stdin = io::stdin();
 
@@ -133,18 +185,7 @@ impl SimpleConsoleController {
                             _ => KeyEvent::None,
                         }
                     }
-                    b' ' => KeyEvent::Space,
-                    b'\n' | b'\r' => KeyEvent::Enter,
-                    b'a' | b'A' => KeyEvent::Left,
-                    b'd' | b'D' => KeyEvent::Right,
-                    b'w' | b'W' => KeyEvent::Up,
-                    b's' | b'S' => KeyEvent::Down,
-                    b'q' | b'Q' => KeyEvent::ButtonA,
-                    b'e' | b'E' => KeyEvent::ButtonB,
-                    _ => {
-                        debug!("Unknown key code {}", ch);
-                        KeyEvent::None
-                    }
+                    _ => bindings.key_event(ch),
                 }
             }
             _ => KeyEvent::None,
@@ -192,7 +233,7 @@ impl GameController for SimpleConsoleController {
         let key = key_guard.clone();
 
         match key {
-            KeyEvent::Space | KeyEvent::Enter => {
+            KeyEvent::Confirm => {
                 *key_guard = KeyEvent::None; // Clear the key since we consumed it
                 true
             }
@@ -226,3 +267,68 @@ impl GameController for SimpleConsoleController {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A controller without the stdin thread, fed one key at a time
+    fn controller() -> SimpleConsoleController {
+        SimpleConsoleController {
+            current_key: Arc::new(Mutex::new(KeyEvent::None)),
+            _input_thread: None,
+        }
+    }
+
+    fn feed(controller: &SimpleConsoleController, bindings: &KeyBindings, ch: u8) {
+        *controller.current_key.lock().unwrap() = bindings.key_event(ch);
+    }
+
+    // Which of the joystick, A and B fired
+    fn presses(controller: &SimpleConsoleController) -> [bool; 3] {
+        [
+            controller.joystick_was_pressed(),
+            controller.a_was_pressed(),
+            controller.b_was_pressed(),
+        ]
+    }
+
+    #[test]
+    fn each_button_key_fires_its_action_once() {
+        let bindings = KeyBindings::default();
+        let controller = controller();
+        let table = [
+            (b'\n', [true, false, false]),
+            (b'\r', [true, false, false]),
+            (b' ', [false, true, false]),
+            (b'x', [false, false, true]),
+            (b'a', [false, false, false]),
+            (b'?', [false, false, false]),
+        ];
+        for (ch, expected) in table {
+            feed(&controller, &bindings, ch);
+            assert_eq!(presses(&controller), expected, "key {}", ch);
+            assert_eq!(presses(&controller), [false; 3], "key {} again", ch);
+        }
+    }
+
+    #[test]
+    fn custom_bindings_replace_the_defaults() {
+        let bindings = KeyBindings {
+            confirm: b"f",
+            button_a: b"j",
+            button_b: b"k",
+            ..KeyBindings::default()
+        };
+        let controller = controller();
+        for (ch, expected) in [
+            (b'f', [true, false, false]),
+            (b'j', [false, true, false]),
+            (b'k', [false, false, true]),
+            (b' ', [false, false, false]),
+        ] {
+            feed(&controller, &bindings, ch);
+            assert_eq!(presses(&controller), expected, "key {}", ch);
+        }
+    }
+}
@@ -1,6 +1,7 @@
 use smart_leds::RGB8;
 use std::io::{self, Write};
 use tetris_lib::common::{LedDisplay, SCREEN_HEIGHT, SCREEN_WIDTH};
+use tetris_lib::coords::led_index;
 
 // Simple console display implementation
 pub struct SimpleConsoleDisplay;
@@ -14,8 +15,7 @@ impl LedDisplay for SimpleConsoleDisplay {
             for _ in 0..2 {
                 // Double height for better visibility
                 for x in 0..SCREEN_WIDTH {
-                    let actual_x = if y % 2 == 0 { SCREEN_WIDTH - 1 - x } else { x };
-                    let idx = y * SCREEN_WIDTH + actual_x;
+                    let idx = led_index(x, y);
 
                     if idx < leds.len() {
                         let color = &leds[idx];
@@ -1,5 +1,8 @@
 use std::time::Duration;
-use tetris_lib::{common::Timer, games::run_game_menu};
+use tetris_lib::{
+    common::{MinFrameTimer, Timer},
+    games::run_game_menu,
+};
 
 mod control;
 mod display;
@@ -36,7 +39,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let mut display = SimpleConsoleDisplay;
     let mut controller = SimpleConsoleController::new();
-    let timer = ConsoleTimer;
+    let timer = MinFrameTimer::new(ConsoleTimer);
 
     // Use the extracted game menu loop
     run_game_menu(&mut display, &mut controller, &timer, || {
@@ -1,39 +1,79 @@
+use core::cell::Cell;
 use core::marker::Sized;
 use embassy_rp::adc::{Adc, Channel};
 use embassy_rp::gpio::Input;
 use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
-use embassy_sync::signal::Signal;
+use embassy_sync::blocking_mutex::Mutex;
+use embassy_time::Instant;
+use tetris_lib::input::{JoystickCache, PressLatch};
 
-// Shared signals accessible from multiple tasks
-pub static JOYSTICK_BUTTON_SIGNAL: Signal<CriticalSectionRawMutex, bool> = Signal::new();
-pub static BUTTON_A_SIGNAL: Signal<CriticalSectionRawMutex, bool> = Signal::new();
-pub static BUTTON_B_SIGNAL: Signal<CriticalSectionRawMutex, bool> = Signal::new();
+// How long a press waits for the game to read it: one frame of the slowest
+// game loop (tanks, 100 ms) plus a display write. Anything older is stale,
+// leftovers between screens are dropped by `flush_presses`.
+const PRESS_HOLD_MS: u64 = 120;
+
+// Timestamped press shared between a button task and the game
+pub struct ButtonLatch(Mutex<CriticalSectionRawMutex, Cell<PressLatch>>);
+
+impl ButtonLatch {
+    const fn new() -> Self {
+        Self(Mutex::new(Cell::new(PressLatch::new(PRESS_HOLD_MS))))
+    }
+
+    fn update<R>(&self, f: impl FnOnce(&mut PressLatch) -> R) -> R {
+        self.0.lock(|cell| {
+            let mut latch = cell.get();
+            let result = f(&mut latch);
+            cell.set(latch);
+            result
+        })
+    }
+
+    fn press(&self) {
+        let now = Instant::now().as_millis();
+        self.update(|latch| latch.press(now));
+    }
+
+    fn take(&self) -> bool {
+        let now = Instant::now().as_millis();
+        self.update(|latch| latch.take(now))
+    }
+
+    fn flush(&self) {
+        self.update(PressLatch::flush);
+    }
+}
+
+// Shared latches accessible from multiple tasks
+pub static JOYSTICK_BUTTON_LATCH: ButtonLatch = ButtonLatch::new();
+pub static BUTTON_A_LATCH: ButtonLatch = ButtonLatch::new();
+pub static BUTTON_B_LATCH: ButtonLatch = ButtonLatch::new();
 
 // Hardware button wrapper for running in tasks
 pub struct ButtonHardware {
     button: Input<'static>,
-    signal: &'static Signal<CriticalSectionRawMutex, bool>,
+    latch: &'static ButtonLatch,
 }
 
 impl ButtonHardware {
     pub fn new_joystick_button(button: Input<'static>) -> Self {
         Self {
             button,
-            signal: &JOYSTICK_BUTTON_SIGNAL,
+            latch: &JOYSTICK_BUTTON_LATCH,
         }
     }
 
     pub fn new_button_a(button: Input<'static>) -> Self {
         Self {
             button,
-            signal: &BUTTON_A_SIGNAL,
+            latch: &BUTTON_A_LATCH,
         }
     }
 
     pub fn new_button_b(button: Input<'static>) -> Self {
         Self {
             button,
-            signal: &BUTTON_B_SIGNAL,
+            latch: &BUTTON_B_LATCH,
         }
     }
 
@@ -42,8 +82,8 @@ impl ButtonHardware {
             // Wait for falling edge interrupt (button press)
             self.button.wait_for_falling_edge().await;
 
-            // Signal that button was pressed
-            self.signal.signal(true);
+            // Latch the press for the game loop
+            self.latch.press();
 
             // Debounce delay
             embassy_time::Timer::after_millis(200).await;
@@ -106,36 +146,126 @@ impl<'a> Joystick<'a> {
     }
 }
 
-// Main game controller that uses shared signals
+// A cached sample stays valid for this many sampling periods
+const SAMPLE_MAX_AGE_PERIODS: u64 = 4;
+
+// Latest joystick position shared between the sampler task and the game
+pub struct SharedJoystick(Mutex<CriticalSectionRawMutex, Cell<JoystickCache>>);
+
+impl SharedJoystick {
+    const fn new() -> Self {
+        Self(Mutex::new(Cell::new(JoystickCache::new(0))))
+    }
+
+    fn reset(&self, max_age_ms: u64) {
+        self.0.lock(|cell| cell.set(JoystickCache::new(max_age_ms)));
+    }
+
+    fn store(&self, x: i8, y: i8) {
+        let now = Instant::now().as_millis();
+        self.0.lock(|cell| {
+            let mut cache = cell.get();
+            cache.store(x, y, now);
+            cell.set(cache);
+        });
+    }
+
+    fn read(&self) -> (i8, i8) {
+        let now = Instant::now().as_millis();
+        self.0.lock(|cell| cell.get().read(now))
+    }
+}
+
+pub static JOYSTICK_SAMPLE: SharedJoystick = SharedJoystick::new();
+
+// Reads the ADC at a fixed rate, off the game loop
+pub struct JoystickSampler {
+    joystick: Joystick<'static>,
+    period_ms: u64,
+}
+
+impl JoystickSampler {
+    pub fn new(joystick: Joystick<'static>, period_ms: u64) -> Self {
+        JOYSTICK_SAMPLE.reset(period_ms * SAMPLE_MAX_AGE_PERIODS);
+        Self {
+            joystick,
+            period_ms,
+        }
+    }
+
+    pub async fn run(mut self) -> ! {
+        loop {
+            let x = self.joystick.read_x().await;
+            let y = self.joystick.read_y().await;
+            JOYSTICK_SAMPLE.store(x, y);
+            embassy_time::Timer::after_millis(self.period_ms).await;
+        }
+    }
+}
+
+#[embassy_executor::task]
+pub async fn joystick_sampler_task(sampler: JoystickSampler) {
+    sampler.run().await;
+}
+
+enum JoystickSource<'a> {
+    // Blocking ADC read on every call
+    Direct(Joystick<'a>),
+    // Latest value from the sampler task
+    Sampled,
+}
+
+// Main game controller that uses the shared button latches
 pub struct Control<'a> {
-    joystick: Joystick<'a>,
+    joystick: JoystickSource<'a>,
 }
 
 impl<'a> Control<'a> {
     pub fn new(joystick: Joystick<'a>) -> Self {
-        Self { joystick }
+        Self {
+            joystick: JoystickSource::Direct(joystick),
+        }
+    }
+
+    /// Controller reading the position cached by `joystick_sampler_task`
+    pub fn sampled() -> Self {
+        Self {
+            joystick: JoystickSource::Sampled,
+        }
     }
 }
 
 // Implement the GameController trait
 impl<'a> tetris_lib::common::GameController for Control<'a> {
     async fn read_x(&mut self) -> i8 {
-        self.joystick.read_x().await
+        match &mut self.joystick {
+            JoystickSource::Direct(joystick) => joystick.read_x().await,
+            JoystickSource::Sampled => JOYSTICK_SAMPLE.read().0,
+        }
     }
 
     async fn read_y(&mut self) -> i8 {
-        self.joystick.read_y().await
+        match &mut self.joystick {
+            JoystickSource::Direct(joystick) => joystick.read_y().await,
+            JoystickSource::Sampled => JOYSTICK_SAMPLE.read().1,
+        }
     }
 
     fn joystick_was_pressed(&self) -> bool {
-        JOYSTICK_BUTTON_SIGNAL.try_take().unwrap_or(false)
+        JOYSTICK_BUTTON_LATCH.take()
     }
 
     fn a_was_pressed(&self) -> bool {
-        BUTTON_A_SIGNAL.try_take().unwrap_or(false)
+        BUTTON_A_LATCH.take()
     }
 
     fn b_was_pressed(&self) -> bool {
-        BUTTON_B_SIGNAL.try_take().unwrap_or(false)
+        BUTTON_B_LATCH.take()
+    }
+
+    fn flush_presses(&self) {
+        JOYSTICK_BUTTON_LATCH.flush();
+        BUTTON_A_LATCH.flush();
+        BUTTON_B_LATCH.flush();
     }
 }
@@ -2,7 +2,8 @@
 #![no_main]
 
 use crate::control::{
-    button_a_task, button_b_task, joystick_button_task, ButtonHardware, Control, Joystick,
+    button_a_task, button_b_task, joystick_button_task, joystick_sampler_task, ButtonHardware,
+    Control, Joystick, JoystickSampler,
 };
 use defmt::*;
 use embassy_executor::Spawner;
@@ -15,12 +16,16 @@ use embassy_rp::pio::{InterruptHandler, Pio};
 use embassy_rp::pio_programs::ws2812::{PioWs2812, PioWs2812Program};
 use embassy_time::{Instant, Timer as EmbassyTimer};
 use smart_leds::RGB8;
-use tetris_lib::common::{LedDisplay, Timer};
-use tetris_lib::games::run_game_menu;
+use tetris_lib::common::{LedDisplay, SkipUnchanged, Timer};
+use tetris_lib::games::{run_game_menu, run_self_test};
 use {defmt_rtt as _, panic_probe as _};
 
 mod control;
 
+// Sample the joystick ADC on its own task every this many milliseconds so
+// the game loop only reads a cached value. `None` reads the ADC every frame.
+const JOYSTICK_SAMPLE_MS: Option<u64> = None;
+
 // Embedded timer implementation
 pub struct EmbeddedTimer;
 
@@ -63,7 +68,8 @@ async fn main(spawner: Spawner) {
 
     let program = PioWs2812Program::new(&mut common);
     let ws2812 = PioWs2812::new(&mut common, sm0, p.DMA_CH0, p.PIN_13, &program);
-    let mut display = Ws2812Display::new(ws2812);
+    // Only push frames that changed, the WS2812 transfer is the slow part
+    let mut display = SkipUnchanged::new(Ws2812Display::new(ws2812));
 
     // Hardware setup
     let adc_reader = Adc::new(p.ADC, Irqs, Config::default());
@@ -72,13 +78,20 @@ async fn main(spawner: Spawner) {
     let joystick_push_pin = Input::new(p.PIN_16, Pull::Up);
     let button_a_pin = Input::new(p.PIN_0, Pull::Up);
     let button_b_pin = Input::new(p.PIN_1, Pull::Up);
+    let timer = EmbeddedTimer;
+
+    // Holding A while powering up runs the LED self-test
+    if button_a_pin.is_low() {
+        info!("Running LED self-test");
+        run_self_test(&mut display, &timer).await;
+    }
 
     // Create hardware button controllers
     let joystick_button_hw = ButtonHardware::new_joystick_button(joystick_push_pin);
     let button_a_hw = ButtonHardware::new_button_a(button_a_pin);
     let button_b_hw = ButtonHardware::new_button_b(button_b_pin);
 
-    // Spawn button tasks - these will run independently and signal through static signals
+    // Spawn button tasks - these will run independently and latch presses in static latches
     spawner
         .spawn(joystick_button_task(joystick_button_hw))
         .unwrap();
@@ -87,8 +100,14 @@ async fn main(spawner: Spawner) {
 
     // Create game controller (no longer needs to own button hardware)
     let joystick = Joystick::new(adc_reader, adc_pin_x, adc_pin_y);
-    let mut control = Control::new(joystick);
-    let timer = EmbeddedTimer;
+    let mut control = match JOYSTICK_SAMPLE_MS {
+        Some(period_ms) => {
+            let sampler = JoystickSampler::new(joystick, period_ms);
+            spawner.spawn(joystick_sampler_task(sampler)).unwrap();
+            Control::sampled()
+        }
+        None => Control::new(joystick),
+    };
 
     info!("Starting main menu loop");
     run_game_menu(&mut display, &mut control, &timer, || {
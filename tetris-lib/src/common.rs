@@ -1,9 +1,28 @@
-use crate::figure::Figure;
+use crate::digits::DIGITS;
+use crate::figure::{Figure, HEART};
+use crate::font::{glyph, GLYPH_WIDTH};
+use core::cell::Cell;
+use core::sync::atomic::{AtomicBool, AtomicU16, AtomicU32, Ordering};
 use smart_leds::RGB8;
 
-pub const SCREEN_WIDTH: usize = 8;
-pub const SCREEN_HEIGHT: usize = 32;
-pub const SCREEN_SIZE: usize = SCREEN_WIDTH * SCREEN_HEIGHT;
+use crate::coords::{in_bounds, led_index, screen_index};
+pub use crate::coords::{SCREEN_HEIGHT, SCREEN_SIZE, SCREEN_WIDTH};
+
+/// Time the HUD shows the score, then the lives, in turn
+pub const HUD_SWAP_MS: u64 = 2000;
+/// Colors of the HUD score digits and lives hearts in every game
+pub const HUD_SCORE_COLOR: u8 = GREEN_IDX;
+pub const HUD_LIVES_COLOR: u8 = RED_IDX;
+/// Most lives a game can start with
+pub const MAX_LIVES: u8 = Difficulty::Easy.lives();
+/// Top-left corners of the lives hearts when they take the place of the
+/// score, one for each of `MAX_LIVES`. Rows of hearts arm to arm, the last
+/// one in a row clipped at the edge.
+pub const HUD_HEART_SLOTS: [(i8, i8); MAX_LIVES as usize] = hud_heart_slots();
+/// Frames per column a score too long for the HUD scrolls by
+pub const SCORE_SCROLL_FRAMES: u16 = 2;
+/// Row between the HUD and the playfield
+pub const HUD_SEPARATOR_Y: usize = 5;
 
 // Colors matching the Python version
 pub const BLACK: RGB8 = RGB8::new(0, 0, 0);
@@ -16,6 +35,9 @@ pub const PINK: RGB8 = RGB8::new(3, 0, 3);
 pub const YELLOW: RGB8 = RGB8::new(6, 6, 0);
 pub const DARK_GREEN: RGB8 = RGB8::new(0, 3, 0);
 pub const LIGHT_GREEN: RGB8 = RGB8::new(0, 9, 0);
+pub const PURPLE: RGB8 = RGB8::new(4, 0, 8);
+pub const ORANGE: RGB8 = RGB8::new(9, 3, 0);
+pub const GREY: RGB8 = RGB8::new(2, 2, 2);
 
 // Color indices
 pub const BLACK_IDX: u8 = 0;
@@ -28,8 +50,11 @@ pub const PINK_IDX: u8 = 6;
 pub const YELLOW_IDX: u8 = 7;
 pub const DARK_GREEN_IDX: u8 = 8;
 pub const LIGHT_GREEN_IDX: u8 = 9;
+pub const PURPLE_IDX: u8 = 10;
+pub const ORANGE_IDX: u8 = 11;
+pub const GREY_IDX: u8 = 12;
 
-pub type ColorsType = [RGB8; 10];
+pub type ColorsType = [RGB8; 13];
 pub const COLORS: ColorsType = [
     BLACK,
     BRICK,
@@ -41,8 +66,110 @@ pub const COLORS: ColorsType = [
     YELLOW,
     DARK_GREEN,
     LIGHT_GREEN,
+    PURPLE,
+    ORANGE,
+    GREY,
 ];
 
+// Hue rotation applied to the palette when rendering, in degrees
+static HUE_SHIFT: AtomicU16 = AtomicU16::new(0);
+// `COLORS` rotated by `HUE_SHIFT`, packed as 0xRRGGBB. Worked out once when
+// the shift changes so rendering only reads it.
+static ACTIVE_COLORS: [AtomicU32; COLORS.len()] = packed_palette(COLORS);
+
+const fn pack_color(color: RGB8) -> u32 {
+    (color.r as u32) << 16 | (color.g as u32) << 8 | color.b as u32
+}
+
+fn unpack_color(packed: u32) -> RGB8 {
+    RGB8::new((packed >> 16) as u8, (packed >> 8) as u8, packed as u8)
+}
+
+const fn packed_palette(palette: ColorsType) -> [AtomicU32; COLORS.len()] {
+    let mut packed = [const { AtomicU32::new(0) }; COLORS.len()];
+    let mut idx = 0;
+    while idx < palette.len() {
+        packed[idx] = AtomicU32::new(pack_color(palette[idx]));
+        idx += 1;
+    }
+    packed
+}
+
+/// Rotate the whole palette around the color wheel
+pub fn set_hue_shift(degrees: u16) {
+    let degrees = degrees % 360;
+    HUE_SHIFT.store(degrees, Ordering::Relaxed);
+    for (slot, color) in ACTIVE_COLORS.iter().zip(shifted_palette(degrees)) {
+        slot.store(pack_color(color), Ordering::Relaxed);
+    }
+}
+
+pub fn hue_shift() -> u16 {
+    HUE_SHIFT.load(Ordering::Relaxed)
+}
+
+/// Palette used for rendering: `COLORS` rotated by the current hue shift
+pub fn active_colors() -> ColorsType {
+    core::array::from_fn(|idx| active_color(idx as u8))
+}
+
+fn active_color(idx: u8) -> RGB8 {
+    unpack_color(ACTIVE_COLORS[idx as usize % ACTIVE_COLORS.len()].load(Ordering::Relaxed))
+}
+
+pub fn shifted_palette(degrees: u16) -> ColorsType {
+    let mut palette = COLORS;
+    if !degrees.is_multiple_of(360) {
+        for color in palette.iter_mut() {
+            *color = rotate_hue(*color, degrees);
+        }
+    }
+    palette
+}
+
+// Hue is kept in 1/256 fractions of a 60 degree sector to stay precise for
+// the dim palette values without floating point
+const HUE_SECTOR: i32 = 256;
+const HUE_CIRCLE: i32 = HUE_SECTOR * 6;
+
+fn div_round(num: i32, den: i32) -> i32 {
+    (num + den / 2).div_euclid(den)
+}
+
+/// Rotate the hue of `color` keeping its saturation and brightness
+pub fn rotate_hue(color: RGB8, degrees: u16) -> RGB8 {
+    let (r, g, b) = (color.r as i32, color.g as i32, color.b as i32);
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let chroma = max - min;
+    if chroma == 0 || degrees.is_multiple_of(360) {
+        return color;
+    }
+
+    let hue = if max == r {
+        div_round(HUE_SECTOR * (g - b), chroma)
+    } else if max == g {
+        2 * HUE_SECTOR + div_round(HUE_SECTOR * (b - r), chroma)
+    } else {
+        4 * HUE_SECTOR + div_round(HUE_SECTOR * (r - g), chroma)
+    };
+    let shift = div_round((degrees % 360) as i32 * HUE_CIRCLE, 360);
+    let hue = (hue + shift).rem_euclid(HUE_CIRCLE);
+
+    let fraction = hue % HUE_SECTOR;
+    let rising = div_round(chroma * fraction, HUE_SECTOR);
+    let falling = chroma - rising;
+    let (r, g, b) = match hue / HUE_SECTOR {
+        0 => (chroma, rising, 0),
+        1 => (falling, chroma, 0),
+        2 => (0, chroma, rising),
+        3 => (0, falling, chroma),
+        4 => (rising, 0, chroma),
+        _ => (chroma, 0, falling),
+    };
+    RGB8::new((r + min) as u8, (g + min) as u8, (b + min) as u8)
+}
+
 trait ColorsIndexer {
     fn at(&self, idx: u8) -> RGB8;
 }
@@ -83,17 +210,46 @@ impl Prng {
         }
         self.next() % max
     }
+
+    pub fn next_range_u16(&mut self, max: u16) -> u16 {
+        if max == 0 {
+            return 0;
+        }
+        let value = (self.next() as u16) << 8 | self.next() as u16;
+        value % max
+    }
+}
+
+/// Random source used by the games. Implemented by `Prng`; tests and mods
+/// can plug in their own generator.
+pub trait Rng {
+    /// Value in `0..max`, 0 when `max` is 0
+    fn next_range(&mut self, max: u8) -> u8;
+    fn next_range_u16(&mut self, max: u16) -> u16;
+    fn next_bool(&mut self) -> bool {
+        self.next_range(2) == 1
+    }
+}
+
+impl Rng for Prng {
+    fn next_range(&mut self, max: u8) -> u8 {
+        Prng::next_range(self, max)
+    }
+
+    fn next_range_u16(&mut self, max: u16) -> u16 {
+        Prng::next_range_u16(self, max)
+    }
 }
 
 // Point/Dot structure for coordinates
-#[derive(Copy, Clone, PartialEq)]
+#[derive(Copy, Clone, PartialEq, Debug)]
 pub struct Dot {
     pub x: i8,
     pub y: i8,
 }
 
 impl Dot {
-    pub fn new(x: i8, y: i8) -> Self {
+    pub const fn new(x: i8, y: i8) -> Self {
         Self { x, y }
     }
 
@@ -132,7 +288,17 @@ impl Dot {
     }
 
     pub fn _outside(&self) -> bool {
-        self.x < 0 || self.x >= SCREEN_WIDTH as i8 || self.y < 0 || self.y >= SCREEN_HEIGHT as i8
+        !self.in_bounds()
+    }
+
+    /// True if the dot lies on the screen
+    pub fn in_bounds(&self) -> bool {
+        in_bounds(self.x, self.y)
+    }
+
+    /// Each coordinate limited to the range from `min` to `max`, inclusive
+    pub fn clamped(&self, min: Dot, max: Dot) -> Dot {
+        Dot::new(self.x.clamp(min.x, max.x), self.y.clamp(min.y, max.y))
     }
 
     pub fn to_direction(mut self) -> Dot {
@@ -143,31 +309,185 @@ impl Dot {
     }
 }
 
+/// Palette index of the color closest to `rgb`.
+///
+/// Exact palette colors map to their own index, anything else (dimmed,
+/// gamma corrected or otherwise perturbed values) maps to the palette entry
+/// with the smallest squared RGB distance. Ties resolve to the lower index.
+pub fn nearest_index(rgb: RGB8) -> u8 {
+    nearest_index_in(&COLORS, rgb)
+}
+
+/// Same as `nearest_index` but against an arbitrary palette
+pub fn nearest_index_in(palette: &ColorsType, rgb: RGB8) -> u8 {
+    let mut best_idx = 0;
+    let mut best_distance = u32::MAX;
+    for (i, color) in palette.iter().enumerate() {
+        let dr = color.r.abs_diff(rgb.r) as u32;
+        let dg = color.g.abs_diff(rgb.g) as u32;
+        let db = color.b.abs_diff(rgb.b) as u32;
+        let distance = dr * dr + dg * dg + db * db;
+        if distance < best_distance {
+            best_distance = distance;
+            best_idx = i as u8;
+            if distance == 0 {
+                break;
+            }
+        }
+    }
+    best_idx
+}
+
 pub fn get_pixel(leds: &[RGB8], x: usize, y: usize) -> u8 {
-    let mut x = x;
-    if y % 2 == 0 {
-        x = 7 - x;
+    nearest_index_in(&active_colors(), leds[led_index(x, y)])
+}
+
+pub fn set_pixel(leds: &mut [RGB8], x: usize, y: usize, color_idx: u8) {
+    set_pixel_with(leds, x, y, active_color(color_idx));
+}
+
+fn set_pixel_with(leds: &mut [RGB8], x: usize, y: usize, color: RGB8) {
+    if x < SCREEN_WIDTH && y < SCREEN_HEIGHT {
+        leds[led_index(x, y)] = color;
+    }
+}
+
+/// Square wave for blinking driven by a frame counter: true for `period`
+/// ticks, then false for `period` ticks. A zero period is always true.
+pub fn blink(counter: u16, period: u16) -> bool {
+    counter
+        .checked_div(period)
+        .is_none_or(|phase| phase.is_multiple_of(2))
+}
+
+/// Next dimmer palette index, fading a color out one step at a time.
+/// Every chain ends in `BLACK_IDX`.
+pub fn fade(color: u8) -> u8 {
+    match color {
+        LIGHT_GREEN_IDX | YELLOW_IDX => GREEN_IDX,
+        GREEN_IDX => DARK_GREEN_IDX,
+        BRICK_IDX | ORANGE_IDX => RED_IDX,
+        LIGHT_BLUE_IDX => BLUE_IDX,
+        PURPLE_IDX => PINK_IDX,
+        _ => BLACK_IDX,
+    }
+}
+
+const fn hud_heart_slots() -> [(i8, i8); MAX_LIVES as usize] {
+    let pitch = HEART.width() as usize;
+    let per_row = SCREEN_WIDTH.div_ceil(pitch);
+    let mut slots = [(0, 0); MAX_LIVES as usize];
+    let mut idx = 0;
+    while idx < slots.len() {
+        let x = idx % per_row * pitch;
+        let y = idx / per_row * (HEART.height() as usize + 1);
+        slots[idx] = (x as i8, y as i8);
+        idx += 1;
     }
-    let idx = SCREEN_WIDTH * y + x;
-    for (i, color) in COLORS.iter().enumerate() {
-        if color == &leds[idx] {
-            return i as u8;
+    slots
+}
+
+/// Frames of `frame_ms` the HUD shows the score or the lives for, see
+/// `HUD_SWAP_MS`
+pub const fn hud_swap_frames(frame_ms: u64) -> u16 {
+    let frames = HUD_SWAP_MS / if frame_ms == 0 { 1 } else { frame_ms };
+    if frames > u16::MAX as u64 {
+        u16::MAX
+    } else if frames == 0 {
+        1
+    } else {
+        frames as u16
+    }
+}
+
+/// Backdrop shown in the cells a game leaves empty
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Background {
+    #[default]
+    None,
+    /// Every other cell lit with the given color
+    Checkerboard(u8),
+    /// Screen edges lit with the given color
+    Border(u8),
+}
+
+impl Background {
+    pub fn color_at(&self, x: usize, y: usize) -> u8 {
+        match *self {
+            Background::None => BLACK_IDX,
+            Background::Checkerboard(color) if (x + y).is_multiple_of(2) => color,
+            Background::Border(color)
+                if x == 0 || y == 0 || x == SCREEN_WIDTH - 1 || y == SCREEN_HEIGHT - 1 =>
+            {
+                color
+            }
+            _ => BLACK_IDX,
         }
     }
-    0
 }
 
-pub fn set_pixel(leds: &mut [RGB8], x: usize, y: usize, color_idx: u8) {
-    let mut x = x;
-    if y % 2 == 0 {
-        x = 7 - x;
+/// Accessibility setting for players sensitive to flashing and moving
+/// effects
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Motion {
+    #[default]
+    Full,
+    /// Game-over flashes, invulnerability blinks and screen shakes are
+    /// turned off, the screen stays steady instead
+    Reduced,
+}
+
+impl Motion {
+    pub fn is_reduced(self) -> bool {
+        self == Motion::Reduced
+    }
+
+    /// `shake` as configured with full motion, disabled when reduced
+    pub fn shake(self, shake: ShakeConfig) -> ShakeConfig {
+        if self.is_reduced() {
+            ShakeConfig::default()
+        } else {
+            shake
+        }
+    }
+}
+
+/// Line drawn between the HUD and the playfield
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HudSeparator {
+    None,
+    /// The whole row lit with the given color
+    Solid(u8),
+    /// Every other cell lit with the given color, starting at the left edge
+    Dotted(u8),
+}
+
+impl Default for HudSeparator {
+    fn default() -> Self {
+        HudSeparator::Solid(PINK_IDX)
     }
-    let idx = SCREEN_WIDTH * y + x;
-    if idx < leds.len() {
-        leds[idx] = COLORS.at(color_idx);
+}
+
+/// Difficulty preset, sets how many lives a game starts with
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub const fn lives(self) -> u8 {
+        match self {
+            Difficulty::Easy => 5,
+            Difficulty::Normal => 3,
+            Difficulty::Hard => 1,
+        }
     }
 }
 
+#[derive(Clone, PartialEq)]
 pub struct FrameBuffer {
     content: [u8; SCREEN_SIZE],
 }
@@ -183,6 +503,10 @@ impl FrameBuffer {
         self.content.fill(0);
     }
 
+    pub fn fill(&mut self, color: u8) {
+        self.content.fill(color);
+    }
+
     pub fn clear_range(&mut self, from: usize, to: usize) {
         for idx in from..to.min(SCREEN_SIZE) {
             self.content[idx] = 0;
@@ -191,34 +515,42 @@ impl FrameBuffer {
 
     pub fn set(&mut self, x: usize, y: usize, color: u8) {
         if x < SCREEN_WIDTH && y < SCREEN_HEIGHT {
-            let idx = y * SCREEN_WIDTH + x;
-            self.content[idx] = color;
+            self.content[screen_index(x, y)] = color;
         }
     }
 
     pub fn get(&self, x: usize, y: usize) -> u8 {
         if x < SCREEN_WIDTH && y < SCREEN_HEIGHT {
-            self.content[y * SCREEN_WIDTH + x]
+            self.content[screen_index(x, y)]
         } else {
             0
         }
     }
 
     fn available(&self, x: i8, y: i8, color: u8) -> bool {
-        if x >= 0 && x < SCREEN_WIDTH as i8 && y >= 0 && y < SCREEN_HEIGHT as i8 {
-            self.content[y as usize * SCREEN_WIDTH + x as usize] == color
+        if in_bounds(x, y) {
+            self.content[screen_index(x as usize, y as usize)] == color
         } else {
             false
         }
     }
 
     pub fn collides(&self, x: i8, y: i8, figure: &Figure) -> bool {
-        for row in 0..figure.height() {
-            for col in 0..figure.width() {
-                if figure.get_bit(col, row)
-                    && !self.available(x + col as i8, y + row as i8, BLACK_IDX)
-                {
-                    return true;
+        self.collides_scaled(x, y, figure, 1)
+    }
+
+    /// Collision check with every figure cell covering a `scale` x `scale`
+    /// block of pixels
+    pub fn collides_scaled(&self, x: i8, y: i8, figure: &Figure, scale: i8) -> bool {
+        for (col, row) in figure.cells() {
+            let (col, row) = (col as i8, row as i8);
+            for dy in 0..scale {
+                for dx in 0..scale {
+                    let px = x + col * scale + dx;
+                    let py = y + row * scale + dy;
+                    if !self.available(px, py, BLACK_IDX) {
+                        return true;
+                    }
                 }
             }
         }
@@ -226,12 +558,18 @@ impl FrameBuffer {
     }
 
     pub fn draw_figure(&mut self, x: i8, y: i8, figure: &Figure, color: u8) {
-        for row in 0..figure.height() {
-            for col in 0..figure.width() {
-                if figure.get_bit(col, row) {
-                    let px = x + col as i8;
-                    let py = y + row as i8;
-                    if px >= 0 && px < SCREEN_WIDTH as i8 && py >= 0 && py < SCREEN_HEIGHT as i8 {
+        self.draw_figure_scaled(x, y, figure, color, 1);
+    }
+
+    /// Draw with every figure cell covering a `scale` x `scale` block of pixels
+    pub fn draw_figure_scaled(&mut self, x: i8, y: i8, figure: &Figure, color: u8, scale: i8) {
+        for (col, row) in figure.cells() {
+            let (col, row) = (col as i8, row as i8);
+            for dy in 0..scale {
+                for dx in 0..scale {
+                    let px = x + col * scale + dx;
+                    let py = y + row * scale + dy;
+                    if in_bounds(px, py) {
                         self.set(px as usize, py as usize, color);
                     }
                 }
@@ -239,15 +577,260 @@ impl FrameBuffer {
         }
     }
 
+    /// Draw `text` from the top left corner at (`x`, `y`), one column
+    /// between letters. Characters the font has no glyph for are left blank,
+    /// anything off the screen is clipped.
+    pub fn draw_text(&mut self, x: i8, y: i8, text: &str, color: u8) {
+        let advance = i16::from(GLYPH_WIDTH) + 1;
+        let mut left = i16::from(x);
+        for c in text.chars() {
+            if left >= SCREEN_WIDTH as i16 {
+                break;
+            }
+            if let Some(glyph) = glyph(c) {
+                self.draw_figure(left as i8, y, &glyph, color);
+            }
+            left += advance;
+        }
+    }
+
+    /// Draw a straight line from `a` to `b`, both ends included, with
+    /// Bresenham's algorithm. Points off the screen are clipped.
+    pub fn draw_line(&mut self, a: Dot, b: Dot, color: u8) {
+        // Wide enough for any two i8 points to be apart
+        let (mut x, mut y) = (i16::from(a.x), i16::from(a.y));
+        let (end_x, end_y) = (i16::from(b.x), i16::from(b.y));
+        let dx = (end_x - x).abs();
+        let dy = -(end_y - y).abs();
+        let step_x = if x < end_x { 1 } else { -1 };
+        let step_y = if y < end_y { 1 } else { -1 };
+        let mut error = dx + dy;
+        loop {
+            if (0..SCREEN_WIDTH as i16).contains(&x) && (0..SCREEN_HEIGHT as i16).contains(&y) {
+                self.set(x as usize, y as usize, color);
+            }
+            if x == end_x && y == end_y {
+                break;
+            }
+            let doubled = 2 * error;
+            if doubled >= dy {
+                error += dy;
+                x += step_x;
+            }
+            if doubled <= dx {
+                error += dx;
+                y += step_y;
+            }
+        }
+    }
+
+    /// Fill the `w` x `h` rectangle with its top left corner at (x, y).
+    /// Cells off the screen are clipped, an empty rectangle draws nothing.
+    pub fn fill_rect(&mut self, x: i8, y: i8, w: u8, h: u8, color: u8) {
+        let (left, top) = (i16::from(x), i16::from(y));
+        let right = (left + i16::from(w)).min(SCREEN_WIDTH as i16);
+        let bottom = (top + i16::from(h)).min(SCREEN_HEIGHT as i16);
+        for py in top.max(0)..bottom {
+            for px in left.max(0)..right {
+                self.set(px as usize, py as usize, color);
+            }
+        }
+    }
+
+    /// Outline of the rectangle `fill_rect` fills, one cell thick
+    pub fn draw_rect(&mut self, x: i8, y: i8, w: u8, h: u8, color: u8) {
+        if w == 0 || h == 0 {
+            return;
+        }
+        let (right, bottom) = (x as i16 + w as i16 - 1, y as i16 + h as i16 - 1);
+        // Edges past the i8 range are off the screen anyway
+        let right = right.min(i8::MAX as i16) as i8;
+        let bottom = bottom.min(i8::MAX as i16) as i8;
+        self.draw_line(Dot::new(x, y), Dot::new(right, y), color);
+        self.draw_line(Dot::new(x, bottom), Dot::new(right, bottom), color);
+        self.draw_line(Dot::new(x, y), Dot::new(x, bottom), color);
+        self.draw_line(Dot::new(right, y), Dot::new(right, bottom), color);
+    }
+
+    /// Draw one heart per life left to right from (x, y), arm to arm.
+    /// Hearts past the screen edge are clipped.
+    pub fn draw_lives(&mut self, count: u8, x: i8, y: i8, color: u8) {
+        let mut heart_x = x;
+        for _ in 0..count {
+            if heart_x >= SCREEN_WIDTH as i8 {
+                break;
+            }
+            self.draw_figure(heart_x, y, &HEART, color);
+            heart_x += HEART.width() as i8;
+        }
+    }
+
+    /// Draw a heart per life at `slots`. With more lives than slots a
+    /// heart and the count show instead, the count in the right digit slot.
+    pub fn draw_hud_lives(&mut self, lives: u8, slots: &[(i8, i8)], color: u8) {
+        if usize::from(lives) <= slots.len() {
+            for &(x, y) in &slots[..usize::from(lives)] {
+                self.draw_figure(x, y, &HEART, color);
+            }
+            return;
+        }
+        let (x, y) = slots.first().copied().unwrap_or((0, 0));
+        self.draw_figure(x, y, &HEART, color);
+        self.draw_figure(5, 0, DIGITS.wrapping_at(lives.min(9)), color);
+    }
+
+    /// Draw `score` in the HUD rows at the top. Up to 99 it takes the usual
+    /// two digit slots, longer scores scroll by one column every
+    /// `SCORE_SCROLL_FRAMES` of `frame`, with a blank gap between rounds.
+    pub fn draw_hud_score(&mut self, score: u16, frame: u16, color: u8) {
+        if score < 100 {
+            self.draw_figure(0, 0, &DIGITS[usize::from(score / 10)], color);
+            self.draw_figure(4, 0, &DIGITS[usize::from(score % 10)], color);
+            return;
+        }
+
+        let mut digits = [0u8; 5];
+        let mut len = 0;
+        let mut rest = score;
+        while rest > 0 {
+            digits[len] = (rest % 10) as u8;
+            rest /= 10;
+            len += 1;
+        }
+        digits[..len].reverse();
+
+        // A digit and the column after it
+        let advance: i16 = 4;
+        let period = advance * (len as i16 + 1);
+        let offset = (frame / SCORE_SCROLL_FRAMES) as i16 % period;
+        for round in [0, period] {
+            for (idx, &digit) in digits[..len].iter().enumerate() {
+                let x = advance * idx as i16 + round - offset;
+                if (-advance..SCREEN_WIDTH as i16).contains(&x) {
+                    self.draw_figure(x as i8, 0, DIGITS.wrapping_at(digit), color);
+                }
+            }
+        }
+    }
+
+    /// Draw `separator` across `row`, cells it leaves dark are untouched
+    pub fn draw_hud_separator(&mut self, row: usize, separator: HudSeparator) {
+        let (color, step) = match separator {
+            HudSeparator::None => return,
+            HudSeparator::Solid(color) => (color, 1),
+            HudSeparator::Dotted(color) => (color, 2),
+        };
+        for x in (0..SCREEN_WIDTH).step_by(step) {
+            self.set(x, row, color);
+        }
+    }
+
+    /// FNV-1a hash of the content, a compact signature of the frame
+    pub fn checksum(&self) -> u32 {
+        self.content.iter().fold(0x811c_9dc5, |hash: u32, &color| {
+            (hash ^ color as u32).wrapping_mul(0x0100_0193)
+        })
+    }
+
     pub fn copy_from(&mut self, other: &FrameBuffer) {
         self.content.copy_from_slice(&other.content);
     }
 
+    /// Move the content up by `rows`, the rows left behind at the bottom
+    /// get `fill`. Shifting by the screen height or more fills everything.
+    pub fn shift_up(&mut self, rows: usize, fill: u8) {
+        self.shift(0, -(rows.min(SCREEN_HEIGHT) as isize), fill);
+    }
+
+    /// Move the content down by `rows`, see `shift_up`
+    pub fn shift_down(&mut self, rows: usize, fill: u8) {
+        self.shift(0, rows.min(SCREEN_HEIGHT) as isize, fill);
+    }
+
+    /// Move the content left by `columns`, see `shift_up`
+    pub fn shift_left(&mut self, columns: usize, fill: u8) {
+        self.shift(-(columns.min(SCREEN_WIDTH) as isize), 0, fill);
+    }
+
+    /// Move the content right by `columns`, see `shift_up`
+    pub fn shift_right(&mut self, columns: usize, fill: u8) {
+        self.shift(columns.min(SCREEN_WIDTH) as isize, 0, fill);
+    }
+
+    /// Mirror the content left to right
+    pub fn flip_horizontal(&mut self) {
+        for row in self.content.chunks_exact_mut(SCREEN_WIDTH) {
+            row.reverse();
+        }
+    }
+
+    /// Mirror the content top to bottom
+    pub fn flip_vertical(&mut self) {
+        for y in 0..SCREEN_HEIGHT / 2 {
+            for x in 0..SCREEN_WIDTH {
+                self.content
+                    .swap(screen_index(x, y), screen_index(x, SCREEN_HEIGHT - 1 - y));
+            }
+        }
+    }
+
+    fn shift(&mut self, dx: isize, dy: isize, fill: u8) {
+        let source = self.clone();
+        for y in 0..SCREEN_HEIGHT {
+            for x in 0..SCREEN_WIDTH {
+                let (from_x, from_y) = (x as isize - dx, y as isize - dy);
+                let inside = (0..SCREEN_WIDTH as isize).contains(&from_x)
+                    && (0..SCREEN_HEIGHT as isize).contains(&from_y);
+                let color = if inside {
+                    source.get(from_x as usize, from_y as usize)
+                } else {
+                    fill
+                };
+                self.set(x, y, color);
+            }
+        }
+    }
+
     pub fn render(&self, leds: &mut [RGB8]) {
+        self.render_with(leds, &active_colors());
+    }
+
+    pub fn render_with(&self, leds: &mut [RGB8], palette: &ColorsType) {
         for (idx, &color_idx) in self.content.iter().enumerate() {
             let x = idx % SCREEN_WIDTH;
             let y = idx / SCREEN_WIDTH;
-            set_pixel(leds, x, y, color_idx);
+            set_pixel_with(leds, x, y, palette.at(color_idx));
+        }
+    }
+
+    /// Render shifted by `offset`, cells uncovered by the shift stay black
+    pub fn render_offset(&self, leds: &mut [RGB8], offset: Dot) {
+        let palette = active_colors();
+        for y in 0..SCREEN_HEIGHT {
+            for x in 0..SCREEN_WIDTH {
+                let source = Dot::new(x as i8 - offset.x, y as i8 - offset.y);
+                let color_idx = if source.in_bounds() {
+                    self.get(source.x as usize, source.y as usize)
+                } else {
+                    BLACK_IDX
+                };
+                set_pixel_with(leds, x, y, palette.at(color_idx));
+            }
+        }
+    }
+
+    /// Render with `background` composited behind the empty cells
+    pub fn render_over(&self, leds: &mut [RGB8], background: Background) {
+        let palette = active_colors();
+        for (idx, &color_idx) in self.content.iter().enumerate() {
+            let x = idx % SCREEN_WIDTH;
+            let y = idx / SCREEN_WIDTH;
+            let color_idx = if color_idx == BLACK_IDX {
+                background.color_at(x, y)
+            } else {
+                color_idx
+            };
+            set_pixel_with(leds, x, y, palette.at(color_idx));
         }
     }
 
@@ -283,6 +866,21 @@ impl FrameBuffer {
         true
     }
 
+    /// Row packed into one byte, bit 7 is x = 0. Any color is a set bit.
+    pub fn row_bits(&self, row: usize) -> u8 {
+        (0..SCREEN_WIDTH).fold(0, |bits, x| {
+            bits << 1 | (self.content[row * SCREEN_WIDTH + x] != 0) as u8
+        })
+    }
+
+    /// Fill a row packed by `row_bits`, set bits get `color`
+    pub fn set_row_bits(&mut self, row: usize, bits: u8, color: u8) {
+        for x in 0..SCREEN_WIDTH {
+            let set = bits & (0x80 >> x) != 0;
+            self.content[row * SCREEN_WIDTH + x] = if set { color } else { BLACK_IDX };
+        }
+    }
+
     pub fn from_rows(rows: &[u32; 8], color: u8) -> FrameBuffer {
         let mut buffer = FrameBuffer::new();
 
@@ -312,6 +910,202 @@ pub trait LedDisplay {
     async fn write(&mut self, leds: &[smart_leds::RGB8; 256]);
 }
 
+/// Render `screen` and write it to `display` once, for splash screens and
+/// other frames that don't keep an LED buffer around
+pub async fn present<D: LedDisplay>(display: &mut D, screen: &FrameBuffer) {
+    let mut leds = [BLACK; SCREEN_SIZE];
+    screen.render(&mut leds);
+    display.write(&leds).await;
+}
+
+/// Display wrapper that drops writes identical to the previous one.
+///
+/// Opt-in for displays where every write costs real I/O. Displays that must
+/// be refreshed every frame (e.g. a window surface) should not be wrapped.
+pub struct SkipUnchanged<D> {
+    inner: D,
+    last: Option<[RGB8; SCREEN_SIZE]>,
+}
+
+impl<D: LedDisplay> SkipUnchanged<D> {
+    pub fn new(inner: D) -> Self {
+        Self { inner, last: None }
+    }
+
+    /// Force the next write through, e.g. after the panel was power cycled
+    pub fn invalidate(&mut self) {
+        self.last = None;
+    }
+}
+
+impl<D: LedDisplay> LedDisplay for SkipUnchanged<D> {
+    async fn write(&mut self, leds: &[RGB8; 256]) {
+        if self.last.as_ref() == Some(leds) {
+            return;
+        }
+        self.last = Some(*leds);
+        self.inner.write(leds).await;
+    }
+}
+
+/// Display wrapper that mirrors every write to a second display, e.g. the
+/// panel and a capture or network sink. `first` is written before `second`
+/// and a write returns once both are done.
+pub struct TeeDisplay<A, B> {
+    first: A,
+    second: B,
+}
+
+impl<A: LedDisplay, B: LedDisplay> TeeDisplay<A, B> {
+    pub fn new(first: A, second: B) -> Self {
+        Self { first, second }
+    }
+
+    /// Unwrap the two displays, e.g. to inspect a capture after the game
+    pub fn into_inner(self) -> (A, B) {
+        (self.first, self.second)
+    }
+}
+
+impl<A: LedDisplay, B: LedDisplay> LedDisplay for TeeDisplay<A, B> {
+    async fn write(&mut self, leds: &[RGB8; 256]) {
+        self.first.write(leds).await;
+        self.second.write(leds).await;
+    }
+}
+
+/// One frame for a monochrome panel: byte `y` holds row `y` packed like
+/// `FrameBuffer::row_bits`, bit 7 is the LED at column 0
+pub type MonoFrame = [u8; SCREEN_HEIGHT];
+
+/// Output of a single-color panel, e.g. an OLED or an e-ink matrix
+pub trait MonoWriter {
+    async fn write_bits(&mut self, bits: &MonoFrame);
+}
+
+/// Display adapter for single-color panels. An LED lights up when its
+/// brightest channel is above `threshold`, so black is always off and with
+/// the default of zero every other color is on. Raise the threshold to
+/// leave dimmed colors like trails and ghosts dark.
+pub struct MonoDisplay<W> {
+    inner: W,
+    threshold: u8,
+}
+
+impl<W: MonoWriter> MonoDisplay<W> {
+    pub fn new(inner: W) -> Self {
+        Self::with_threshold(inner, 0)
+    }
+
+    pub fn with_threshold(inner: W, threshold: u8) -> Self {
+        Self { inner, threshold }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+
+    fn pack(&self, leds: &[RGB8; 256]) -> MonoFrame {
+        let mut bits = [0; SCREEN_HEIGHT];
+        for (y, row) in bits.iter_mut().enumerate() {
+            for x in 0..SCREEN_WIDTH {
+                let led = leds[led_index(x, y)];
+                if led.r.max(led.g).max(led.b) > self.threshold {
+                    *row |= 0x80 >> x;
+                }
+            }
+        }
+        bits
+    }
+}
+
+impl<W: MonoWriter> LedDisplay for MonoDisplay<W> {
+    async fn write(&mut self, leds: &[RGB8; 256]) {
+        let bits = self.pack(leds);
+        self.inner.write_bits(&bits).await;
+    }
+}
+
+/// Focus flag of the host window or app, set from its event handlers
+pub struct Focus(AtomicBool);
+
+impl Focus {
+    pub const fn new() -> Self {
+        Self(AtomicBool::new(true))
+    }
+
+    pub fn set_focused(&self, focused: bool) {
+        self.0.store(focused, Ordering::Relaxed);
+    }
+
+    pub fn is_focused(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+impl Default for Focus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Focus is rechecked this often while paused
+const FOCUS_POLL_MS: u64 = 100;
+
+/// Timer that holds the game loop while `focus` is lost. Every game and the
+/// menu sleep once per frame, so nothing advances until focus returns.
+pub struct PauseOnFocusLoss<'a, T> {
+    inner: T,
+    focus: &'a Focus,
+}
+
+impl<'a, T: Timer> PauseOnFocusLoss<'a, T> {
+    pub fn new(inner: T, focus: &'a Focus) -> Self {
+        Self { inner, focus }
+    }
+}
+
+impl<T: Timer> Timer for PauseOnFocusLoss<'_, T> {
+    async fn sleep_millis(&self, millis: u64) {
+        self.inner.sleep_millis(millis).await;
+        while !self.focus.is_focused() {
+            self.inner.sleep_millis(FOCUS_POLL_MS).await;
+        }
+    }
+}
+
+/// Shortest sleep `MinFrameTimer` lets through by default
+pub const DEFAULT_MIN_FRAME_MILLIS: u64 = 4;
+
+/// Timer that sleeps at least a minimum on every call. Keeps a game loop
+/// from spinning a CPU core on backends where display writes return at once
+/// and a frame asks for little or no sleep. The game logic is unaffected,
+/// only the wall time of such frames grows.
+pub struct MinFrameTimer<T> {
+    inner: T,
+    min_millis: u64,
+}
+
+impl<T: Timer> MinFrameTimer<T> {
+    pub fn new(inner: T) -> Self {
+        Self::with_min_millis(inner, DEFAULT_MIN_FRAME_MILLIS)
+    }
+
+    pub fn with_min_millis(inner: T, min_millis: u64) -> Self {
+        Self { inner, min_millis }
+    }
+
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+}
+
+impl<T: Timer> Timer for MinFrameTimer<T> {
+    async fn sleep_millis(&self, millis: u64) {
+        self.inner.sleep_millis(millis.max(self.min_millis)).await;
+    }
+}
+
 /// Trait for game controller functionality (joystick + button)
 pub trait GameController {
     async fn read_x(&mut self) -> i8;
@@ -319,9 +1113,900 @@ pub trait GameController {
     fn joystick_was_pressed(&self) -> bool;
     fn a_was_pressed(&self) -> bool;
     fn b_was_pressed(&self) -> bool;
+
+    /// True while the joystick button is held down. Controllers that only
+    /// latch presses report false, which leaves hold mechanics disabled.
+    fn joystick_held(&self) -> bool {
+        false
+    }
+
+    /// True if the joystick, A or B was pressed. Consumes all three edges.
+    fn any_pressed(&self) -> bool {
+        self.joystick_was_pressed() | self.a_was_pressed() | self.b_was_pressed()
+    }
+
+    /// Drop presses left over from a previous screen, called between games
+    fn flush_presses(&self) {
+        self.any_pressed();
+    }
+}
+
+/// Controller wrapper that reads each joystick axis as centered, and the
+/// joystick button as released, until the player lets go of it once. A game
+/// launched from the menu thus ignores a direction or press still held from
+/// the menu instead of turning or dropping on its first frame. Presses are
+/// edges consumed by the menu already and pass through.
+pub struct NeutralStart<'a, C> {
+    inner: &'a mut C,
+    x_released: bool,
+    y_released: bool,
+    held_released: Cell<bool>,
+}
+
+impl<'a, C: GameController> NeutralStart<'a, C> {
+    /// Without `grace` every input passes through from the start
+    pub fn new(inner: &'a mut C, grace: bool) -> Self {
+        Self {
+            inner,
+            x_released: !grace,
+            y_released: !grace,
+            held_released: Cell::new(!grace),
+        }
+    }
+}
+
+impl<C: GameController> GameController for NeutralStart<'_, C> {
+    async fn read_x(&mut self) -> i8 {
+        let x = self.inner.read_x().await;
+        self.x_released |= x == 0;
+        if self.x_released {
+            x
+        } else {
+            0
+        }
+    }
+
+    async fn read_y(&mut self) -> i8 {
+        let y = self.inner.read_y().await;
+        self.y_released |= y == 0;
+        if self.y_released {
+            y
+        } else {
+            0
+        }
+    }
+
+    fn joystick_was_pressed(&self) -> bool {
+        self.inner.joystick_was_pressed()
+    }
+
+    fn a_was_pressed(&self) -> bool {
+        self.inner.a_was_pressed()
+    }
+
+    fn b_was_pressed(&self) -> bool {
+        self.inner.b_was_pressed()
+    }
+
+    fn joystick_held(&self) -> bool {
+        let held = self.inner.joystick_held();
+        if !held {
+            self.held_released.set(true);
+        }
+        held && self.held_released.get()
+    }
+}
+
+/// Last `N` positions of a moving entity, newest first, drawn as a trail
+/// that gets one `fade` step dimmer per position
+#[derive(Clone, Copy, Debug)]
+pub struct Trail<const N: usize> {
+    positions: [Dot; N],
+    len: usize,
+}
+
+impl<const N: usize> Trail<N> {
+    pub const fn new() -> Self {
+        Self {
+            positions: [Dot { x: 0, y: 0 }; N],
+            len: 0,
+        }
+    }
+
+    /// Remember a position the entity is leaving, dropping the oldest one
+    pub fn push(&mut self, pos: Dot) {
+        if N == 0 {
+            return;
+        }
+        self.positions.copy_within(0..N - 1, 1);
+        self.positions[0] = pos;
+        self.len = (self.len + 1).min(N);
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    /// Draw the trail of an entity of `color` into the empty cells only,
+    /// so it never covers the scene
+    pub fn draw(&self, screen: &mut FrameBuffer, color: u8) {
+        let mut color = color;
+        for pos in &self.positions[..self.len] {
+            color = fade(color);
+            if color == BLACK_IDX {
+                break;
+            }
+            if in_bounds(pos.x, pos.y) && screen.get(pos.x as usize, pos.y as usize) == BLACK_IDX {
+                screen.set(pos.x as usize, pos.y as usize, color);
+            }
+        }
+    }
+}
+
+impl<const N: usize> Default for Trail<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Counts game loop frames against an optional limit, so tests and
+/// benchmarks can run a game for a bounded time
+#[derive(Clone, Copy, Debug)]
+pub struct FrameLimit {
+    max_frames: Option<u64>,
+    frames: u64,
+}
+
+impl FrameLimit {
+    pub const fn new(max_frames: Option<u64>) -> Self {
+        Self {
+            max_frames,
+            frames: 0,
+        }
+    }
+
+    /// Count one finished frame, true once the limit is reached
+    pub fn tick(&mut self) -> bool {
+        self.frames += 1;
+        self.max_frames.is_some_and(|max| self.frames >= max)
+    }
+}
+
+/// Screen shake on impacts, e.g. a hit or a crash
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ShakeConfig {
+    /// Largest offset in pixels along each axis, 0 disables the shake
+    pub intensity: u8,
+    /// Frames one shake lasts
+    pub frames: u8,
+}
+
+/// Running screen shake, see `ShakeConfig`
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Shake {
+    config: ShakeConfig,
+    frames_left: u8,
+}
+
+impl Shake {
+    pub const fn new(config: ShakeConfig) -> Self {
+        Self {
+            config,
+            frames_left: 0,
+        }
+    }
+
+    /// Start or restart the shake, does nothing when disabled
+    pub fn start(&mut self) {
+        if self.config.intensity > 0 {
+            self.frames_left = self.config.frames;
+        }
+    }
+
+    /// Render offset for the next frame, zero once the shake is over.
+    /// Draws from `rng` only while shaking.
+    pub fn next_offset(&mut self, rng: &mut impl Rng) -> Dot {
+        if self.frames_left == 0 {
+            return Dot::new(0, 0);
+        }
+        self.frames_left -= 1;
+
+        let intensity = self.config.intensity.min(SCREEN_WIDTH as u8);
+        let span = intensity * 2 + 1;
+        Dot::new(
+            rng.next_range(span) as i8 - intensity as i8,
+            rng.next_range(span) as i8 - intensity as i8,
+        )
+    }
+}
+
+/// Game events reported to an optional status indicator
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StatusEvent {
+    Score(u16),
+    LevelUp(u8),
+    GameOver,
+}
+
+/// Secondary output, e.g. a single status LED, that reacts to game events
+pub trait StatusSink {
+    fn notify(&mut self, event: StatusEvent);
+}
+
+/// Best score of a game kept across sessions, e.g. in flash or web storage
+pub trait ScoreStore {
+    fn high_score(&self) -> u16;
+    fn set_high_score(&mut self, score: u16);
+}
+
+/// Best scores of `N` games, kept in RAM until power off
+#[derive(Clone, Copy, Debug)]
+pub struct HighScores<const N: usize> {
+    scores: [u16; N],
+}
+
+impl<const N: usize> HighScores<N> {
+    pub const fn new() -> Self {
+        Self { scores: [0; N] }
+    }
+
+    pub fn high_score(&self, game: usize) -> u16 {
+        self.scores[game]
+    }
+
+    /// Score store of the game at `game`, for its `with_score_store`
+    pub fn store(&mut self, game: usize) -> GameHighScore<'_> {
+        GameHighScore(&mut self.scores[game])
+    }
+}
+
+impl<const N: usize> Default for HighScores<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// One game's entry in `HighScores`
+pub struct GameHighScore<'a>(&'a mut u16);
+
+impl ScoreStore for GameHighScore<'_> {
+    fn high_score(&self) -> u16 {
+        *self.0
+    }
+
+    fn set_high_score(&mut self, score: u16) {
+        *self.0 = score;
+    }
 }
 
 /// Game trait for different game implementations - using generics to avoid dyn issues
 pub trait Game {
     async fn run(&mut self);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn high_scores_keep_one_record_per_game() {
+        let mut scores = HighScores::<3>::new();
+        scores.store(1).set_high_score(40);
+        scores.store(2).set_high_score(7);
+        assert_eq!(scores.store(1).high_score(), 40);
+        assert_eq!([0, 1, 2].map(|game| scores.high_score(game)), [0, 40, 7]);
+    }
+
+    #[test]
+    fn blink_has_even_duty_cycle() {
+        for period in 1..8u16 {
+            let on = (0..period * 20).filter(|&tick| blink(tick, period)).count();
+            assert_eq!(on, period as usize * 10, "period {}", period);
+            // Switches exactly at each period boundary
+            assert!(blink(0, period) && blink(period - 1, period));
+            assert!(!blink(period, period) && !blink(2 * period - 1, period));
+            assert!(blink(2 * period, period));
+        }
+        assert!((0..100).all(|tick| blink(tick, 0)));
+    }
+
+    #[test]
+    fn hud_separator_styles() {
+        let row = 9;
+        let lit = |separator| {
+            let mut screen = FrameBuffer::new();
+            screen.draw_hud_separator(row, separator);
+            for y in (0..SCREEN_HEIGHT).filter(|&y| y != row) {
+                assert!(screen.row_is_empty(y), "row {}", y);
+            }
+            core::array::from_fn::<_, SCREEN_WIDTH, _>(|x| screen.get(x, row))
+        };
+
+        assert_eq!(lit(HudSeparator::None), [BLACK_IDX; SCREEN_WIDTH]);
+        assert_eq!(lit(HudSeparator::Solid(BLUE_IDX)), [BLUE_IDX; SCREEN_WIDTH]);
+        let dotted = lit(HudSeparator::Dotted(RED_IDX));
+        for (x, &color) in dotted.iter().enumerate() {
+            let expected = if x % 2 == 0 { RED_IDX } else { BLACK_IDX };
+            assert_eq!(color, expected, "x {}", x);
+        }
+        assert_eq!(HudSeparator::default(), HudSeparator::Solid(PINK_IDX));
+    }
+
+    // Cells of `screen` set to any color, in reading order
+    fn lit_cells(screen: &FrameBuffer) -> impl Iterator<Item = (usize, usize)> + '_ {
+        (0..SCREEN_HEIGHT)
+            .flat_map(|y| (0..SCREEN_WIDTH).map(move |x| (x, y)))
+            .filter(|&(x, y)| screen.get(x, y) != BLACK_IDX)
+    }
+
+    #[test]
+    fn lines_set_exactly_the_bresenham_cells() {
+        let line = |a, b| {
+            let mut screen = FrameBuffer::new();
+            screen.draw_line(a, b, RED_IDX);
+            screen
+        };
+        let assert_cells = |screen: &FrameBuffer, expected: &[(usize, usize)]| {
+            assert!(lit_cells(screen).eq(expected.iter().copied()));
+        };
+
+        // Horizontal, vertical and diagonal, in both directions
+        let horizontal = [(1, 4), (2, 4), (3, 4), (4, 4), (5, 4)];
+        assert_cells(&line(Dot::new(1, 4), Dot::new(5, 4)), &horizontal);
+        assert_cells(&line(Dot::new(5, 4), Dot::new(1, 4)), &horizontal);
+        let vertical = [(6, 10), (6, 11), (6, 12), (6, 13)];
+        assert_cells(&line(Dot::new(6, 10), Dot::new(6, 13)), &vertical);
+        assert_cells(&line(Dot::new(6, 13), Dot::new(6, 10)), &vertical);
+        let diagonal = [(3, 0), (2, 1), (1, 2), (0, 3)];
+        assert_cells(&line(Dot::new(0, 3), Dot::new(3, 0)), &diagonal);
+
+        // Shallow and steep slopes step along the longer axis
+        let shallow = [(0, 0), (1, 1), (2, 1), (3, 2), (4, 2)];
+        assert_cells(&line(Dot::new(0, 0), Dot::new(4, 2)), &shallow);
+        let steep = [(0, 20), (1, 21), (1, 22), (2, 23), (2, 24)];
+        assert_cells(&line(Dot::new(0, 20), Dot::new(2, 24)), &steep);
+
+        // A single point, and a line running off the screen is clipped
+        assert_cells(&line(Dot::new(7, 31), Dot::new(7, 31)), &[(7, 31)]);
+        let clipped = line(Dot::new(-3, 29), Dot::new(i8::MAX, 29));
+        assert_eq!(clipped.row_bits(29), 0xff);
+        assert_eq!(lit_cells(&clipped).count(), SCREEN_WIDTH);
+        assert_eq!(
+            lit_cells(&line(Dot::new(-5, -5), Dot::new(-1, -9))).count(),
+            0
+        );
+    }
+
+    #[test]
+    fn rectangles_fill_and_outline_with_clipping() {
+        let mut screen = FrameBuffer::new();
+        screen.fill_rect(1, 2, 3, 2, GREEN_IDX);
+        assert!(lit_cells(&screen).eq([(1, 2), (2, 2), (3, 2), (1, 3), (2, 3), (3, 3)]));
+
+        let mut screen = FrameBuffer::new();
+        screen.draw_rect(2, 10, 4, 3, GREEN_IDX);
+        let outline = [
+            (2, 10),
+            (3, 10),
+            (4, 10),
+            (5, 10),
+            (2, 11),
+            (5, 11),
+            (2, 12),
+            (3, 12),
+            (4, 12),
+            (5, 12),
+        ];
+        assert!(lit_cells(&screen).eq(outline));
+
+        // Hanging off the bottom right corner only the visible part is drawn
+        let mut screen = FrameBuffer::new();
+        screen.fill_rect(6, 30, 5, 5, BLUE_IDX);
+        assert!(lit_cells(&screen).eq([(6, 30), (7, 30), (6, 31), (7, 31)]));
+        let mut screen = FrameBuffer::new();
+        screen.draw_rect(-2, -1, 4, 3, BLUE_IDX);
+        assert!(lit_cells(&screen).eq([(1, 0), (0, 1), (1, 1)]));
+
+        // Empty and far off rectangles draw nothing
+        let mut screen = FrameBuffer::new();
+        screen.fill_rect(3, 3, 0, 4, RED_IDX);
+        screen.draw_rect(3, 3, 4, 0, RED_IDX);
+        screen.fill_rect(i8::MAX, i8::MAX, u8::MAX, u8::MAX, RED_IDX);
+        screen.draw_rect(i8::MIN, i8::MIN, 10, 10, RED_IDX);
+        assert_eq!(lit_cells(&screen).count(), 0);
+
+        // Covering the whole screen and more
+        screen.fill_rect(-10, -10, u8::MAX, u8::MAX, RED_IDX);
+        assert_eq!(lit_cells(&screen).count(), SCREEN_SIZE);
+    }
+
+    #[test]
+    fn shifts_move_content_and_fill_the_vacated_cells() {
+        let mut pattern = FrameBuffer::new();
+        pattern.set(0, 0, RED_IDX);
+        pattern.set(3, 10, GREEN_IDX);
+        pattern.set(7, 31, BLUE_IDX);
+        let cells =
+            |screen: &FrameBuffer| [(0, 0), (3, 10), (7, 31)].map(|(x, y)| screen.get(x, y));
+
+        let mut screen = pattern.clone();
+        screen.shift_up(2, YELLOW_IDX);
+        assert_eq!(screen.get(3, 8), GREEN_IDX);
+        assert_eq!(screen.get(7, 29), BLUE_IDX);
+        assert!(screen.row_bits(30) == 0xff && screen.row_bits(31) == 0xff);
+        assert_eq!(screen.get(0, 30), YELLOW_IDX);
+        assert_eq!(lit_cells(&screen).count(), 2 + 2 * SCREEN_WIDTH);
+
+        let mut screen = pattern.clone();
+        screen.shift_down(1, BLACK_IDX);
+        assert_eq!(screen.get(0, 1), RED_IDX);
+        assert_eq!(screen.get(3, 11), GREEN_IDX);
+        assert!(screen.row_is_empty(0));
+        assert_eq!(lit_cells(&screen).count(), 2);
+
+        let mut screen = pattern.clone();
+        screen.shift_left(3, BLACK_IDX);
+        assert_eq!(screen.get(0, 10), GREEN_IDX);
+        assert_eq!(screen.get(4, 31), BLUE_IDX);
+        assert_eq!(lit_cells(&screen).count(), 2);
+
+        let mut screen = pattern.clone();
+        screen.shift_right(4, PINK_IDX);
+        assert_eq!(screen.get(4, 0), RED_IDX);
+        assert_eq!(screen.get(7, 10), GREEN_IDX);
+        assert_eq!(screen.get(3, 31), PINK_IDX);
+        assert_eq!(screen.get(4, 31), BLACK_IDX);
+
+        // Shifting by nothing keeps the content, by a whole dimension or
+        // more leaves only the fill
+        let mut screen = pattern.clone();
+        screen.shift_up(0, RED_IDX);
+        screen.shift_right(0, RED_IDX);
+        assert!(screen == pattern);
+        for shift in [
+            FrameBuffer::shift_up,
+            FrameBuffer::shift_down,
+            FrameBuffer::shift_left,
+            FrameBuffer::shift_right,
+        ] {
+            let mut screen = pattern.clone();
+            shift(&mut screen, usize::MAX, ORANGE_IDX);
+            assert_eq!(cells(&screen), [ORANGE_IDX; 3]);
+            assert_eq!(lit_cells(&screen).count(), SCREEN_SIZE);
+        }
+        let mut screen = pattern.clone();
+        screen.shift_left(SCREEN_WIDTH, BLACK_IDX);
+        assert_eq!(lit_cells(&screen).count(), 0);
+    }
+
+    #[test]
+    fn flips_mirror_cells_and_undo_themselves() {
+        // An L in the top left corner, no axis of symmetry
+        let mut pattern = FrameBuffer::new();
+        pattern.set(0, 0, RED_IDX);
+        pattern.set(0, 1, GREEN_IDX);
+        pattern.set(1, 1, BLUE_IDX);
+
+        let mut screen = pattern.clone();
+        screen.flip_horizontal();
+        assert!(lit_cells(&screen).eq([(7, 0), (6, 1), (7, 1)]));
+        assert_eq!(screen.get(7, 0), RED_IDX);
+        assert_eq!(screen.get(6, 1), BLUE_IDX);
+        screen.flip_horizontal();
+        assert!(screen == pattern);
+
+        screen.flip_vertical();
+        assert!(lit_cells(&screen).eq([(0, 30), (1, 30), (0, 31)]));
+        assert_eq!(screen.get(0, 31), RED_IDX);
+        assert_eq!(screen.get(1, 30), BLUE_IDX);
+        screen.flip_vertical();
+        assert!(screen == pattern);
+
+        // Both flips turn the screen half way around
+        screen.flip_horizontal();
+        screen.flip_vertical();
+        assert_eq!(screen.get(7, 31), RED_IDX);
+        assert_eq!(screen.get(7, 30), GREEN_IDX);
+        assert_eq!(screen.get(6, 30), BLUE_IDX);
+    }
+
+    #[test]
+    fn long_scores_scroll_through_every_digit() {
+        let digits = |pairs: &[(i8, usize)]| {
+            let mut screen = FrameBuffer::new();
+            for &(x, digit) in pairs {
+                screen.draw_figure(x, 0, &DIGITS[digit], GREEN_IDX);
+            }
+            screen
+        };
+        let score = |value: u16, frame: u16| {
+            let mut screen = FrameBuffer::new();
+            screen.draw_hud_score(value, frame, GREEN_IDX);
+            screen
+        };
+
+        assert!(score(7, 0) == digits(&[(0, 0), (4, 7)]));
+        assert!(score(42, 99) == digits(&[(0, 4), (4, 2)]));
+
+        // 1234 starts with its first two digits and moves a digit along
+        // every four columns
+        assert!(score(1234, 0) == digits(&[(0, 1), (4, 2)]));
+        let step = 4 * SCORE_SCROLL_FRAMES;
+        assert!(score(1234, step) == digits(&[(0, 2), (4, 3)]));
+        assert!(score(1234, 3 * step) == digits(&[(0, 4)]));
+        // After the gap it comes round again
+        assert!(score(1234, 5 * step) == score(1234, 0));
+        assert!(score(1234, SCORE_SCROLL_FRAMES) == digits(&[(-1, 1), (3, 2), (7, 3)]));
+    }
+
+    #[test]
+    fn nearest_index_of_palette_colors() {
+        for (i, color) in COLORS.iter().enumerate() {
+            assert_eq!(nearest_index(*color), i as u8);
+        }
+    }
+
+    #[test]
+    fn nearest_index_of_perturbed_colors() {
+        assert_eq!(nearest_index(RGB8::new(7, 1, 0)), RED_IDX);
+        assert_eq!(nearest_index(RGB8::new(11, 3, 0)), BRICK_IDX);
+        assert_eq!(nearest_index(RGB8::new(0, 4, 1)), DARK_GREEN_IDX);
+        assert_eq!(nearest_index(RGB8::new(0, 10, 0)), LIGHT_GREEN_IDX);
+        assert_eq!(nearest_index(RGB8::new(1, 5, 6)), LIGHT_BLUE_IDX);
+        assert_eq!(nearest_index(RGB8::new(1, 1, 1)), BLACK_IDX);
+    }
+
+    #[test]
+    fn zero_hue_shift_is_identity() {
+        assert_eq!(shifted_palette(0), COLORS);
+        assert_eq!(shifted_palette(360), COLORS);
+        for color in COLORS {
+            assert_eq!(rotate_hue(color, 0), color);
+        }
+    }
+
+    #[test]
+    fn packed_palette_keeps_every_channel() {
+        for color in COLORS.iter().chain(shifted_palette(180).iter()) {
+            assert_eq!(unpack_color(pack_color(*color)), *color);
+        }
+        assert_eq!(
+            unpack_color(pack_color(RGB8::new(255, 1, 128))),
+            RGB8::new(255, 1, 128)
+        );
+    }
+
+    #[test]
+    fn half_turn_hue_shift_moves_to_complementary_colors() {
+        let palette = shifted_palette(180);
+        assert_eq!(palette[BLACK_IDX as usize], BLACK);
+        assert_eq!(palette[RED_IDX as usize], LIGHT_BLUE);
+        assert_eq!(palette[LIGHT_BLUE_IDX as usize], RED);
+        assert_eq!(palette[GREEN_IDX as usize], RGB8::new(6, 0, 6));
+        assert_eq!(palette[BLUE_IDX as usize], YELLOW);
+        assert_eq!(palette[YELLOW_IDX as usize], BLUE);
+        assert_eq!(palette[PINK_IDX as usize], DARK_GREEN);
+
+        // Rotating twice by 180 degrees gets back to the start
+        for color in COLORS {
+            assert_eq!(rotate_hue(rotate_hue(color, 180), 180), color);
+        }
+    }
+
+    #[test]
+    fn background_fills_only_empty_cells() {
+        let mut screen = FrameBuffer::new();
+        screen.set(2, 4, RED_IDX);
+        let mut leds = [BLACK; SCREEN_SIZE];
+        screen.render_over(&mut leds, Background::Checkerboard(DARK_GREEN_IDX));
+
+        assert_eq!(get_pixel(&leds, 2, 4), RED_IDX);
+        assert_eq!(get_pixel(&leds, 0, 0), DARK_GREEN_IDX);
+        assert_eq!(get_pixel(&leds, 1, 0), BLACK_IDX);
+        assert_eq!(get_pixel(&leds, 3, 5), DARK_GREEN_IDX);
+
+        let mut plain = [BLACK; SCREEN_SIZE];
+        screen.render_over(&mut plain, Background::None);
+        assert_eq!(get_pixel(&plain, 0, 0), BLACK_IDX);
+    }
+
+    #[test]
+    fn checksum_detects_single_cell_change() {
+        let mut first = FrameBuffer::new();
+        let mut second = FrameBuffer::new();
+        first.set(3, 7, GREEN_IDX);
+        second.set(3, 7, GREEN_IDX);
+        assert_eq!(first.checksum(), second.checksum());
+
+        second.set(4, 7, GREEN_IDX);
+        assert_ne!(first.checksum(), second.checksum());
+        second.set(4, 7, BLACK_IDX);
+        second.set(3, 7, RED_IDX);
+        assert_ne!(first.checksum(), second.checksum());
+    }
+
+    #[test]
+    fn min_frame_timer_stretches_short_frames_only() {
+        use crate::games::tanks::{TanksConfig, TanksGame};
+        use crate::testing::{CaptureDisplay, Runner, ScriptedController, VirtualTimer};
+
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = MinFrameTimer::with_min_millis(VirtualTimer::new(), 5);
+        let config = TanksConfig {
+            frame_millis: 0,
+            ..TanksConfig::default()
+        };
+        let mut game =
+            TanksGame::with_config(Prng::new(7), &mut display, &mut controller, &timer, config);
+        let mut runner = Runner::new(game.run());
+        runner.steps(20);
+        drop(runner);
+
+        let sleeps = timer.inner().sleeps();
+        assert_eq!(sleeps.len(), 20);
+        assert!(sleeps.iter().all(|&ms| ms == 5));
+
+        // Longer sleeps pass through untouched
+        let mut runner = Runner::new(timer.sleep_millis(50));
+        while runner.step() {}
+        assert_eq!(timer.inner().sleeps().last(), Some(&50));
+    }
+
+    #[test]
+    fn lost_focus_stops_frames_until_regained() {
+        use crate::testing::{CaptureDisplay, Runner, VirtualTimer};
+
+        let capture = CaptureDisplay::new();
+        let mut display = capture.clone();
+        let focus = Focus::new();
+        let timer = PauseOnFocusLoss::new(VirtualTimer::new(), &focus);
+        let leds = [BLACK; SCREEN_SIZE];
+
+        let mut runner = Runner::new(async {
+            loop {
+                display.write(&leds).await;
+                timer.sleep_millis(20).await;
+            }
+        });
+        runner.steps(3);
+        assert_eq!(capture.frame_count(), 3);
+
+        focus.set_focused(false);
+        runner.steps(10);
+        assert_eq!(capture.frame_count(), 3);
+
+        focus.set_focused(true);
+        runner.steps(3);
+        assert_eq!(capture.frame_count(), 6);
+    }
+
+    #[test]
+    fn identical_writes_are_skipped() {
+        use crate::testing::{CaptureDisplay, Runner};
+
+        let capture = CaptureDisplay::new();
+        let mut display = SkipUnchanged::new(capture.clone());
+        let mut screen = FrameBuffer::new();
+        let mut leds = [BLACK; SCREEN_SIZE];
+
+        let mut runner = Runner::new(async {
+            screen.set(1, 1, GREEN_IDX);
+            screen.render(&mut leds);
+            display.write(&leds).await;
+            screen.render(&mut leds);
+            display.write(&leds).await;
+
+            screen.set(2, 2, RED_IDX);
+            screen.render(&mut leds);
+            display.write(&leds).await;
+        });
+        while runner.step() {}
+
+        assert_eq!(capture.frame_count(), 2);
+    }
+
+    #[test]
+    fn tee_mirrors_every_write_to_both_displays() {
+        use crate::games::snake::SnakeGame;
+        use crate::testing::{CaptureDisplay, Runner, ScriptedController, VirtualTimer};
+
+        let first = CaptureDisplay::new();
+        let second = CaptureDisplay::new();
+        let mut display = TeeDisplay::new(first.clone(), second.clone());
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+
+        let mut game = SnakeGame::new(Prng::new(3), &mut display, &mut controller, &timer);
+        let mut runner = Runner::new(game.run());
+        runner.steps(10);
+        drop(runner);
+
+        assert!(first.frame_count() > 0);
+        assert_eq!(second.frame_count(), first.frame_count());
+        for idx in 0..first.frame_count() {
+            assert_eq!(first.frame(idx), second.frame(idx), "frame {}", idx);
+        }
+    }
+
+    // Mono writer that keeps the last frame written
+    struct LastBits(Option<MonoFrame>);
+
+    impl MonoWriter for LastBits {
+        async fn write_bits(&mut self, bits: &MonoFrame) {
+            self.0 = Some(*bits);
+        }
+    }
+
+    #[test]
+    fn mono_display_thresholds_leds_into_row_bits() {
+        use crate::testing::Runner;
+
+        let mut display = MonoDisplay::with_threshold(LastBits(None), 40);
+        let mut leds = [BLACK; SCREEN_SIZE];
+        leds[led_index(0, 0)] = RGB8::new(41, 0, 0);
+        leds[led_index(7, 0)] = RGB8::new(0, 0, 255);
+        leds[led_index(3, 6)] = RGB8::new(40, 40, 40);
+        leds[led_index(2, 31)] = RGB8::new(0, 200, 0);
+
+        let mut runner = Runner::new(display.write(&leds));
+        while runner.step() {}
+        drop(runner);
+
+        let bits = display.into_inner().0.unwrap();
+        assert_eq!(bits[0], 0b1000_0001);
+        // At the threshold is still off, like black
+        assert_eq!(bits[6], 0);
+        assert_eq!(bits[31], 0b0010_0000);
+        assert_eq!(bits.iter().filter(|&&row| row != 0).count(), 2);
+    }
+
+    #[test]
+    fn mono_frame_rows_match_row_bits() {
+        use crate::testing::Runner;
+
+        // Lopsided so a mirrored row can't match
+        let mut screen = FrameBuffer::new();
+        screen.set_row_bits(0, 0b1100_0000, RED_IDX);
+        screen.set_row_bits(9, 0b1010_0011, GREEN_IDX);
+        screen.set_row_bits(31, 0b0000_0001, BLUE_IDX);
+        let mut leds = [BLACK; SCREEN_SIZE];
+        screen.render_with(&mut leds, &COLORS);
+
+        let mut display = MonoDisplay::new(LastBits(None));
+        let mut runner = Runner::new(display.write(&leds));
+        while runner.step() {}
+        drop(runner);
+
+        let bits = display.into_inner().0.unwrap();
+        for (y, &row) in bits.iter().enumerate() {
+            assert_eq!(row, screen.row_bits(y), "row {}", y);
+        }
+    }
+
+    #[test]
+    fn present_writes_rendered_frame_once() {
+        use crate::testing::{CaptureDisplay, Runner};
+
+        let mut display = CaptureDisplay::new();
+        let capture = display.clone();
+        let mut screen = FrameBuffer::new();
+        screen.set(0, 0, GREEN_IDX);
+        screen.set(5, 17, RED_IDX);
+
+        let mut runner = Runner::new(present(&mut display, &screen));
+        while runner.step() {}
+
+        let mut expected = [BLACK; SCREEN_SIZE];
+        expected[led_index(0, 0)] = GREEN;
+        expected[led_index(5, 17)] = RED;
+        assert_eq!(capture.frame_count(), 1);
+        assert_eq!(capture.frame(0), expected);
+    }
+
+    #[test]
+    fn clamped_stops_at_every_edge() {
+        let min = Dot::new(1, 6);
+        let max = Dot::new(6, 31);
+
+        assert_eq!(Dot::new(3, 10).clamped(min, max), Dot::new(3, 10));
+        assert_eq!(Dot::new(0, 10).clamped(min, max), Dot::new(1, 10));
+        assert_eq!(Dot::new(-5, 10).clamped(min, max), Dot::new(1, 10));
+        assert_eq!(Dot::new(7, 10).clamped(min, max), Dot::new(6, 10));
+        assert_eq!(Dot::new(3, -1).clamped(min, max), Dot::new(3, 6));
+        assert_eq!(Dot::new(3, 32).clamped(min, max), Dot::new(3, 31));
+        assert_eq!(
+            Dot::new(i8::MIN, i8::MAX).clamped(min, max),
+            Dot::new(1, 31)
+        );
+    }
+
+    #[test]
+    fn dot_in_bounds_matches_screen() {
+        assert!(Dot::new(0, 0).in_bounds());
+        assert!(Dot::new(7, 31).in_bounds());
+        assert!(!Dot::new(-1, 5).in_bounds());
+        assert!(!Dot::new(5, -1).in_bounds());
+        assert!(!Dot::new(8, 5).in_bounds());
+        assert!(!Dot::new(5, 32).in_bounds());
+    }
+
+    #[test]
+    fn lives_are_hearts_clipped_at_the_edge() {
+        let lit = |screen: &FrameBuffer, y: usize| -> [bool; SCREEN_WIDTH] {
+            core::array::from_fn(|x| screen.get(x, y) == RED_IDX)
+        };
+
+        let mut three = FrameBuffer::new();
+        three.draw_lives(3, 0, 1, RED_IDX);
+        // The third heart loses its right arm past the edge
+        assert_eq!(
+            lit(&three, 1),
+            [true, false, true, true, false, true, true, false]
+        );
+        assert_eq!(
+            lit(&three, 2),
+            [false, true, false, false, true, false, false, true]
+        );
+        assert!((0..SCREEN_HEIGHT)
+            .filter(|&y| y != 1 && y != 2)
+            .all(|y| lit(&three, y) == [false; SCREEN_WIDTH]));
+
+        // Hearts that start past the edge are not drawn at all
+        let mut many = FrameBuffer::new();
+        many.draw_lives(u8::MAX, 0, 1, RED_IDX);
+        assert!(many == three);
+    }
+
+    #[test]
+    fn hud_shows_every_life_up_to_the_maximum() {
+        let hearts = |screen: &FrameBuffer| {
+            HUD_HEART_SLOTS
+                .iter()
+                .filter(|&&(x, y)| screen.get(x as usize, y as usize) == RED_IDX)
+                .count()
+        };
+        for lives in 0..=MAX_LIVES {
+            let mut screen = FrameBuffer::new();
+            screen.draw_hud_lives(lives, &HUD_HEART_SLOTS, RED_IDX);
+            assert_eq!(hearts(&screen), lives as usize, "lives {}", lives);
+            // Every heart stays inside the HUD
+            assert!(screen.row_is_empty(HUD_SEPARATOR_Y));
+        }
+
+        // Past the slots one heart and the count show
+        let mut screen = FrameBuffer::new();
+        screen.draw_hud_lives(5, &HUD_HEART_SLOTS[..2], RED_IDX);
+        let mut expected = FrameBuffer::new();
+        expected.draw_figure(0, 0, &HEART, RED_IDX);
+        expected.draw_figure(5, 0, &DIGITS[5], RED_IDX);
+        assert!(screen == expected);
+    }
+
+    #[test]
+    fn hud_swap_is_the_same_time_at_any_frame_rate() {
+        assert_eq!(hud_swap_frames(50), 40);
+        assert_eq!(hud_swap_frames(100), 20);
+        assert_eq!(hud_swap_frames(0), u16::try_from(HUD_SWAP_MS).unwrap());
+        assert_eq!(hud_swap_frames(10_000), 1);
+    }
+
+    #[test]
+    fn any_pressed_covers_every_button() {
+        use crate::testing::ScriptedController;
+
+        let controller = ScriptedController::new();
+        assert!(!controller.any_pressed());
+
+        controller.press_a();
+        assert!(controller.any_pressed());
+        assert!(!controller.any_pressed());
+
+        controller.press_b();
+        controller.press_joystick();
+        assert!(controller.any_pressed());
+        // Both edges were consumed by the first call
+        assert!(!controller.joystick_was_pressed());
+        assert!(!controller.b_was_pressed());
+    }
+}
@@ -0,0 +1,81 @@
+// Screen coordinate system shared by every game and display.
+//
+//        x
+//     0 --->  7
+//    0+-------+
+//     |       |
+//     |   S   |
+//   | |   C   |
+// y | |   R   |---+
+//   | |   E   | +----+
+//   v |   E   | |::::| <- microbit
+//     |   N   | +----+
+//     |       | @ |<---- joystick
+//   31+-------+---+
+//
+// `x` grows to the right and `y` grows downward. Game state (`FrameBuffer`)
+// is stored row by row in this orientation. The LED strip is wired as a
+// serpentine: odd rows run left to right, even rows run right to left, so
+// only `led_index` may be used to address the `[RGB8; 256]` frame.
+
+pub const SCREEN_WIDTH: usize = 8;
+pub const SCREEN_HEIGHT: usize = 32;
+pub const SCREEN_SIZE: usize = SCREEN_WIDTH * SCREEN_HEIGHT;
+
+/// True if the signed position lies on the screen
+pub fn in_bounds(x: i8, y: i8) -> bool {
+    x >= 0 && (x as usize) < SCREEN_WIDTH && y >= 0 && (y as usize) < SCREEN_HEIGHT
+}
+
+/// Row-major index of an on-screen cell, as stored by `FrameBuffer`
+pub fn screen_index(x: usize, y: usize) -> usize {
+    debug_assert!(x < SCREEN_WIDTH && y < SCREEN_HEIGHT, "({}, {})", x, y);
+    y * SCREEN_WIDTH + x
+}
+
+/// Index of an on-screen cell on the serpentine LED strip
+pub fn led_index(x: usize, y: usize) -> usize {
+    debug_assert!(x < SCREEN_WIDTH && y < SCREEN_HEIGHT, "({}, {})", x, y);
+    if y.is_multiple_of(2) {
+        y * SCREEN_WIDTH + (SCREEN_WIDTH - 1 - x)
+    } else {
+        y * SCREEN_WIDTH + x
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn corners_and_serpentine_rows_map_to_strip() {
+        let last_x = SCREEN_WIDTH - 1;
+        let last_y = SCREEN_HEIGHT - 1;
+
+        assert_eq!(screen_index(0, 0), 0);
+        assert_eq!(screen_index(last_x, 0), last_x);
+        assert_eq!(screen_index(0, last_y), SCREEN_SIZE - SCREEN_WIDTH);
+        assert_eq!(screen_index(last_x, last_y), SCREEN_SIZE - 1);
+
+        // The top row is even, so the strip starts at its right end
+        assert_eq!(led_index(0, 0), last_x);
+        assert_eq!(led_index(last_x, 0), 0);
+        // The bottom row is odd and runs left to right
+        assert_eq!(led_index(0, last_y), SCREEN_SIZE - SCREEN_WIDTH);
+        assert_eq!(led_index(last_x, last_y), SCREEN_SIZE - 1);
+
+        // Mid-screen cells on an even and an odd row
+        assert_eq!(led_index(2, 16), 16 * SCREEN_WIDTH + 5);
+        assert_eq!(led_index(2, 17), 17 * SCREEN_WIDTH + 2);
+    }
+
+    #[test]
+    fn in_bounds_rejects_every_edge() {
+        assert!(in_bounds(0, 0));
+        assert!(in_bounds(7, 31));
+        assert!(!in_bounds(-1, 0));
+        assert!(!in_bounds(0, -1));
+        assert!(!in_bounds(8, 0));
+        assert!(!in_bounds(0, 32));
+    }
+}
@@ -1,7 +1,9 @@
 use core::default::Default;
 
-use crate::figure::Figure;
+use crate::figure::{glyphs_from_ascii, Figure};
 
+// Digits are drawn in digits.txt, '#' for a set cell and '.' for an empty
+// one with a blank line between digits, and packed when compiling.
 // Each digit is represented by a 5x3 bit pattern stored in a u16
 // The bits are arranged in rows, with each row taking 3 bits
 // For example, digit 1:
@@ -10,98 +12,12 @@ use crate::figure::Figure;
 //  0 1 0  -> 010
 //  0 1 0  -> 010
 //  0 1 0  -> 010
-const DIGITS_DATA: [Figure; 10] = [
-    // 0: ###
-    //    # #
-    //    # #
-    //    # #
-    //    ###
-    Figure {
-        data: 0b111_101_101_101_111,
-        wh: 3 << 4 | 5,
-    },
-    // 1:  #
-    //    ##
-    //     #
-    //     #
-    //     #
-    Figure {
-        data: 0b010_110_010_010_010,
-        wh: 3 << 4 | 5,
-    },
-    // 2: ###
-    //      #
-    //    ###
-    //    #
-    //    ###
-    Figure {
-        data: 0b111_001_111_100_111,
-        wh: 3 << 4 | 5,
-    },
-    // 3: ###
-    //      #
-    //    ###
-    //      #
-    //    ###
-    Figure {
-        data: 0b111_001_111_001_111,
-        wh: 3 << 4 | 5,
-    },
-    // 4: # #
-    //    # #
-    //    ###
-    //      #
-    //      #
-    Figure {
-        data: 0b101_101_111_001_001,
-        wh: 3 << 4 | 5,
-    },
-    // 5: ###
-    //    #
-    //    ###
-    //      #
-    //    ###
-    Figure {
-        data: 0b111_100_111_001_111,
-        wh: 3 << 4 | 5,
-    },
-    // 6: ###
-    //    #
-    //    ###
-    //    # #
-    //    ###
-    Figure {
-        data: 0b111_100_111_101_111,
-        wh: 3 << 4 | 5,
-    },
-    // 7: ###
-    //      #
-    //      #
-    //      #
-    //      #
-    Figure {
-        data: 0b111_001_001_001_001,
-        wh: 3 << 4 | 5,
-    },
-    // 8: ###
-    //    # #
-    //    ###
-    //    # #
-    //    ###
-    Figure {
-        data: 0b111_101_111_101_111,
-        wh: 3 << 4 | 5,
-    },
-    // 9: ###
-    //    # #
-    //    ###
-    //      #
-    //    ###
-    Figure {
-        data: 0b111_101_111_001_111,
-        wh: 3 << 4 | 5,
-    },
-];
+const DIGITS_DATA: [Figure; 10] = glyphs_from_ascii(include_str!("digits.txt"));
+
+/// Hexadecimal digits after 9 in the same style, drawn in digits_hex.txt.
+/// A, C, E and F are the font's letters, b and d are lowercase to tell them
+/// apart from 8 and 0
+pub static DIGITS_HEX: [Figure; 6] = glyphs_from_ascii(include_str!("digits_hex.txt"));
 
 #[derive(Default)]
 pub struct Digits([Figure; 10]);
@@ -114,6 +30,14 @@ impl Digits {
     pub fn wrapping_at(&self, idx: u8) -> &Figure {
         &self.0[idx as usize % 10]
     }
+
+    /// Hexadecimal digit for the low four bits of `value`
+    pub fn hex_at(&self, value: u8) -> &Figure {
+        match (value & 0x0f) as usize {
+            digit @ 0..=9 => &self.0[digit],
+            digit => &DIGITS_HEX[digit - 10],
+        }
+    }
 }
 
 impl core::ops::Index<usize> for Digits {
@@ -128,8 +52,9 @@ pub const DIGITS: Digits = Digits::new(DIGITS_DATA);
 
 #[cfg(test)]
 mod tests {
-    #![allow(unused_imports)]
+    #![allow(unused_imports, clippy::bool_assert_comparison)]
     use super::*;
+    use crate::font::glyph;
 
     #[test]
     fn test_digit_one_pattern() {
@@ -179,6 +104,76 @@ mod tests {
         assert_eq!(DIGITS.wrapping_at(9), &DIGITS.0[9]);
     }
 
+    #[test]
+    fn test_hex_digits() {
+        for (i, digit) in DIGITS_HEX.iter().enumerate() {
+            assert_eq!(digit.width(), 3, "Hex digit {} has wrong width", i + 10);
+            assert_eq!(digit.height(), 5, "Hex digit {} has wrong height", i + 10);
+            // None of them looks like a decimal digit
+            assert!(
+                !DIGITS.0.contains(digit),
+                "Hex digit {} is ambiguous",
+                i + 10
+            );
+        }
+
+        assert_eq!(DIGITS.hex_at(7), &DIGITS.0[7]);
+        assert_eq!(DIGITS.hex_at(0xa), &DIGITS_HEX[0]);
+        assert_eq!(DIGITS.hex_at(0xf), &DIGITS_HEX[5]);
+        // Only the low nibble counts
+        assert_eq!(DIGITS.hex_at(0x3c), &DIGITS_HEX[2]);
+        assert_eq!(
+            DIGITS.hex_at(0xa),
+            &Figure::from_ascii(".#.\n#.#\n###\n#.#\n#.#")
+        );
+        // The uppercase ones read the same as in text
+        for (value, c) in [(0xa, 'A'), (0xc, 'C'), (0xe, 'E'), (0xf, 'F')] {
+            assert_eq!(Some(*DIGITS.hex_at(value)), glyph(c), "Hex digit {}", c);
+        }
+    }
+
+    #[test]
+    fn test_digits_match_ascii_source() {
+        // The runtime parser reads the same file the same way
+        let mut glyphs = include_str!("digits.txt").split("\n\n");
+        for (i, digit) in DIGITS.0.iter().enumerate() {
+            let glyph = glyphs.next().expect("missing glyph in digits.txt");
+            let parsed = Figure::from_ascii(glyph.trim_end_matches('\n'));
+            assert_eq!(
+                &parsed,
+                digit,
+                "Digit {} differs from digits.txt\nEXPECTED:\n{}\nACTUAL:\n{}",
+                i,
+                parsed.str(),
+                digit.str(),
+            );
+        }
+        assert!(glyphs.next().is_none(), "digits.txt has extra glyphs");
+
+        // Both parsers could misread the file alike, pin some bits by hand
+        assert_eq!(
+            DIGITS.0[1],
+            Figure {
+                data: 0b010_110_010_010_010,
+                wh: 3 << 4 | 5,
+            }
+        );
+        assert_eq!(
+            DIGITS.0[7],
+            Figure {
+                data: 0b111_001_001_001_001,
+                wh: 3 << 4 | 5,
+            }
+        );
+        assert_eq!(
+            DIGITS_HEX[1],
+            Figure {
+                data: 0b100_100_111_101_111,
+                wh: 3 << 4 | 5,
+            }
+        );
+    }
+
     #[test]
     fn test_digit_patterns() {
         // Test that each digit has at least some pixels set
@@ -13,11 +13,11 @@ pub struct Figure {
 }
 
 impl Figure {
-    pub fn width(&self) -> u8 {
+    pub const fn width(&self) -> u8 {
         self.wh >> 4
     }
 
-    pub fn height(&self) -> u8 {
+    pub const fn height(&self) -> u8 {
         self.wh & 0x0f
     }
 
@@ -29,6 +29,31 @@ impl Figure {
         self.height() * self.width()
     }
 
+    /// Parse a figure from ASCII art: `#` is a set cell, any other character
+    /// is empty. Rows are separated by newlines and shorter rows are padded
+    /// on the right, so the width is that of the longest row.
+    pub fn from_ascii(text: &str) -> Self {
+        let height = text.lines().count() as u8;
+        let width = text.lines().map(|line| line.len()).max().unwrap_or(0) as u8;
+        debug_assert!(width <= 15 && height <= 15 && width * height <= 16);
+
+        let mut data: u16 = 0;
+        for line in text.lines() {
+            let bytes = line.as_bytes();
+            for col in 0..width as usize {
+                data <<= 1;
+                if bytes.get(col) == Some(&b'#') {
+                    data |= 1;
+                }
+            }
+        }
+
+        Self {
+            data,
+            wh: width << 4 | height,
+        }
+    }
+
     pub fn rotate(&self) -> Self {
         let mut rotated: u16 = 0;
         let height = self.height();
@@ -56,52 +81,75 @@ impl Figure {
         }
     }
 
+    const fn packed(data: u16, width: u8, height: u8) -> Self {
+        assert!(width <= 15 && height <= 15 && width * height <= 16);
+        Self {
+            data,
+            wh: width << 4 | height,
+        }
+    }
+
+    /// ASCII art of the figure as it is drawn, in the `from_ascii` format
     pub fn str(&self) -> str32 {
         let mut repr = str32::new();
-        let mut cursor: u16 = 1;
-
-        let mut ch_idx = 0;
-        let signs = self.width() * self.height();
-        while cursor.trailing_zeros() as u8 != signs {
-            let ch = if self.data & cursor != 0 { "#" } else { " " };
-            repr.push(ch);
-
-            ch_idx += 1;
-            if ch_idx == self.width() {
-                repr.push("\n");
-                ch_idx = 0;
+        for row in 0..self.height() {
+            for col in 0..self.width() {
+                repr.push(if self.get_bit(col, row) { "#" } else { " " });
             }
-            cursor <<= 1;
+            repr.push("\n");
         }
         repr
     }
 
+    /// Cell at `col` (from the left) and `row` (from the top).
+    ///
+    /// `data` is stored the way figures are written down: the most
+    /// significant bit is the top-left cell and rows follow each other from
+    /// top to bottom, so `0b111_010` with width 3 is `###` over ` # `. The
+    /// bit index therefore counts from the bottom-right cell.
     pub fn get_bit(&self, col: u8, row: u8) -> bool {
         if col >= self.width() || row >= self.height() {
             return false;
         }
-        let bit_idx = (self.height() - 1 - row) * self.width() + (self.width() - 1 - col); // Flip both row and column order
-        let cursor = 1u16 << bit_idx;
-        self.data & cursor != 0
+        self.data & 1 << self.bit_index(col, row) != 0
     }
 
-    pub fn draw(&self, m: &mut [RGB8], x: u8, y: u8, color: RGB8, paniter: Painter) -> bool {
-        let mut row: u8 = 0;
-        let mut col: u8 = 0;
-        let mut cursor: u16 = 1;
-        let signs = self.width() * self.height();
-        while cursor.trailing_zeros() as u8 != signs {
-            if self.data & cursor != 0 && !paniter(m, x + col, y + row, color) {
-                return false;
-            }
-            col += 1;
-            if col == self.width() {
-                row += 1;
-                col = 0;
-            }
-            cursor <<= 1;
+    /// `(col, row)` of every set cell, row by row from the top left
+    pub fn cells(&self) -> impl Iterator<Item = (u8, u8)> {
+        let figure = *self;
+        (0..figure.height())
+            .flat_map(move |row| (0..figure.width()).map(move |col| (col, row)))
+            .filter(move |&(col, row)| figure.get_bit(col, row))
+    }
+
+    // Bit of `data` holding the cell at `col` and `row`, see `get_bit`
+    fn bit_index(&self, col: u8, row: u8) -> u8 {
+        (self.height() - 1 - row) * self.width() + (self.width() - 1 - col)
+    }
+
+    /// Mirror image swapping left and right, the size stays the same
+    pub fn flip_horizontal(&self) -> Self {
+        self.remapped(|col, row| (self.width() - 1 - col, row))
+    }
+
+    /// Mirror image swapping top and bottom, the size stays the same
+    pub fn flip_vertical(&self) -> Self {
+        self.remapped(|col, row| (col, self.height() - 1 - row))
+    }
+
+    // Same size figure with every set cell moved to where `to` puts it
+    fn remapped(&self, to: impl Fn(u8, u8) -> (u8, u8)) -> Self {
+        let mut data = 0;
+        for (col, row) in self.cells() {
+            let (col, row) = to(col, row);
+            data |= 1 << self.bit_index(col, row);
         }
-        true
+        Self { data, wh: self.wh }
+    }
+
+    pub fn draw(&self, m: &mut [RGB8], x: u8, y: u8, color: RGB8, paniter: Painter) -> bool {
+        self.cells()
+            .all(|(col, row)| paniter(m, x + col, y + row, color))
     }
 }
 
@@ -133,7 +181,45 @@ impl Tetramino {
     }
 }
 
-// Standard Tetris tetraminoes (I, O, T, S, Z, J, L)
+/// Parse `N` figures at compile time from ASCII art in the `from_ascii`
+/// format, with a blank line after each figure. Every row of a figure has
+/// to be as wide as its first one.
+pub const fn glyphs_from_ascii<const N: usize>(text: &str) -> [Figure; N] {
+    let bytes = text.as_bytes();
+    let mut glyphs = [Figure { data: 0, wh: 0 }; N];
+    let mut count = 0;
+    let (mut data, mut width, mut height, mut col) = (0u16, 0u8, 0u8, 0u8);
+    let mut idx = 0;
+    // Two more newlines past the end close the last row and figure
+    while idx < bytes.len() + 2 {
+        let byte = if idx < bytes.len() { bytes[idx] } else { b'\n' };
+        idx += 1;
+        if byte != b'\n' {
+            data = data << 1 | (byte == b'#') as u16;
+            col += 1;
+        } else if col > 0 {
+            // End of a row
+            assert!(
+                height == 0 || col == width,
+                "rows of a glyph differ in width"
+            );
+            width = col;
+            height += 1;
+            col = 0;
+        } else if height > 0 {
+            // Blank line or the end of the text after a figure
+            assert!(count < N, "more glyphs than expected");
+            glyphs[count] = Figure::packed(data, width, height);
+            count += 1;
+            (data, width, height) = (0, 0, 0);
+        }
+    }
+    assert!(count == N, "fewer glyphs than expected");
+    glyphs
+}
+
+// Standard Tetris tetraminoes (I, O, T, S, Z, J, L), see `Figure::get_bit`
+// for how `data` maps to cells
 pub const TETRAMINO: Tetramino = Tetramino::new([
     // I: ####
     Figure {
@@ -149,29 +235,29 @@ pub const TETRAMINO: Tetramino = Tetramino::new([
     // T:  #
     //    ###
     Figure {
-        data: 0b111_010,
+        data: 0b010_111,
         wh: 3 << 4 | 2,
     },
-    // Z:  ##
-    //      ##
+    // S:  ##
+    //    ##
     Figure {
         data: 0b011_110,
         wh: 3 << 4 | 2,
     },
-    // S:  ##
-    //    ##
+    // Z: ##
+    //     ##
     Figure {
         data: 0b110_011,
         wh: 3 << 4 | 2,
     },
-    // L: #
+    // J: #
     //    ###
     Figure {
         data: 0b100_111,
         wh: 3 << 4 | 2,
     },
-    // J:    #
-    //     ###
+    // L:   #
+    //    ###
     Figure {
         data: 0b001_111,
         wh: 3 << 4 | 2,
@@ -186,6 +272,13 @@ pub const TANK: Figure = Figure {
     wh: 3 << 4 | 3,
 };
 
+// # #
+//  #
+pub const HEART: Figure = Figure {
+    data: 0b_101_010,
+    wh: 3 << 4 | 2,
+};
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -223,4 +316,132 @@ mod tests {
         );
         assert_eq!(rotated.wh, rotated_four.wh);
     }
+
+    #[test]
+    fn flips_mirror_the_cells_and_keep_the_size() {
+        let four = Figure {
+            data: 0b101_101_111_001_001,
+            wh: 3 << 4 | 5,
+        };
+
+        // # #
+        // # #
+        // ###
+        // #
+        // #
+        let mirrored = four.flip_horizontal();
+        assert_eq!(mirrored.data, 0b101_101_111_100_100, "\n{}", mirrored.str());
+        assert_eq!(mirrored.wh, four.wh);
+
+        //   #
+        //   #
+        // ###
+        // # #
+        // # #
+        let upside_down = four.flip_vertical();
+        assert_eq!(
+            upside_down.data,
+            0b001_001_111_101_101,
+            "\n{}",
+            upside_down.str()
+        );
+        assert_eq!(upside_down.wh, four.wh);
+
+        // J and L are mirror images of each other
+        let (j, l) = (TETRAMINO.wrapping_at(5), TETRAMINO.wrapping_at(6));
+        assert_eq!(j.flip_horizontal(), l);
+        assert_eq!(j.flip_vertical().data, 0b111_100);
+        assert_eq!(TANK.flip_vertical(), TANK);
+        assert_eq!(four.flip_horizontal().flip_horizontal(), four);
+    }
+
+    #[test]
+    fn cells_lists_set_cells_in_reading_order() {
+        let t = TETRAMINO.wrapping_at(2);
+        assert!(t.cells().eq([(1, 0), (0, 1), (1, 1), (2, 1)]));
+        assert!(t.rotate().cells().eq([(0, 0), (0, 1), (1, 1), (0, 2)]));
+        assert_eq!(Figure::default().cells().count(), 0);
+    }
+
+    #[test]
+    fn glyphs_parse_at_compile_time() {
+        const GLYPHS: [Figure; 2] = glyphs_from_ascii(".#.\n###\n\n#..\n###");
+        assert_eq!(GLYPHS[0], TETRAMINO.wrapping_at(2));
+        assert_eq!(GLYPHS[1], TETRAMINO.wrapping_at(5));
+    }
+
+    #[test]
+    fn from_ascii_matches_bit_layout() {
+        let t = Figure::from_ascii(" # \n###");
+        assert_eq!(t, TETRAMINO.wrapping_at(2));
+
+        // Short rows are padded to the widest one
+        let j = Figure::from_ascii("#\n###");
+        assert_eq!(j, TETRAMINO.wrapping_at(5));
+    }
+
+    // Spawn shapes in `TETRAMINO` order
+    const TETRAMINO_ASCII: [&str; 7] = [
+        "####", "##\n##", " # \n###", " ##\n## ", "## \n ##", "#  \n###", "  #\n###",
+    ];
+
+    fn assert_cells(figure: &Figure, ascii: &str) {
+        let rows = ascii.lines().count() as u8;
+        let cols = ascii.lines().map(str::len).max().unwrap() as u8;
+        assert_eq!((figure.width(), figure.height()), (cols, rows), "{}", ascii);
+        for (row, line) in ascii.lines().enumerate() {
+            for col in 0..cols {
+                let expected = line.as_bytes().get(col as usize) == Some(&b'#');
+                assert_eq!(
+                    figure.get_bit(col, row as u8),
+                    expected,
+                    "col {} row {} of\n{}",
+                    col,
+                    row,
+                    ascii
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn get_bit_reads_tetraminoes_top_left_first() {
+        for (idx, ascii) in TETRAMINO_ASCII.iter().enumerate() {
+            assert_cells(&TETRAMINO.wrapping_at(idx as u8), ascii);
+        }
+        // Out of range cells are empty
+        let t = TETRAMINO.wrapping_at(2);
+        assert!(!t.get_bit(3, 0));
+        assert!(!t.get_bit(0, 2));
+    }
+
+    #[test]
+    fn t_rotates_clockwise_through_all_orientations() {
+        let rotations = [" # \n###", "# \n##\n# ", "###\n # ", " #\n##\n #"];
+        let mut t = TETRAMINO.wrapping_at(2);
+        for ascii in rotations.iter().chain(rotations.first()) {
+            assert_cells(&t, ascii);
+            t = t.rotate();
+        }
+    }
+
+    #[test]
+    fn every_rotation_moves_cells_clockwise() {
+        for idx in 0..TETRAMINO_ASCII.len() as u8 {
+            let mut figure = TETRAMINO.wrapping_at(idx);
+            for _ in 0..4 {
+                let rotated = figure.rotate();
+                assert_eq!(rotated.width(), figure.height());
+                assert_eq!(rotated.height(), figure.width());
+                for row in 0..rotated.height() {
+                    for col in 0..rotated.width() {
+                        let source = figure.get_bit(row, figure.height() - 1 - col);
+                        assert_eq!(rotated.get_bit(col, row), source, "piece {}", idx);
+                    }
+                }
+                figure = rotated;
+            }
+            assert_eq!(figure, TETRAMINO.wrapping_at(idx));
+        }
+    }
 }
@@ -0,0 +1,94 @@
+use crate::figure::{glyphs_from_ascii, Figure};
+
+/// Columns of a glyph, `draw_text` leaves one empty column after each
+pub const GLYPH_WIDTH: u8 = 3;
+pub const GLYPH_HEIGHT: u8 = 5;
+
+// Letters in the 5x3 style of the digits, drawn in letters.txt the same way
+// as digits.txt
+pub const LETTERS: [Figure; 26] = glyphs_from_ascii(include_str!("letters.txt"));
+
+/// Blank glyph, it only takes up room
+pub const SPACE: Figure = Figure {
+    data: 0,
+    wh: GLYPH_WIDTH << 4 | GLYPH_HEIGHT,
+};
+
+/// Glyph for `c`, letters in either case and space. `None` for characters
+/// the font has no glyph for.
+pub fn glyph(c: char) -> Option<Figure> {
+    match c {
+        'A'..='Z' => Some(LETTERS[c as usize - 'A' as usize]),
+        'a'..='z' => Some(LETTERS[c as usize - 'a' as usize]),
+        ' ' => Some(SPACE),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::{FrameBuffer, BLACK_IDX, RED_IDX, SCREEN_HEIGHT, SCREEN_WIDTH};
+
+    #[test]
+    fn letters_match_ascii_source() {
+        // The runtime parser reads the same file the same way
+        let mut glyphs = include_str!("letters.txt").split("\n\n");
+        for (c, letter) in ('A'..='Z').zip(LETTERS.iter()) {
+            let source = glyphs.next().expect("missing glyph in letters.txt");
+            let parsed = Figure::from_ascii(source.trim_end_matches('\n'));
+            assert_eq!(&parsed, letter, "Letter {} differs from letters.txt", c);
+            assert_eq!(glyph(c), Some(*letter));
+            assert_eq!(glyph(c.to_ascii_lowercase()), Some(*letter));
+        }
+        assert!(glyphs.next().is_none(), "letters.txt has extra glyphs");
+        // Both parsers could misread the file alike, pin some bits by hand
+        assert_eq!(
+            LETTERS[0],
+            Figure {
+                data: 0b010_101_111_101_101,
+                wh: 3 << 4 | 5,
+            }
+        );
+        assert_eq!(
+            LETTERS[25],
+            Figure {
+                data: 0b111_001_010_100_111,
+                wh: 3 << 4 | 5,
+            }
+        );
+        assert_eq!(glyph(' '), Some(SPACE));
+        assert_eq!(glyph('?'), None);
+    }
+
+    #[test]
+    fn text_is_laid_out_left_to_right_and_clipped() {
+        let mut screen = FrameBuffer::new();
+        screen.draw_text(0, 1, "AB", RED_IDX);
+
+        // A in the first three columns
+        for (y, row) in [".#.", "#.#", "###", "#.#", "#.#"].iter().enumerate() {
+            for (x, cell) in row.chars().enumerate() {
+                let expected = if cell == '#' { RED_IDX } else { BLACK_IDX };
+                assert_eq!(screen.get(x, y + 1), expected, "A at ({}, {})", x, y);
+            }
+        }
+        // One empty column, then B
+        for y in 0..SCREEN_HEIGHT {
+            assert_eq!(screen.get(3, y), BLACK_IDX);
+        }
+        assert_eq!(screen.get(4, 1), RED_IDX);
+        assert_eq!(screen.get(6, 1), BLACK_IDX);
+        assert_eq!(screen.get(6, 2), RED_IDX);
+
+        // The second letter hangs off the right edge and the first off the top
+        let mut clipped = FrameBuffer::new();
+        clipped.draw_text(2, -2, "BA", RED_IDX);
+        // Third row of each letter lands on the top row
+        let top: [u8; SCREEN_WIDTH] = core::array::from_fn(|x| clipped.get(x, 0));
+        let (on, off) = (RED_IDX, BLACK_IDX);
+        assert_eq!(top, [off, off, on, on, off, off, on, on]);
+        assert_eq!(clipped.get(6, 1), RED_IDX);
+        assert_eq!(clipped.get(7, 1), BLACK_IDX);
+    }
+}
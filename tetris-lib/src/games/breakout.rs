@@ -0,0 +1,437 @@
+use smart_leds::RGB8;
+
+use crate::{
+    common::{
+        blink, hud_swap_frames, Background, Difficulty, Dot, FrameBuffer, FrameLimit, Game,
+        GameController, HudSeparator, LedDisplay, Motion, Prng, Rng, ScoreStore, StatusEvent,
+        StatusSink, Timer, BLUE_IDX, GREEN_IDX, HUD_HEART_SLOTS, HUD_LIVES_COLOR, HUD_SCORE_COLOR,
+        HUD_SEPARATOR_Y, LIGHT_BLUE_IDX, ORANGE_IDX, RED_IDX, SCREEN_HEIGHT, SCREEN_WIDTH,
+        YELLOW_IDX,
+    },
+    games::reveal::reveal_and_wait,
+};
+
+const PADDLE_WIDTH: i8 = 3;
+const PADDLE_ROW: i8 = SCREEN_HEIGHT as i8 - 1;
+const PADDLE_MAX_X: i8 = SCREEN_WIDTH as i8 - PADDLE_WIDTH;
+/// Row right below the HUD, the ball bounces back down off its top edge
+const TOP_ROW: i8 = HUD_SEPARATOR_Y as i8 + 1;
+/// First brick row, one free row below the top lets the ball run along
+/// behind the wall
+const BRICKS_Y: usize = TOP_ROW as usize + 1;
+const BRICK_ROWS: usize = 4;
+/// Color of each brick row, top to bottom
+const BRICK_COLORS: [u8; BRICK_ROWS] = [RED_IDX, ORANGE_IDX, YELLOW_IDX, GREEN_IDX];
+/// Where the ball waits on the paddle before each serve
+const SERVE_Y: i8 = PADDLE_ROW - 1;
+/// Frames the ball waits before it is served
+const SERVE_FRAMES: u8 = 20;
+/// Frames the HUD shows the score, then the lives, in turn
+const HUD_SWAP_FRAMES: u16 = hud_swap_frames(FRAME_MS);
+const FRAME_MS: u64 = 40;
+
+#[derive(Clone, Copy, Debug)]
+pub struct BreakoutConfig {
+    /// Frames between two moves of the ball, lower is faster
+    pub ball_frames: u8,
+    /// Sets the lives, one is lost each time the ball passes the paddle
+    pub difficulty: Difficulty,
+    pub background: Background,
+    pub hud_separator: HudSeparator,
+    pub motion: Motion,
+    /// Return from `run` after this many frames, for tests and benchmarks.
+    /// `None` plays until game over.
+    pub max_frames: Option<u64>,
+}
+
+impl Default for BreakoutConfig {
+    fn default() -> Self {
+        Self {
+            ball_frames: 3,
+            difficulty: Difficulty::default(),
+            background: Background::None,
+            hud_separator: HudSeparator::default(),
+            motion: Motion::Full,
+            max_frames: None,
+        }
+    }
+}
+
+pub struct BreakoutGame<'a, D, C, T, R = Prng> {
+    screen: FrameBuffer,
+    display: &'a mut D,
+    controller: &'a mut C,
+    timer: &'a T,
+    prng: R,
+
+    // Leftmost cell of the paddle
+    paddle_x: i8,
+    ball: Dot,
+    // One row up or down per move, and at most one column sideways
+    velocity: Dot,
+    serve_frames: u8,
+    // One byte per brick row packed like `FrameBuffer::row_bits`
+    bricks: [u8; BRICK_ROWS],
+    score: u16,
+    lives: u8,
+    hud_frames: u16,
+    config: BreakoutConfig,
+    status: Option<&'a mut dyn StatusSink>,
+    scores: Option<&'a mut dyn ScoreStore>,
+}
+
+impl<'a, D: LedDisplay, C: GameController, T: Timer, R: Rng> BreakoutGame<'a, D, C, T, R> {
+    pub fn new(prng: R, display: &'a mut D, controller: &'a mut C, timer: &'a T) -> Self {
+        Self::with_config(prng, display, controller, timer, BreakoutConfig::default())
+    }
+
+    pub fn with_config(
+        prng: R,
+        display: &'a mut D,
+        controller: &'a mut C,
+        timer: &'a T,
+        config: BreakoutConfig,
+    ) -> Self {
+        let mut game = Self {
+            screen: FrameBuffer::new(),
+            display,
+            controller,
+            timer,
+            prng,
+            paddle_x: PADDLE_MAX_X / 2,
+            ball: Dot::new(0, 0),
+            velocity: Dot::new(0, 0),
+            serve_frames: 0,
+            bricks: [0xff; BRICK_ROWS],
+            score: 0,
+            lives: config.difficulty.lives(),
+            hud_frames: 0,
+            config,
+            status: None,
+            scores: None,
+        };
+        game.serve();
+        game
+    }
+
+    /// Report score changes and game over to `sink`
+    pub fn with_status_sink(mut self, sink: &'a mut dyn StatusSink) -> Self {
+        self.status = Some(sink);
+        self
+    }
+
+    /// Record a new high score in `store` at game over
+    pub fn with_score_store(mut self, store: &'a mut dyn ScoreStore) -> Self {
+        self.scores = Some(store);
+        self
+    }
+
+    fn notify(&mut self, event: StatusEvent) {
+        if let Some(status) = self.status.as_mut() {
+            status.notify(event);
+        }
+    }
+
+    /// Put the ball on top of the paddle middle, to leave it upwards to a
+    /// random side
+    fn serve(&mut self) {
+        let dx = if self.prng.next_bool() { 1 } else { -1 };
+        self.ball = Dot::new(self.paddle_x + PADDLE_WIDTH / 2, SERVE_Y);
+        self.velocity = Dot::new(dx, -1);
+        self.serve_frames = SERVE_FRAMES;
+    }
+
+    fn has_brick(&self, dot: Dot) -> bool {
+        let row = dot.y as usize;
+        (BRICKS_Y..BRICKS_Y + BRICK_ROWS).contains(&row)
+            && (0..SCREEN_WIDTH as i8).contains(&dot.x)
+            && self.bricks[row - BRICKS_Y] & (0x80 >> dot.x) != 0
+    }
+
+    /// Knock out the brick at `dot` and score it, false if there is none
+    fn hit_brick(&mut self, dot: Dot) -> bool {
+        if !self.has_brick(dot) {
+            return false;
+        }
+        self.bricks[dot.y as usize - BRICKS_Y] &= !(0x80 >> dot.x);
+        self.score += 1;
+        self.notify(StatusEvent::Score(self.score));
+        true
+    }
+
+    /// Bounce `velocity` off the paddle if it covers `x`. The paddle edges
+    /// send the ball off to their side, the middle keeps its angle. True on
+    /// a hit.
+    fn bounce_off_paddle(&mut self, x: i8) -> bool {
+        let offset = x - self.paddle_x;
+        if !(0..PADDLE_WIDTH).contains(&offset) {
+            return false;
+        }
+        self.velocity.y = -self.velocity.y;
+        if offset == 0 {
+            self.velocity.x = -1;
+        } else if offset == PADDLE_WIDTH - 1 {
+            self.velocity.x = 1;
+        }
+        true
+    }
+
+    /// Move the ball one step, bouncing off the walls, the bricks and the
+    /// paddle. A brick hit reflects the ball in place, a side neighbor
+    /// turns it sideways, one above or below vertically and a lone corner
+    /// brick both ways. False once the ball passed the paddle.
+    fn move_ball(&mut self) -> bool {
+        let mut next = self.ball.move_by(self.velocity);
+        if !(0..SCREEN_WIDTH as i8).contains(&next.x) {
+            self.velocity.x = -self.velocity.x;
+        }
+        if next.y < TOP_ROW {
+            self.velocity.y = -self.velocity.y;
+        }
+        next = self.ball.move_by(self.velocity);
+
+        let vertical = Dot::new(self.ball.x, next.y);
+        let horizontal = Dot::new(next.x, self.ball.y);
+        let mut hit = false;
+        if self.hit_brick(vertical) {
+            self.velocity.y = -self.velocity.y;
+            hit = true;
+        }
+        if self.hit_brick(horizontal) {
+            self.velocity.x = -self.velocity.x;
+            hit = true;
+        }
+        if !hit && self.hit_brick(next) {
+            self.velocity = Dot::new(-self.velocity.x, -self.velocity.y);
+            hit = true;
+        }
+        if hit {
+            return true;
+        }
+
+        if next.y == PADDLE_ROW {
+            if !self.bounce_off_paddle(next.x) {
+                return false;
+            }
+            // Leave the paddle row the other way, a new angle into a side
+            // wall is bounced off by the next move
+            self.ball.y += self.velocity.y;
+            self.ball.x = (self.ball.x + self.velocity.x).clamp(0, SCREEN_WIDTH as i8 - 1);
+            return true;
+        }
+
+        self.ball = next;
+        true
+    }
+
+    fn bricks_cleared(&self) -> bool {
+        self.bricks.iter().all(|&row| row == 0)
+    }
+
+    /// Take a life, true if any are left to keep playing
+    fn lose_life(&mut self) -> bool {
+        self.lives = self.lives.saturating_sub(1);
+        self.lives > 0
+    }
+
+    fn draw(&mut self) {
+        self.screen.clear();
+
+        // The score and the lives take turns
+        self.hud_frames = self.hud_frames.wrapping_add(1);
+        if blink(self.hud_frames, HUD_SWAP_FRAMES) {
+            // A long score scrolls from its start every time it shows
+            let frame = self.hud_frames % HUD_SWAP_FRAMES;
+            self.screen
+                .draw_hud_score(self.score, frame, HUD_SCORE_COLOR);
+        } else {
+            self.screen
+                .draw_hud_lives(self.lives, &HUD_HEART_SLOTS, HUD_LIVES_COLOR);
+        }
+        self.screen
+            .draw_hud_separator(HUD_SEPARATOR_Y, self.config.hud_separator);
+
+        for (row, (&bits, &color)) in self.bricks.iter().zip(&BRICK_COLORS).enumerate() {
+            self.screen.set_row_bits(BRICKS_Y + row, bits, color);
+        }
+        for dx in 0..PADDLE_WIDTH {
+            self.screen
+                .set((self.paddle_x + dx) as usize, PADDLE_ROW as usize, BLUE_IDX);
+        }
+        self.screen
+            .set(self.ball.x as usize, self.ball.y as usize, LIGHT_BLUE_IDX);
+    }
+
+    async fn game_over(&mut self) {
+        reveal_and_wait(
+            &mut *self.display,
+            &*self.controller,
+            self.timer,
+            self.score,
+            self.scores.as_deref_mut(),
+        )
+        .await;
+    }
+}
+
+impl<'a, D: LedDisplay, C: GameController, T: Timer, R: Rng> Game for BreakoutGame<'a, D, C, T, R> {
+    async fn run(&mut self) {
+        let mut leds = [RGB8::new(0, 0, 0); 256];
+        let mut frame: u16 = 0;
+
+        let mut frames = FrameLimit::new(self.config.max_frames);
+        loop {
+            let x = self.controller.read_x().await;
+            let paddle_x = (self.paddle_x + x.signum()).clamp(0, PADDLE_MAX_X);
+            if self.serve_frames > 0 {
+                // The ball rides on the paddle until served
+                self.ball.x += paddle_x - self.paddle_x;
+                self.serve_frames -= 1;
+            }
+            self.paddle_x = paddle_x;
+
+            frame = frame.wrapping_add(1);
+            if self.serve_frames == 0
+                && frame.is_multiple_of(u16::from(self.config.ball_frames.max(1)))
+            {
+                if !self.move_ball() {
+                    if !self.lose_life() {
+                        self.notify(StatusEvent::GameOver);
+                        self.game_over().await;
+                        break;
+                    }
+                    self.serve();
+                } else if self.bricks_cleared() {
+                    // A new wall, the ball starts over from the paddle
+                    self.bricks = [0xff; BRICK_ROWS];
+                    self.serve();
+                }
+            }
+
+            self.draw();
+            self.screen.render_over(&mut leds, self.config.background);
+            self.display.write(&leds).await;
+
+            self.timer.sleep_millis(FRAME_MS).await;
+            if frames.tick() {
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{
+        CaptureDisplay, Runner, ScriptedController, StatusRecorder, VirtualTimer,
+    };
+
+    #[test]
+    fn ball_reflects_off_walls_and_ceiling() {
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let mut game = BreakoutGame::new(Prng::new(1), &mut display, &mut controller, &timer);
+        game.bricks = [0; BRICK_ROWS];
+
+        // Side walls
+        game.ball = Dot::new(SCREEN_WIDTH as i8 - 1, 20);
+        game.velocity = Dot::new(1, -1);
+        assert!(game.move_ball());
+        assert_eq!(game.velocity, Dot::new(-1, -1));
+        assert_eq!(game.ball, Dot::new(SCREEN_WIDTH as i8 - 2, 19));
+        game.ball = Dot::new(0, 20);
+        game.velocity = Dot::new(-1, 1);
+        assert!(game.move_ball());
+        assert_eq!(game.ball, Dot::new(1, 21));
+
+        // The ceiling, and the corner with it
+        game.ball = Dot::new(3, TOP_ROW);
+        game.velocity = Dot::new(1, -1);
+        assert!(game.move_ball());
+        assert_eq!(game.ball, Dot::new(4, TOP_ROW + 1));
+        game.ball = Dot::new(0, TOP_ROW);
+        game.velocity = Dot::new(-1, -1);
+        assert!(game.move_ball());
+        assert_eq!(game.velocity, Dot::new(1, 1));
+        assert_eq!(game.ball, Dot::new(1, TOP_ROW + 1));
+
+        // The paddle, and the ball getting past it
+        game.paddle_x = 2;
+        game.ball = Dot::new(3, PADDLE_ROW - 1);
+        game.velocity = Dot::new(0, 1);
+        assert!(game.move_ball());
+        assert_eq!(game.velocity, Dot::new(0, -1));
+        game.ball = Dot::new(6, PADDLE_ROW - 1);
+        game.velocity = Dot::new(1, 1);
+        assert!(!game.move_ball());
+    }
+
+    #[test]
+    fn hit_bricks_are_removed_and_scored() {
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let mut status = StatusRecorder::new();
+        let mut game = BreakoutGame::new(Prng::new(1), &mut display, &mut controller, &timer)
+            .with_status_sink(&mut status);
+        let bottom = (BRICKS_Y + BRICK_ROWS) as i8;
+
+        // Straight up into the bottom row
+        game.ball = Dot::new(2, bottom);
+        game.velocity = Dot::new(0, -1);
+        assert!(game.move_ball());
+        assert!(!game.has_brick(Dot::new(2, bottom - 1)));
+        assert_eq!(game.bricks[BRICK_ROWS - 1], 0b1101_1111);
+        assert_eq!(game.velocity, Dot::new(0, 1));
+        assert_eq!(game.ball, Dot::new(2, bottom));
+
+        // Into the gap, then diagonally where bricks are on both sides of
+        // the corner, which both go
+        game.velocity = Dot::new(0, -1);
+        assert!(game.move_ball());
+        assert_eq!(game.ball, Dot::new(2, bottom - 1));
+        game.velocity = Dot::new(1, -1);
+        assert!(game.move_ball());
+        assert_eq!(game.ball, Dot::new(2, bottom - 1));
+        assert_eq!(game.velocity, Dot::new(-1, 1));
+        assert!(!game.has_brick(Dot::new(2, bottom - 2)));
+        assert!(!game.has_brick(Dot::new(3, bottom - 1)));
+        assert!(game.has_brick(Dot::new(3, bottom - 2)));
+        assert_eq!(game.score, 3);
+
+        game.bricks = [0; BRICK_ROWS];
+        assert!(game.bricks_cleared());
+        assert_eq!(
+            status.events,
+            [
+                StatusEvent::Score(1),
+                StatusEvent::Score(2),
+                StatusEvent::Score(3)
+            ]
+        );
+    }
+
+    #[test]
+    fn last_missed_ball_ends_the_game() {
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let input = controller.clone();
+        let mut game = BreakoutGame::new(Prng::new(1), &mut display, &mut controller, &timer);
+        game.lives = 1;
+        game.serve_frames = 0;
+        game.paddle_x = 0;
+        game.ball = Dot::new(6, PADDLE_ROW - 1);
+        game.velocity = Dot::new(1, 1);
+        input.set_x(-1);
+        let mut runner = Runner::new(game.run());
+        while runner.step() {
+            input.press_a();
+        }
+        drop(runner);
+
+        assert_eq!(game.lives, 0);
+    }
+}
@@ -0,0 +1,351 @@
+use smart_leds::RGB8;
+
+use crate::{
+    common::{
+        Background, FrameBuffer, FrameLimit, Game, GameController, HudSeparator, LedDisplay,
+        Motion, Prng, Rng, ScoreStore, StatusEvent, StatusSink, Timer, HUD_SCORE_COLOR,
+        HUD_SEPARATOR_Y, LIGHT_GREEN_IDX, SCREEN_HEIGHT, SCREEN_WIDTH, YELLOW_IDX,
+    },
+    games::reveal::reveal_and_wait,
+};
+
+/// Column the bird flies in, pipes come at it from the right
+const BIRD_X: i8 = 1;
+/// Highest row the bird can fly in, above it is the HUD
+const PLAY_TOP: i8 = HUD_SEPARATOR_Y as i8 + 1;
+const PLAY_BOTTOM: i8 = SCREEN_HEIGHT as i8 - 1;
+/// The bird height is kept in fractions of a row for smooth falling
+const SUBROWS: i16 = 8;
+/// Speed gained every frame, in subrows per frame
+const GRAVITY: i16 = 1;
+/// Fastest fall, one row per frame
+const MAX_FALL: i16 = SUBROWS;
+/// Upward speed right after a flap
+const FLAP_SPEED: i16 = 6;
+/// Rows of each opening the bird flies through
+const GAP_HEIGHT: i8 = 8;
+const NUM_PIPES: usize = 2;
+/// Columns from one pipe to the next
+const PIPE_SPACING: i8 = 5;
+const FRAME_MS: u64 = 40;
+
+#[derive(Clone, Copy, Debug)]
+pub struct FlappyConfig {
+    /// Frames between two steps of the pipes towards the bird, lower is
+    /// faster
+    pub scroll_frames: u8,
+    pub background: Background,
+    pub hud_separator: HudSeparator,
+    pub motion: Motion,
+    /// Return from `run` after this many frames, for tests and benchmarks.
+    /// `None` plays until game over.
+    pub max_frames: Option<u64>,
+}
+
+impl Default for FlappyConfig {
+    fn default() -> Self {
+        Self {
+            scroll_frames: 6,
+            background: Background::None,
+            hud_separator: HudSeparator::default(),
+            motion: Motion::Full,
+            max_frames: None,
+        }
+    }
+}
+
+/// Column of pipe with an opening of `GAP_HEIGHT` rows from `gap_y` down
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Pipe {
+    x: i8,
+    gap_y: i8,
+}
+
+impl Pipe {
+    fn blocks(&self, x: i8, y: i8) -> bool {
+        self.x == x && !(self.gap_y..self.gap_y + GAP_HEIGHT).contains(&y)
+    }
+}
+
+pub struct FlappyGame<'a, D, C, T, R = Prng> {
+    screen: FrameBuffer,
+    display: &'a mut D,
+    controller: &'a mut C,
+    timer: &'a T,
+    prng: R,
+
+    // In subrows, see `SUBROWS`
+    bird_y: i16,
+    velocity: i16,
+    // The bird hovers and the pipes wait until the first flap
+    started: bool,
+    pipes: [Pipe; NUM_PIPES],
+    // Pipes passed
+    score: u16,
+    config: FlappyConfig,
+    status: Option<&'a mut dyn StatusSink>,
+    scores: Option<&'a mut dyn ScoreStore>,
+    // Frames drawn, scrolls a score too long for the HUD
+    hud_frames: u16,
+}
+
+impl<'a, D: LedDisplay, C: GameController, T: Timer, R: Rng> FlappyGame<'a, D, C, T, R> {
+    pub fn new(prng: R, display: &'a mut D, controller: &'a mut C, timer: &'a T) -> Self {
+        Self::with_config(prng, display, controller, timer, FlappyConfig::default())
+    }
+
+    pub fn with_config(
+        prng: R,
+        display: &'a mut D,
+        controller: &'a mut C,
+        timer: &'a T,
+        config: FlappyConfig,
+    ) -> Self {
+        let mut game = Self {
+            screen: FrameBuffer::new(),
+            display,
+            controller,
+            timer,
+            prng,
+            bird_y: i16::from((PLAY_TOP + PLAY_BOTTOM) / 2) * SUBROWS,
+            velocity: 0,
+            started: false,
+            pipes: [Pipe { x: 0, gap_y: 0 }; NUM_PIPES],
+            score: 0,
+            config,
+            status: None,
+            scores: None,
+            hud_frames: 0,
+        };
+        // The first pipe enters right after the start
+        for i in 0..NUM_PIPES {
+            game.pipes[i] = game.new_pipe(SCREEN_WIDTH as i8 + PIPE_SPACING * i as i8);
+        }
+        game
+    }
+
+    /// Report score changes and game over to `sink`
+    pub fn with_status_sink(mut self, sink: &'a mut dyn StatusSink) -> Self {
+        self.status = Some(sink);
+        self
+    }
+
+    /// Record a new high score in `store` at game over
+    pub fn with_score_store(mut self, store: &'a mut dyn ScoreStore) -> Self {
+        self.scores = Some(store);
+        self
+    }
+
+    fn notify(&mut self, event: StatusEvent) {
+        if let Some(status) = self.status.as_mut() {
+            status.notify(event);
+        }
+    }
+
+    /// Pipe at column `x` with its opening at a random height
+    fn new_pipe(&mut self, x: i8) -> Pipe {
+        let range = PLAY_BOTTOM - PLAY_TOP - GAP_HEIGHT;
+        let gap_y = PLAY_TOP + 1 + self.prng.next_range(range as u8) as i8;
+        Pipe { x, gap_y }
+    }
+
+    fn bird_row(&self) -> i8 {
+        self.bird_y.div_euclid(SUBROWS) as i8
+    }
+
+    fn flap(&mut self) {
+        self.velocity = -FLAP_SPEED;
+    }
+
+    /// Speed up the fall by gravity and move the bird by its speed
+    fn fall(&mut self) {
+        self.velocity = (self.velocity + GRAVITY).min(MAX_FALL);
+        self.bird_y += self.velocity;
+    }
+
+    /// Move every pipe one column towards the bird, scoring the ones that
+    /// just passed it. A pipe leaving the screen comes back behind the last
+    /// one with a new opening.
+    fn scroll_pipes(&mut self) {
+        for i in 0..NUM_PIPES {
+            self.pipes[i].x -= 1;
+            if self.pipes[i].x == BIRD_X - 1 {
+                self.score += 1;
+                self.notify(StatusEvent::Score(self.score));
+            }
+        }
+        for i in 0..NUM_PIPES {
+            if self.pipes[i].x < 0 {
+                let last = self.pipes.iter().map(|pipe| pipe.x).max().unwrap_or(0);
+                self.pipes[i] = self.new_pipe(last + PIPE_SPACING);
+            }
+        }
+    }
+
+    /// True if the bird flew into a pipe, the ceiling or the floor
+    fn crashed(&self) -> bool {
+        let row = self.bird_row();
+        !(PLAY_TOP..=PLAY_BOTTOM).contains(&row)
+            || self.pipes.iter().any(|pipe| pipe.blocks(BIRD_X, row))
+    }
+
+    fn draw(&mut self) {
+        self.screen.clear();
+        self.hud_frames = self.hud_frames.wrapping_add(1);
+        self.screen
+            .draw_hud_score(self.score, self.hud_frames, HUD_SCORE_COLOR);
+        self.screen
+            .draw_hud_separator(HUD_SEPARATOR_Y, self.config.hud_separator);
+
+        for pipe in self.pipes {
+            if !(0..SCREEN_WIDTH as i8).contains(&pipe.x) {
+                continue;
+            }
+            for y in PLAY_TOP..=PLAY_BOTTOM {
+                if pipe.blocks(pipe.x, y) {
+                    self.screen
+                        .set(pipe.x as usize, y as usize, LIGHT_GREEN_IDX);
+                }
+            }
+        }
+        let row = self.bird_row();
+        if (PLAY_TOP..=PLAY_BOTTOM).contains(&row) {
+            self.screen.set(BIRD_X as usize, row as usize, YELLOW_IDX);
+        }
+    }
+
+    async fn game_over(&mut self) {
+        reveal_and_wait(
+            &mut *self.display,
+            &*self.controller,
+            self.timer,
+            self.score,
+            self.scores.as_deref_mut(),
+        )
+        .await;
+    }
+}
+
+impl<'a, D: LedDisplay, C: GameController, T: Timer, R: Rng> Game for FlappyGame<'a, D, C, T, R> {
+    async fn run(&mut self) {
+        let mut leds = [RGB8::new(0, 0, 0); 256];
+        let mut frame: u16 = 0;
+
+        let mut frames = FrameLimit::new(self.config.max_frames);
+        loop {
+            if self.controller.joystick_was_pressed() {
+                self.started = true;
+                self.flap();
+            }
+
+            if self.started {
+                self.fall();
+                frame = frame.wrapping_add(1);
+                if frame.is_multiple_of(u16::from(self.config.scroll_frames.max(1))) {
+                    self.scroll_pipes();
+                }
+            }
+
+            let crashed = self.crashed();
+            self.draw();
+            self.screen.render_over(&mut leds, self.config.background);
+            self.display.write(&leds).await;
+
+            if crashed {
+                self.notify(StatusEvent::GameOver);
+                self.game_over().await;
+                break;
+            }
+
+            self.timer.sleep_millis(FRAME_MS).await;
+            if frames.tick() {
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{CaptureDisplay, Runner, ScriptedController, VirtualTimer};
+
+    #[test]
+    fn flap_turns_a_fall_into_a_climb() {
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let mut game = FlappyGame::new(Prng::new(1), &mut display, &mut controller, &timer);
+
+        // Falling speeds up to one row per frame
+        for _ in 0..20 {
+            game.fall();
+        }
+        assert_eq!(game.velocity, MAX_FALL);
+
+        game.flap();
+        assert_eq!(game.velocity, -FLAP_SPEED);
+        let y = game.bird_y;
+        game.fall();
+        assert!(game.bird_y < y);
+    }
+
+    #[test]
+    fn passing_a_pipe_scores() {
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let mut game = FlappyGame::new(Prng::new(1), &mut display, &mut controller, &timer);
+        let row = game.bird_row();
+
+        // Through the opening
+        game.pipes[0] = Pipe {
+            x: BIRD_X + 1,
+            gap_y: row - 1,
+        };
+        game.scroll_pipes();
+        assert!(!game.crashed());
+        assert_eq!(game.score, 0);
+        game.scroll_pipes();
+        assert_eq!(game.score, 1);
+        assert!(!game.crashed());
+
+        // The pipe left the screen and was put back behind the other one
+        game.scroll_pipes();
+        assert_eq!(game.pipes[0].x, game.pipes[1].x + PIPE_SPACING);
+
+        // Into a pipe
+        game.pipes[1] = Pipe {
+            x: BIRD_X,
+            gap_y: row + 1,
+        };
+        assert!(game.crashed());
+    }
+
+    #[test]
+    fn falling_to_the_floor_ends_the_game() {
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let input = controller.clone();
+        let capture = display.clone();
+        let mut game = FlappyGame::new(Prng::new(1), &mut display, &mut controller, &timer);
+
+        // Nothing moves until the first flap
+        let mut runner = Runner::new(game.run());
+        runner.steps(10);
+        assert!(capture.frame(0) == capture.frame(9));
+        input.press_joystick();
+        let mut steps = 0;
+        while runner.step() {
+            steps += 1;
+            assert!(steps < 1000);
+            if steps > 100 {
+                input.press_a();
+            }
+        }
+        drop(runner);
+
+        assert!(game.crashed());
+    }
+}
@@ -2,8 +2,9 @@ use smart_leds::RGB8;
 
 use crate::{
     common::{
-        get_pixel, set_pixel, FrameBuffer, Game, GameController, LedDisplay, Prng, Timer,
-        BLACK_IDX, BRICK_IDX, GREEN_IDX, PINK_IDX, SCREEN_HEIGHT, SCREEN_WIDTH, YELLOW_IDX,
+        blink, get_pixel, set_pixel, Dot, FrameBuffer, FrameLimit, Game, GameController,
+        LedDisplay, Motion, Prng, Rng, Timer, BLACK_IDX, BRICK_IDX, GREEN_IDX, LIGHT_GREEN_IDX,
+        PINK_IDX, SCREEN_HEIGHT, SCREEN_WIDTH, YELLOW_IDX,
     },
     log::{debug, info},
 };
@@ -15,23 +16,158 @@ enum GameState {
     DrawMode,
 }
 
-pub struct LifeGame<'a, D, C, T> {
+// The draw mode cursor lights up for this many frames, then shows the cell
+const CURSOR_BLINK_FRAMES: u16 = 10;
+
+// The draw mode cursor stays in the game area below the HUD
+const CURSOR_MIN: Dot = Dot::new(0, 6);
+const CURSOR_MAX: Dot = Dot::new(SCREEN_WIDTH as i8 - 1, SCREEN_HEIGHT as i8 - 1);
+
+// Speeds cycled through with A while paused, from slowest to fastest
+const MAX_SPEED: u8 = 4;
+
+// Cell colors by generations survived, the last one sticks
+const AGE_COLORS: [u8; 3] = [GREEN_IDX, LIGHT_GREEN_IDX, YELLOW_IDX];
+
+/// Cellular automaton rules as neighbor count bitmasks: bit `n` set in
+/// `birth` makes a dead cell with `n` live neighbors come alive, in
+/// `survive` keeps a live one alive
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rules {
+    pub birth: u16,
+    pub survive: u16,
+}
+
+impl Rules {
+    /// Conway's Game of Life, B3/S23
+    pub const CONWAY: Self = Self::new(&[3], &[2, 3]);
+    /// HighLife, B36/S23, home of the replicator
+    pub const HIGHLIFE: Self = Self::new(&[3, 6], &[2, 3]);
+    /// Seeds, B2/S, every live cell dies each generation
+    pub const SEEDS: Self = Self::new(&[2], &[]);
+
+    /// Rules from birth and survival neighbor counts, `B36/S23` is
+    /// `Rules::new(&[3, 6], &[2, 3])`. Counts above 8 are ignored.
+    pub const fn new(birth: &[u8], survive: &[u8]) -> Self {
+        Self {
+            birth: mask(birth),
+            survive: mask(survive),
+        }
+    }
+
+    /// Parse rules in B/S notation, e.g. `B36/S23` or `b2/s`
+    pub fn parse(notation: &str) -> Option<Self> {
+        let (birth, survive) = notation.split_once('/')?;
+        let birth = birth.strip_prefix(['B', 'b'])?;
+        let survive = survive.strip_prefix(['S', 's'])?;
+        Some(Self {
+            birth: parse_counts(birth)?,
+            survive: parse_counts(survive)?,
+        })
+    }
+
+    pub fn born(&self, neighbors: u8) -> bool {
+        self.birth & (1 << neighbors) != 0
+    }
+
+    pub fn survives(&self, neighbors: u8) -> bool {
+        self.survive & (1 << neighbors) != 0
+    }
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Self::CONWAY
+    }
+}
+
+const fn mask(counts: &[u8]) -> u16 {
+    let mut mask = 0;
+    let mut i = 0;
+    while i < counts.len() {
+        if counts[i] <= 8 {
+            mask |= 1 << counts[i];
+        }
+        i += 1;
+    }
+    mask
+}
+
+fn parse_counts(counts: &str) -> Option<u16> {
+    counts.chars().try_fold(0, |mask, c| match c.to_digit(10) {
+        Some(count) if count <= 8 => Some(mask | 1 << count),
+        _ => None,
+    })
+}
+
+// Rule sets cycled through after the last pattern, with their HUD color
+const RULE_SETS: [(Rules, u8); 3] = [
+    (Rules::CONWAY, GREEN_IDX),
+    (Rules::HIGHLIFE, YELLOW_IDX),
+    (Rules::SEEDS, PINK_IDX),
+];
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LifeConfig {
+    /// Color cells by how long they have been alive instead of plain green
+    pub aging: bool,
+    /// Rules to start with, A cycles through the built-in rule sets once
+    /// every pattern has been shown
+    pub rules: Rules,
+    /// With reduced motion the draw mode cursor stays lit instead of
+    /// blinking
+    pub motion: Motion,
+    /// Return from `run` after this many frames, for tests and benchmarks.
+    /// `None` plays until game over.
+    pub max_frames: Option<u64>,
+}
+
+pub struct LifeGame<'a, D, C, T, R = Prng> {
     screen: FrameBuffer,
     next_screen: FrameBuffer,
     display: &'a mut D,
     controller: &'a mut C,
     timer: &'a T,
-    prng: Prng,
+    prng: R,
     generation: u32,
     state: GameState,
     pattern_index: usize,
     cursor_x: usize,
     cursor_y: usize,
     blink_counter: u8,
+    // Generations each cell has survived, parallel to the screen
+    ages: FrameBuffer,
+    rules: Rules,
+    config: LifeConfig,
 }
 
-impl<'a, D: LedDisplay, C: GameController, T: Timer> LifeGame<'a, D, C, T> {
-    pub fn new(prng: Prng, display: &'a mut D, controller: &'a mut C, timer: &'a T) -> Self {
+impl<'a, D: LedDisplay, C: GameController, T: Timer, R: Rng> LifeGame<'a, D, C, T, R> {
+    pub fn new(prng: R, display: &'a mut D, controller: &'a mut C, timer: &'a T) -> Self {
+        Self::with_config(prng, display, controller, timer, LifeConfig::default())
+    }
+
+    /// Play by other `rules` than Conway's, e.g. `Rules::HIGHLIFE`
+    pub fn with_rules(
+        prng: R,
+        display: &'a mut D,
+        controller: &'a mut C,
+        timer: &'a T,
+        rules: Rules,
+    ) -> Self {
+        let config = LifeConfig {
+            rules,
+            ..LifeConfig::default()
+        };
+        Self::with_config(prng, display, controller, timer, config)
+    }
+
+    pub fn with_config(
+        prng: R,
+        display: &'a mut D,
+        controller: &'a mut C,
+        timer: &'a T,
+        config: LifeConfig,
+    ) -> Self {
         let mut game = Self {
             screen: FrameBuffer::new(),
             next_screen: FrameBuffer::new(),
@@ -45,6 +181,9 @@ impl<'a, D: LedDisplay, C: GameController, T: Timer> LifeGame<'a, D, C, T> {
             cursor_x: SCREEN_WIDTH / 2,
             cursor_y: (SCREEN_HEIGHT + 6) / 2, // Start cursor in middle of game area
             blink_counter: 0,
+            ages: FrameBuffer::new(),
+            rules: config.rules,
+            config,
         };
 
         game.set_pattern();
@@ -53,6 +192,7 @@ impl<'a, D: LedDisplay, C: GameController, T: Timer> LifeGame<'a, D, C, T> {
 
     fn set_pattern(&mut self) {
         self.screen.clear();
+        self.ages.clear();
         self.generation = 0;
 
         let current_pattern = PATTERNS[self.pattern_index];
@@ -79,12 +219,43 @@ impl<'a, D: LedDisplay, C: GameController, T: Timer> LifeGame<'a, D, C, T> {
         }
     }
 
+    /// Replace the board with a pattern decoded by `parse_rle`, e.g. one
+    /// pasted from a pattern collection
+    pub fn load_pattern(&mut self, pattern: &RlePattern) {
+        self.screen.clear();
+        self.ages.clear();
+        self.generation = 0;
+        for &(x, y) in pattern.cells() {
+            self.screen.set(x as usize, y as usize, GREEN_IDX);
+        }
+    }
+
     fn next_pattern(&mut self) {
         self.pattern_index = (self.pattern_index + 1) % PATTERNS.len();
+        if self.pattern_index == 0 {
+            self.next_rules();
+        }
         debug!("Switching to pattern {}", self.pattern_index);
         self.set_pattern();
     }
 
+    fn next_rules(&mut self) {
+        let next = RULE_SETS
+            .iter()
+            .position(|&(rules, _)| rules == self.rules)
+            .map_or(0, |i| (i + 1) % RULE_SETS.len());
+        self.rules = RULE_SETS[next].0;
+        info!("Switching to rule set {}", next);
+    }
+
+    // HUD color of the current rules, plain green for custom ones
+    fn rules_color(&self) -> u8 {
+        RULE_SETS
+            .iter()
+            .find(|&&(rules, _)| rules == self.rules)
+            .map_or(GREEN_IDX, |&(_, color)| color)
+    }
+
     fn count_neighbors(&self, x: usize, y: usize) -> u8 {
         let mut count = 0;
         for dx in -1..=1 {
@@ -133,23 +304,29 @@ impl<'a, D: LedDisplay, C: GameController, T: Timer> LifeGame<'a, D, C, T> {
             }
         }
 
-        // Apply Conway's rules to game area
+        // Apply the rules to game area
         let mut _alive_count = 0;
         for x in 0..SCREEN_WIDTH {
             for y in 6..SCREEN_HEIGHT {
                 let neighbors = self.count_neighbors(x, y);
                 let is_alive = self.screen.get(x, y) != BLACK_IDX;
 
-                // Conway's Game of Life rules:
-                // 1. Live cell with 2-3 neighbors survives
-                let stays_alive = is_alive && (neighbors == 2 || neighbors == 3);
-                // 2. Dead cell with exactly 3 neighbors becomes alive
-                let reborns = !is_alive && neighbors == 3;
-                // 3. All other cells die or stay dead
+                let stays_alive = is_alive && self.rules.survives(neighbors);
+                let reborns = !is_alive && self.rules.born(neighbors);
+                // All other cells die or stay dead
                 if stays_alive || reborns {
-                    self.next_screen.set(x, y, GREEN_IDX);
+                    let age = if stays_alive {
+                        self.ages.get(x, y).saturating_add(1)
+                    } else {
+                        0
+                    };
+                    self.ages.set(x, y, age);
+                    self.next_screen.set(x, y, self.cell_color(age));
                     _alive_count += 1;
-                } // else: cell dies or stays dead (already cleared)
+                } else {
+                    // Cell dies or stays dead (already cleared)
+                    self.ages.set(x, y, 0);
+                }
             }
         }
 
@@ -157,7 +334,7 @@ impl<'a, D: LedDisplay, C: GameController, T: Timer> LifeGame<'a, D, C, T> {
         core::mem::swap(&mut self.screen, &mut self.next_screen);
         self.generation += 1;
 
-        if self.generation % 50 == 0 {
+        if self.generation.is_multiple_of(50) {
             debug!(
                 "Generation {}, alive cells: {}",
                 self.generation, _alive_count
@@ -165,13 +342,18 @@ impl<'a, D: LedDisplay, C: GameController, T: Timer> LifeGame<'a, D, C, T> {
         }
     }
 
+    fn cell_color(&self, age: u8) -> u8 {
+        if self.config.aging {
+            AGE_COLORS[(age as usize).min(AGE_COLORS.len() - 1)]
+        } else {
+            GREEN_IDX
+        }
+    }
+
     fn draw_ui(&mut self, speed: u8) {
         // Clear score area
-        for x in 0..SCREEN_WIDTH {
-            for y in 0..5 {
-                self.screen.set(x, y, BLACK_IDX);
-            }
-        }
+        self.screen
+            .fill_rect(0, 0, SCREEN_WIDTH as u8, 5, BLACK_IDX);
 
         // Show pause indicator or draw mode indicator
         if self.state == GameState::Paused {
@@ -180,6 +362,10 @@ impl<'a, D: LedDisplay, C: GameController, T: Timer> LifeGame<'a, D, C, T> {
                 self.screen.set(2, y, YELLOW_IDX);
                 self.screen.set(4, y, YELLOW_IDX);
             }
+            // Below it a dot moves one step with every generation, so single
+            // steps show even when the cells don't change
+            let tick = self.generation as usize % SCREEN_WIDTH;
+            self.screen.set(tick, 4, GREEN_IDX);
         } else if self.state == GameState::DrawMode {
             // Draw pencil icon (simple representation)
             self.screen.set(1, 1, PINK_IDX);
@@ -188,13 +374,14 @@ impl<'a, D: LedDisplay, C: GameController, T: Timer> LifeGame<'a, D, C, T> {
             self.screen.set(4, 4, PINK_IDX);
         } else {
             // Display pattern index as individual pixels (one pixel per pattern)
+            // in the color of the current rules
+            let color = self.rules_color();
             for i in 0..self.pattern_index {
-                self.screen
-                    .set(i % SCREEN_WIDTH, i / SCREEN_WIDTH, GREEN_IDX);
+                self.screen.set(i % SCREEN_WIDTH, i / SCREEN_WIDTH, color);
             }
 
             let mut available_row = self.pattern_index / SCREEN_WIDTH;
-            if self.pattern_index % SCREEN_WIDTH > 0 {
+            if !self.pattern_index.is_multiple_of(SCREEN_WIDTH) {
                 available_row += 1;
             }
             if available_row < 5 {
@@ -219,7 +406,9 @@ impl<'a, D: LedDisplay, C: GameController, T: Timer> LifeGame<'a, D, C, T> {
     }
 
     fn draw_cursor(&mut self, leds: &mut [RGB8; 256]) {
-        let color = if self.blink_counter >> 1 > 5 {
+        let lit = self.config.motion.is_reduced()
+            || !blink(self.blink_counter.into(), CURSOR_BLINK_FRAMES);
+        let color = if lit {
             PINK_IDX
         } else {
             get_pixel(leds, self.cursor_x, self.cursor_y)
@@ -228,7 +417,7 @@ impl<'a, D: LedDisplay, C: GameController, T: Timer> LifeGame<'a, D, C, T> {
     }
 }
 
-impl<'a, D: LedDisplay, C: GameController, T: Timer> Game for LifeGame<'a, D, C, T> {
+impl<'a, D: LedDisplay, C: GameController, T: Timer, R: Rng> Game for LifeGame<'a, D, C, T, R> {
     async fn run(&mut self) {
         let mut step = 0;
         let round: u8 = 20;
@@ -240,6 +429,7 @@ impl<'a, D: LedDisplay, C: GameController, T: Timer> Game for LifeGame<'a, D, C,
         let mut last_y_input = 0;
         let mut cursor_move_counter: u8 = 0;
 
+        let mut frames = FrameLimit::new(self.config.max_frames);
         loop {
             // Handle input based on current state
             match self.state {
@@ -264,13 +454,12 @@ impl<'a, D: LedDisplay, C: GameController, T: Timer> Game for LifeGame<'a, D, C,
                         self.state = GameState::Running;
                     }
 
-                    // Speed control with A and B buttons
+                    // A cycles through the speeds, B steps one generation
                     if self.controller.a_was_pressed() {
-                        speed = speed.saturating_sub(1).clamp(1, 4);
-                        info!("Speed has increased to {}", speed);
+                        speed = speed % MAX_SPEED + 1;
+                        info!("Speed set to {}", speed);
                     } else if self.controller.b_was_pressed() {
-                        speed = speed.saturating_add(1).clamp(1, 4);
-                        info!("Speed has dropped to {}", speed);
+                        self.next_generation();
                     }
                 }
                 GameState::DrawMode => {
@@ -285,20 +474,14 @@ impl<'a, D: LedDisplay, C: GameController, T: Timer> Game for LifeGame<'a, D, C,
                     let input_changed = x_delta != last_x_input || y_delta != last_y_input;
 
                     // Allow movement on input change OR every 8 frames for held input
-                    let should_move = input_changed || (cursor_move_counter % 8 == 0);
+                    let should_move = input_changed || cursor_move_counter.is_multiple_of(8);
 
                     if should_move {
-                        if x_delta != 0 {
-                            let new_x =
-                                (self.cursor_x as i8 + x_delta).clamp(0, SCREEN_WIDTH as i8 - 1);
-                            self.cursor_x = new_x as usize;
-                        }
-
-                        if y_delta != 0 {
-                            let new_y =
-                                (self.cursor_y as i8 + y_delta).clamp(6, SCREEN_HEIGHT as i8 - 1);
-                            self.cursor_y = new_y as usize;
-                        }
+                        let cursor = Dot::new(self.cursor_x as i8, self.cursor_y as i8)
+                            .move_by(Dot::new(x_delta, y_delta))
+                            .clamped(CURSOR_MIN, CURSOR_MAX);
+                        self.cursor_x = cursor.x as usize;
+                        self.cursor_y = cursor.y as usize;
                     }
 
                     // Update last input state
@@ -308,6 +491,7 @@ impl<'a, D: LedDisplay, C: GameController, T: Timer> Game for LifeGame<'a, D, C,
                     // Toggle cell with joystick press
                     if self.controller.joystick_was_pressed() {
                         let current_color = self.screen.get(self.cursor_x, self.cursor_y);
+                        self.ages.set(self.cursor_x, self.cursor_y, 0);
                         if current_color == BLACK_IDX {
                             self.screen.set(self.cursor_x, self.cursor_y, GREEN_IDX);
                         } else {
@@ -330,7 +514,7 @@ impl<'a, D: LedDisplay, C: GameController, T: Timer> Game for LifeGame<'a, D, C,
             }
 
             // Update blink counter for cursor
-            self.blink_counter = (self.blink_counter + 1) % 20; // Blink every 20 frames
+            self.blink_counter = (self.blink_counter + 1) % (2 * CURSOR_BLINK_FRAMES as u8);
 
             // Draw everything
             self.draw_ui(speed);
@@ -345,8 +529,80 @@ impl<'a, D: LedDisplay, C: GameController, T: Timer> Game for LifeGame<'a, D, C,
 
             step += 1;
             self.timer.sleep_millis(delay as u64).await;
+            if frames.tick() {
+                return;
+            }
+        }
+    }
+}
+
+/// Most live cells an `RlePattern` holds, the whole game area
+pub const MAX_RLE_CELLS: usize = SCREEN_WIDTH * (SCREEN_HEIGHT - 6);
+
+/// Live cells decoded by `parse_rle`, in screen coordinates
+#[derive(Clone, Debug)]
+pub struct RlePattern {
+    cells: [(i8, i8); MAX_RLE_CELLS],
+    len: usize,
+}
+
+impl RlePattern {
+    pub fn cells(&self) -> &[(i8, i8)] {
+        &self.cells[..self.len]
+    }
+}
+
+/// Decode a pattern in the Game of Life RLE format, e.g. `bo$2bo$3o!` for
+/// a glider, with its top-left corner at `origin`. The `x = ..` header and
+/// `#` comment lines are skipped. Cells outside the game area below the
+/// HUD are dropped, so large patterns show cropped. Returns `None` on a
+/// character outside the format.
+pub fn parse_rle(rle: &str, origin: (i8, i8)) -> Option<RlePattern> {
+    let mut pattern = RlePattern {
+        cells: [(0, 0); MAX_RLE_CELLS],
+        len: 0,
+    };
+    let (mut col, mut row) = (0i32, 0i32);
+    let mut run: Option<i32> = None;
+
+    let body = rle
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with('#') && !line.starts_with('x') && !line.is_empty());
+    'lines: for line in body {
+        for c in line.chars() {
+            if let Some(digit) = c.to_digit(10) {
+                // Runs longer than the screen only matter as far as it goes
+                run = Some((run.unwrap_or(0) * 10 + digit as i32).min(SCREEN_HEIGHT as i32));
+                continue;
+            }
+            let count = run.take().unwrap_or(1);
+            match c {
+                'b' | '.' => col += count,
+                'o' | 'A'..='Z' => {
+                    for _ in 0..count {
+                        let x = origin.0 as i32 + col;
+                        let y = origin.1 as i32 + row;
+                        let on_board = (0..SCREEN_WIDTH as i32).contains(&x)
+                            && (6..SCREEN_HEIGHT as i32).contains(&y);
+                        if on_board && pattern.len < MAX_RLE_CELLS {
+                            pattern.cells[pattern.len] = (x as i8, y as i8);
+                            pattern.len += 1;
+                        }
+                        col += 1;
+                    }
+                }
+                '$' => {
+                    row += count;
+                    col = 0;
+                }
+                '!' => break 'lines,
+                c if c.is_whitespace() => {}
+                _ => return None,
+            }
         }
     }
+    Some(pattern)
 }
 
 // Define Conway patterns showcasing different behaviors
@@ -573,3 +829,260 @@ static PATTERNS: &[Option<&[(i8, i8)]>] = &[
     // Thunderbird - methuselah
     Some(&[(1, 10), (2, 10), (3, 10), (2, 11), (2, 12), (2, 13)]),
 ];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{CaptureDisplay, Runner, ScriptedController, VirtualTimer};
+
+    fn seed<D, C, T, R>(game: &mut LifeGame<'_, D, C, T, R>, cells: &[(usize, usize)]) {
+        game.screen.clear();
+        for &(x, y) in cells {
+            game.screen.set(x, y, GREEN_IDX);
+        }
+    }
+
+    fn assert_alive<D, C, T, R>(game: &LifeGame<'_, D, C, T, R>, cells: &[(usize, usize)]) {
+        for y in 6..SCREEN_HEIGHT {
+            for x in 0..SCREEN_WIDTH {
+                let alive = game.screen.get(x, y) != BLACK_IDX;
+                assert_eq!(alive, cells.contains(&(x, y)), "cell ({}, {})", x, y);
+            }
+        }
+    }
+
+    type TestGame<'a> = LifeGame<'a, CaptureDisplay, ScriptedController, VirtualTimer>;
+
+    fn with_config(config: LifeConfig, check: impl FnOnce(&mut TestGame<'_>)) {
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let mut game =
+            LifeGame::with_config(Prng::new(1), &mut display, &mut controller, &timer, config);
+        check(&mut game);
+    }
+
+    fn with_game(check: impl FnOnce(&mut TestGame<'_>)) {
+        with_config(LifeConfig::default(), check);
+    }
+
+    #[test]
+    fn reduced_motion_keeps_cursor_lit() {
+        for (motion, always_lit) in [(Motion::Full, false), (Motion::Reduced, true)] {
+            let config = LifeConfig {
+                motion,
+                ..LifeConfig::default()
+            };
+            with_config(config, |game| {
+                let lit = (0..2 * CURSOR_BLINK_FRAMES as u8).all(|counter| {
+                    let mut leds = [RGB8::default(); 256];
+                    game.blink_counter = counter;
+                    game.draw_cursor(&mut leds);
+                    get_pixel(&leds, game.cursor_x, game.cursor_y) == PINK_IDX
+                });
+                assert_eq!(lit, always_lit, "{:?}", motion);
+            });
+        }
+    }
+
+    #[test]
+    fn block_is_still_life() {
+        with_game(|game| {
+            let block = [(3, 15), (4, 15), (3, 16), (4, 16)];
+            seed(game, &block);
+            for _ in 0..5 {
+                game.next_generation();
+                assert_alive(game, &block);
+            }
+        });
+    }
+
+    #[test]
+    fn blinker_oscillates_with_period_two() {
+        with_game(|game| {
+            let vertical = [(3, 14), (3, 15), (3, 16)];
+            let horizontal = [(2, 15), (3, 15), (4, 15)];
+            seed(game, &vertical);
+            game.next_generation();
+            assert_alive(game, &horizontal);
+            game.next_generation();
+            assert_alive(game, &vertical);
+        });
+    }
+
+    #[test]
+    fn glider_moves_one_cell_diagonally_every_four_generations() {
+        with_game(|game| {
+            seed(game, &[(1, 10), (2, 11), (0, 12), (1, 12), (2, 12)]);
+            for _ in 0..4 {
+                game.next_generation();
+            }
+            assert_alive(game, &[(2, 11), (3, 12), (1, 13), (2, 13), (3, 13)]);
+        });
+    }
+
+    #[test]
+    fn surviving_cells_age_through_colors() {
+        with_config(
+            LifeConfig {
+                aging: true,
+                ..LifeConfig::default()
+            },
+            |game| {
+                seed(game, &[(3, 15), (4, 15), (3, 16), (4, 16)]);
+                assert_eq!(game.screen.get(3, 15), GREEN_IDX);
+                for expected in [LIGHT_GREEN_IDX, YELLOW_IDX, YELLOW_IDX] {
+                    game.next_generation();
+                    assert_eq!(game.screen.get(3, 15), expected);
+                }
+            },
+        );
+    }
+
+    #[test]
+    fn highlife_replicator_grows_a_birth_conway_lacks() {
+        // The replicator copies itself every 12 generations, but on an 8
+        // wide board the copies wrap into each other, so check the first
+        // B6 birth while the pattern still fits
+        let replicator = [
+            (4, 12),
+            (5, 12),
+            (6, 12),
+            (3, 13),
+            (6, 13),
+            (2, 14),
+            (6, 14),
+            (2, 15),
+            (5, 15),
+            (2, 16),
+            (3, 16),
+            (4, 16),
+        ];
+        let mut population = [0; 2];
+        for (rules, population) in [Rules::CONWAY, Rules::HIGHLIFE]
+            .into_iter()
+            .zip(population.iter_mut())
+        {
+            with_config(
+                LifeConfig {
+                    rules,
+                    ..LifeConfig::default()
+                },
+                |game| {
+                    seed(game, &replicator);
+                    game.next_generation();
+                    game.next_generation();
+                    *population = (6..SCREEN_HEIGHT)
+                        .flat_map(|y| (0..SCREEN_WIDTH).map(move |x| (x, y)))
+                        .filter(|&(x, y)| game.screen.get(x, y) != BLACK_IDX)
+                        .count();
+                    // The replicator center has six live neighbors
+                    assert_eq!(game.screen.get(4, 14) != BLACK_IDX, rules.born(6));
+                },
+            );
+        }
+        assert_eq!(population, [16, 17]);
+    }
+
+    #[test]
+    fn rule_sets_cycle_after_the_last_pattern() {
+        with_game(|game| {
+            assert_eq!(game.rules, Rules::CONWAY);
+            for _ in 0..PATTERNS.len() {
+                game.next_pattern();
+            }
+            assert_eq!(game.rules, Rules::HIGHLIFE);
+            for _ in 0..PATTERNS.len() * 2 {
+                game.next_pattern();
+            }
+            assert_eq!(game.rules, Rules::CONWAY);
+            assert_eq!(
+                Rules::SEEDS,
+                Rules {
+                    birth: 1 << 2,
+                    survive: 0
+                }
+            );
+        });
+    }
+
+    #[test]
+    fn rules_parse_from_bs_notation() {
+        assert_eq!(Rules::parse("B3/S23"), Some(Rules::CONWAY));
+        assert_eq!(Rules::parse("b36/s23"), Some(Rules::HIGHLIFE));
+        assert_eq!(Rules::parse("B2/S"), Some(Rules::SEEDS));
+        for invalid in ["", "B3", "S23/B3", "B9/S23", "B3/S2x"] {
+            assert_eq!(Rules::parse(invalid), None, "{}", invalid);
+        }
+        assert_eq!(LifeConfig::default().rules, Rules::CONWAY);
+
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let game = LifeGame::with_rules(
+            Prng::new(1),
+            &mut display,
+            &mut controller,
+            &timer,
+            Rules::SEEDS,
+        );
+        assert_eq!(game.rules, Rules::SEEDS);
+    }
+
+    #[test]
+    fn b_steps_one_generation_while_paused() {
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let capture = display.clone();
+        let input = controller.clone();
+        let mut game = LifeGame::new(Prng::new(1), &mut display, &mut controller, &timer);
+        let vertical = [(3, 14), (3, 15), (3, 16)];
+        let horizontal = [(2, 15), (3, 15), (4, 15)];
+        seed(&mut game, &vertical);
+
+        let mut runner = Runner::new(game.run());
+        input.press_joystick();
+        runner.steps(100);
+        let paused = capture.last_frame_buffer();
+        for (x, y) in vertical {
+            assert_ne!(paused.get(x, y), BLACK_IDX);
+        }
+        assert_eq!(paused.get(0, 4), GREEN_IDX);
+
+        for step in 1..=3 {
+            input.press_b();
+            runner.step();
+            let frame = capture.last_frame_buffer();
+            let expected = if step % 2 == 1 { horizontal } else { vertical };
+            for (x, y) in expected {
+                assert_ne!(frame.get(x, y), BLACK_IDX, "step {}", step);
+            }
+            // The generation dot advances once per step
+            assert_eq!(frame.get(step, 4), GREEN_IDX, "step {}", step);
+            assert_eq!(frame.get(step - 1, 4), BLACK_IDX, "step {}", step);
+        }
+        drop(runner);
+        assert_eq!(game.generation, 3);
+    }
+
+    #[test]
+    fn rle_decodes_to_the_built_in_patterns() {
+        let glider = parse_rle("bo$2bo$3o!", (0, 8)).unwrap();
+        assert_eq!(Some(glider.cells()), PATTERNS[1]);
+
+        let blinker = parse_rle("#N Blinker\nx = 1, y = 3, rule = B3/S23\no$o$o!", (3, 10));
+        assert_eq!(Some(blinker.unwrap().cells()), PATTERNS[2]);
+
+        // Cells off the sides or up in the HUD are dropped
+        let cropped = parse_rle("12o$$3o!", (4, 4)).unwrap();
+        assert_eq!(cropped.cells(), [(4, 6), (5, 6), (6, 6)]);
+
+        assert!(parse_rle("bo$2bo$3q!", (0, 8)).is_none());
+
+        with_game(|game| {
+            game.load_pattern(&parse_rle("2o$2o!", (3, 15)).unwrap());
+            assert_alive(game, &[(3, 15), (4, 15), (3, 16), (4, 16)]);
+        });
+    }
+}
@@ -0,0 +1,367 @@
+use smart_leds::RGB8;
+
+use crate::{
+    common::{
+        Background, Dot, FrameBuffer, FrameLimit, Game, GameController, LedDisplay, Motion, Prng,
+        Rng, ScoreStore, StatusEvent, StatusSink, Timer, BLACK_IDX, BRICK_IDX, HUD_SCORE_COLOR,
+        HUD_SEPARATOR_Y, LIGHT_GREEN_IDX, SCREEN_HEIGHT, SCREEN_WIDTH, YELLOW_IDX,
+    },
+    games::reveal::reveal_and_wait,
+};
+
+/// First maze row, right below the HUD
+const MAZE_TOP: i8 = HUD_SEPARATOR_Y as i8 + 1;
+/// Passages run through every other column and row starting at the top
+/// left, the walls between them are carved away. The right column and the
+/// bottom row stay walls.
+const CELL_COLUMNS: usize = SCREEN_WIDTH / 2;
+const CELL_ROWS: usize = (SCREEN_HEIGHT - MAZE_TOP as usize) / 2;
+const MAZE_CELLS: usize = CELL_COLUMNS * CELL_ROWS;
+const START: Dot = Dot::new(0, MAZE_TOP);
+/// The cell farthest from the start, in the bottom right corner
+const EXIT: Dot = Dot::new(
+    (CELL_COLUMNS as i8 - 1) * 2,
+    MAZE_TOP + (CELL_ROWS as i8 - 1) * 2,
+);
+const DIRECTIONS: [Dot; 4] = [
+    Dot::new(0, -1),
+    Dot::new(1, 0),
+    Dot::new(0, 1),
+    Dot::new(-1, 0),
+];
+const FRAME_MS: u64 = 100;
+
+/// True if `dot` lies in the area of the passage cells, on a cell or on the
+/// wall between two
+fn is_cell(dot: Dot) -> bool {
+    (0..CELL_COLUMNS as i8 * 2).contains(&dot.x)
+        && (MAZE_TOP..MAZE_TOP + CELL_ROWS as i8 * 2).contains(&dot.y)
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct MazeConfig {
+    /// Time to find the exit of each maze, the HUD separator shrinks as it
+    /// runs out and the game ends with it
+    pub seconds_per_maze: u16,
+    pub background: Background,
+    pub motion: Motion,
+    /// Return from `run` after this many frames, for tests and benchmarks.
+    /// `None` plays until game over.
+    pub max_frames: Option<u64>,
+}
+
+impl Default for MazeConfig {
+    fn default() -> Self {
+        Self {
+            seconds_per_maze: 60,
+            background: Background::None,
+            motion: Motion::Full,
+            max_frames: None,
+        }
+    }
+}
+
+pub struct MazeGame<'a, D, C, T, R = Prng> {
+    screen: FrameBuffer,
+    // Walls of the current maze, everything else is a passage
+    maze: FrameBuffer,
+    display: &'a mut D,
+    controller: &'a mut C,
+    timer: &'a T,
+    prng: R,
+
+    player: Dot,
+    frames_left: u32,
+    // Mazes solved
+    score: u16,
+    config: MazeConfig,
+    status: Option<&'a mut dyn StatusSink>,
+    scores: Option<&'a mut dyn ScoreStore>,
+    // Frames drawn, scrolls a score too long for the HUD
+    hud_frames: u16,
+}
+
+impl<'a, D: LedDisplay, C: GameController, T: Timer, R: Rng> MazeGame<'a, D, C, T, R> {
+    pub fn new(prng: R, display: &'a mut D, controller: &'a mut C, timer: &'a T) -> Self {
+        Self::with_config(prng, display, controller, timer, MazeConfig::default())
+    }
+
+    pub fn with_config(
+        prng: R,
+        display: &'a mut D,
+        controller: &'a mut C,
+        timer: &'a T,
+        config: MazeConfig,
+    ) -> Self {
+        let mut game = Self {
+            screen: FrameBuffer::new(),
+            maze: FrameBuffer::new(),
+            display,
+            controller,
+            timer,
+            prng,
+            player: START,
+            frames_left: 0,
+            score: 0,
+            config,
+            status: None,
+            scores: None,
+            hud_frames: 0,
+        };
+        game.new_maze();
+        game
+    }
+
+    /// Report score changes and game over to `sink`
+    pub fn with_status_sink(mut self, sink: &'a mut dyn StatusSink) -> Self {
+        self.status = Some(sink);
+        self
+    }
+
+    /// Record a new high score in `store` at game over
+    pub fn with_score_store(mut self, store: &'a mut dyn ScoreStore) -> Self {
+        self.scores = Some(store);
+        self
+    }
+
+    fn notify(&mut self, event: StatusEvent) {
+        if let Some(status) = self.status.as_mut() {
+            status.notify(event);
+        }
+    }
+
+    fn maze_time(&self) -> u32 {
+        u32::from(self.config.seconds_per_maze) * 1000 / FRAME_MS as u32
+    }
+
+    /// Generate a fresh maze and put the player back at the start
+    fn new_maze(&mut self) {
+        self.generate();
+        self.player = START;
+        self.frames_left = self.maze_time();
+    }
+
+    /// Carve a maze with the recursive backtracker: walk from the start to
+    /// random unvisited neighbor cells, knocking down the wall in between,
+    /// and back up once stuck. Every cell is reached exactly once, so there
+    /// is one path between any two of them.
+    fn generate(&mut self) {
+        self.maze.clear();
+        self.maze.fill_rect(
+            0,
+            MAZE_TOP,
+            SCREEN_WIDTH as u8,
+            SCREEN_HEIGHT as u8 - MAZE_TOP as u8,
+            BRICK_IDX,
+        );
+
+        let mut stack = [START; MAZE_CELLS];
+        let mut len = 1;
+        self.maze.set(START.x as usize, START.y as usize, BLACK_IDX);
+        while len > 0 {
+            let cell = stack[len - 1];
+            let mut options = [Dot::new(0, 0); DIRECTIONS.len()];
+            let mut count = 0;
+            for direction in DIRECTIONS {
+                let next = Dot::new(cell.x + 2 * direction.x, cell.y + 2 * direction.y);
+                if is_cell(next) && self.maze.get(next.x as usize, next.y as usize) == BRICK_IDX {
+                    options[count] = direction;
+                    count += 1;
+                }
+            }
+            if count == 0 {
+                len -= 1;
+                continue;
+            }
+
+            let direction = options[self.prng.next_range(count as u8) as usize];
+            let wall = cell.move_by(direction);
+            let next = wall.move_by(direction);
+            self.maze.set(wall.x as usize, wall.y as usize, BLACK_IDX);
+            self.maze.set(next.x as usize, next.y as usize, BLACK_IDX);
+            stack[len] = next;
+            len += 1;
+        }
+    }
+
+    /// True if `dot` is inside the maze and not a wall
+    fn is_open(&self, dot: Dot) -> bool {
+        dot.in_bounds()
+            && dot.y >= MAZE_TOP
+            && self.maze.get(dot.x as usize, dot.y as usize) == BLACK_IDX
+    }
+
+    /// Step the player one cell in `direction` unless a wall is in the way.
+    /// Reaching the exit scores and starts the next maze.
+    fn move_player(&mut self, direction: Dot) {
+        let target = self.player.move_by(direction);
+        if direction.is_zero() || !self.is_open(target) {
+            return;
+        }
+        self.player = target;
+        if self.player == EXIT {
+            self.score += 1;
+            self.notify(StatusEvent::Score(self.score));
+            self.new_maze();
+        }
+    }
+
+    fn draw(&mut self) {
+        self.screen.copy_from(&self.maze);
+        self.hud_frames = self.hud_frames.wrapping_add(1);
+        self.screen
+            .draw_hud_score(self.score, self.hud_frames, HUD_SCORE_COLOR);
+
+        // The time left as a bar in place of the HUD separator
+        let total = self.maze_time().max(1);
+        let bar = (self.frames_left * SCREEN_WIDTH as u32).div_ceil(total) as usize;
+        for x in 0..bar.min(SCREEN_WIDTH) {
+            self.screen.set(x, HUD_SEPARATOR_Y, YELLOW_IDX);
+        }
+
+        self.screen
+            .set(EXIT.x as usize, EXIT.y as usize, YELLOW_IDX);
+        self.screen.set(
+            self.player.x as usize,
+            self.player.y as usize,
+            LIGHT_GREEN_IDX,
+        );
+    }
+
+    async fn game_over(&mut self) {
+        reveal_and_wait(
+            &mut *self.display,
+            &*self.controller,
+            self.timer,
+            self.score,
+            self.scores.as_deref_mut(),
+        )
+        .await;
+    }
+}
+
+impl<'a, D: LedDisplay, C: GameController, T: Timer, R: Rng> Game for MazeGame<'a, D, C, T, R> {
+    async fn run(&mut self) {
+        let mut leds = [RGB8::new(0, 0, 0); 256];
+
+        let mut frames = FrameLimit::new(self.config.max_frames);
+        loop {
+            let x = self.controller.read_x().await;
+            let y = self.controller.read_y().await;
+            self.move_player(Dot::new(x, y).to_direction());
+
+            self.frames_left = self.frames_left.saturating_sub(1);
+            self.draw();
+            self.screen.render_over(&mut leds, self.config.background);
+            self.display.write(&leds).await;
+
+            if self.frames_left == 0 {
+                self.notify(StatusEvent::GameOver);
+                self.game_over().await;
+                break;
+            }
+
+            self.timer.sleep_millis(FRAME_MS).await;
+            if frames.tick() {
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::GREEN_IDX;
+    use crate::testing::{CaptureDisplay, ScriptedController, VirtualTimer};
+
+    // Flood the passages from the start, marked cells are reachable
+    fn reachable(maze: &FrameBuffer) -> FrameBuffer {
+        let mut seen = FrameBuffer::new();
+        seen.set(START.x as usize, START.y as usize, GREEN_IDX);
+        let mut grew = true;
+        while grew {
+            grew = false;
+            for y in MAZE_TOP..SCREEN_HEIGHT as i8 {
+                for x in 0..SCREEN_WIDTH as i8 {
+                    if seen.get(x as usize, y as usize) != BLACK_IDX {
+                        continue;
+                    }
+                    let dot = Dot::new(x, y);
+                    let open = maze.get(x as usize, y as usize) == BLACK_IDX;
+                    let next_to_seen = DIRECTIONS.iter().any(|&direction| {
+                        let neighbor = dot.move_by(direction);
+                        neighbor.in_bounds()
+                            && seen.get(neighbor.x as usize, neighbor.y as usize) != BLACK_IDX
+                    });
+                    if open && next_to_seen {
+                        seen.set(x as usize, y as usize, GREEN_IDX);
+                        grew = true;
+                    }
+                }
+            }
+        }
+        seen
+    }
+
+    #[test]
+    fn generated_mazes_connect_every_cell_to_the_exit() {
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        for seed in 0..20 {
+            let game = MazeGame::new(Prng::new(seed), &mut display, &mut controller, &timer);
+            let seen = reachable(&game.maze);
+            assert_ne!(seen.get(EXIT.x as usize, EXIT.y as usize), BLACK_IDX);
+
+            // A perfect maze: all cells open and reachable, with one wall
+            // knocked down per cell but the first
+            let mut passages = 0;
+            for y in MAZE_TOP..SCREEN_HEIGHT as i8 {
+                for x in 0..SCREEN_WIDTH as i8 {
+                    let dot = Dot::new(x, y);
+                    let open = game.is_open(dot);
+                    passages += open as usize;
+                    let on_grid = x % 2 == 0 && (y - MAZE_TOP) % 2 == 0 && is_cell(dot);
+                    if on_grid {
+                        assert!(open, "seed {} cell {:?}", seed, dot);
+                    }
+                    assert_eq!(open, seen.get(x as usize, y as usize) != BLACK_IDX);
+                }
+            }
+            assert_eq!(passages, 2 * MAZE_CELLS - 1, "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn walls_block_and_the_exit_starts_a_new_maze() {
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let mut game = MazeGame::new(Prng::new(3), &mut display, &mut controller, &timer);
+
+        // The HUD and the screen edge are off limits
+        game.move_player(Dot::new(0, -1));
+        game.move_player(Dot::new(-1, 0));
+        assert_eq!(game.player, START);
+
+        // Into a wall and along a passage next to the exit
+        game.maze
+            .set(EXIT.x as usize - 1, EXIT.y as usize, BRICK_IDX);
+        game.maze
+            .set(EXIT.x as usize, EXIT.y as usize - 1, BLACK_IDX);
+        game.player = Dot::new(EXIT.x - 1, EXIT.y - 1);
+        game.frames_left = 1;
+        game.move_player(Dot::new(0, 1));
+        assert_eq!(game.player, Dot::new(EXIT.x - 1, EXIT.y - 1));
+        game.maze
+            .set(EXIT.x as usize - 1, EXIT.y as usize - 1, BLACK_IDX);
+        game.move_player(Dot::new(1, 0));
+        assert_eq!(game.player, Dot::new(EXIT.x, EXIT.y - 1));
+        game.move_player(Dot::new(0, 1));
+
+        assert_eq!(game.score, 1);
+        assert_eq!(game.player, START);
+        assert_eq!(game.frames_left, game.maze_time());
+    }
+}
@@ -1,31 +1,37 @@
+pub mod breakout;
+pub mod flappy;
 pub mod life;
+pub mod maze;
+pub mod pong;
+pub mod prompt;
 pub mod races;
+pub mod reveal;
+pub mod simon;
 pub mod snake;
 pub mod tanks;
 pub mod tetris;
 
-use crate::common::{FrameBuffer, Game, GameController, LedDisplay, Prng, Timer, GREEN_IDX};
+use crate::common::{
+    hue_shift, present, set_hue_shift, FrameBuffer, Game, GameController, HighScores, LedDisplay,
+    Motion, NeutralStart, Prng, ScoreStore, Timer, BLACK_IDX, BLUE_IDX, DARK_GREEN_IDX, GREEN_IDX,
+    RED_IDX, SCREEN_HEIGHT, SCREEN_WIDTH, YELLOW_IDX,
+};
 use crate::log::info;
-use life::LifeGame;
-use races::RacesGame;
+#[cfg(feature = "frame-observer")]
+use crate::observer::{FrameObserver, ObservedDisplay};
+use breakout::{BreakoutConfig, BreakoutGame};
+use flappy::{FlappyConfig, FlappyGame};
+use life::{LifeConfig, LifeGame};
+use maze::{MazeConfig, MazeGame};
+use pong::{PongConfig, PongGame};
+use races::{RacesConfig, RacesGame};
+use simon::{SimonConfig, SimonGame};
 use smart_leds::RGB8;
-use snake::SnakeGame;
-use tanks::TanksGame;
-use tetris::TetrisGame;
-
-//  Coordinates
-//        x
-//     0 --->  7
-//    0+-------+
-//     |       |
-//     |   S   |
-//   | |   C   |
-// y | |   R   |---+
-//   | |   E   | +----+
-//   v |   E   | |::::| <- microbit
-//     |   N   | +----+
-//     |       | @ |<---- joystick
-//   31+-------+---+
+use snake::{SnakeConfig, SnakeGame};
+use tanks::{TanksConfig, TanksGame};
+use tetris::{TetrisConfig, TetrisGame};
+
+// Screen coordinates are described in `crate::coords`
 
 // Game title graphics (converted from Python GAMES array)
 pub const TETRIS_TITLE: [u32; 8] = [
@@ -83,15 +89,364 @@ pub const LIFE_TITLE: [u32; 8] = [
     0b_00000000000000000000000000000000,
 ];
 
-// Game titles array
-pub const GAME_TITLES: [&[u32; 8]; 5] = [
-    &TETRIS_TITLE,
-    &SNAKE_TITLE,
-    &TANKS_TITLE,
-    &RACES_TITLE,
-    &LIFE_TITLE,
+pub const PONG_TITLE: [u32; 8] = [
+    0b_00000000000000000000000000000000,
+    0b_00000000000000000000000000000000,
+    0b_00000000111011101010111000000000,
+    0b_00000000101010101010100000000000,
+    0b_00000000101010101110100000000000,
+    0b_00000000101010101010100000000000,
+    0b_00000000101011101010100000000000,
+    0b_00000000000000000000000000000000,
+];
+
+pub const BREAKOUT_TITLE: [u32; 8] = [
+    0b_00000000000000000000000000000000,
+    0b_00000000000000000000000000000000,
+    0b_00000111001101110101010010000000,
+    0b_00000100010101010110011010000000,
+    0b_00000111010101010110010110000000,
+    0b_00000101010101010101010010000000,
+    0b_00000111010101110101010010000000,
+    0b_00000000000000000000000000000000,
+];
+
+pub const FLAPPY_TITLE: [u32; 8] = [
+    0b_00000000000000000000000000000000,
+    0b_00000000000000000000000000000000,
+    0b_00000011101110010010100100000000,
+    0b_00000010100100101010101010000000,
+    0b_00000010100100111001001110000000,
+    0b_00000010100100101010101010000000,
+    0b_00000010100100101010101010000000,
+    0b_00000000000000000000000000000000,
+];
+
+pub const SIMON_TITLE: [u32; 8] = [
+    0b_00000000000000000000000000000000,
+    0b_00000000000000000000000000000000,
+    0b_00011100100100010111011101000000,
+    0b_00010101010110110101001001000000,
+    0b_00010101110101010111001001110000,
+    0b_00010101010100010011001001010000,
+    0b_00010101010100010101001001110000,
+    0b_00000000000000000000000000000000,
+];
+
+pub const MAZE_TITLE: [u32; 8] = [
+    0b_00000000000000000000000000000000,
+    0b_00000000000000000000000000000000,
+    0b_00001100100010101011100111000000,
+    0b_00001010100010101010100101000000,
+    0b_00001100111010010010100101000000,
+    0b_00001010101010101010101111000000,
+    0b_00001100111010101011101001000000,
+    0b_00000000000000000000000000000000,
+];
+
+pub const RANDOM_TITLE: [u32; 8] = [
+    0b_00000000000000000000000000000000,
+    0b_00000000000000000000000000000000,
+    0b_00000011000100100101100111000000,
+    0b_00000010101010110101010001000000,
+    0b_00000011001110101101010011000000,
+    0b_00000010101010100101010000000000,
+    0b_00000010101010100101100010000000,
+    0b_00000000000000000000000000000000,
+];
+
+/// Menu titles: every game of `GAMES` in order, then the random entry
+pub const GAME_TITLES: [&[u32; 8]; GAMES.len() + 1] = game_titles();
+
+const fn game_titles() -> [&'static [u32; 8]; GAMES.len() + 1] {
+    let mut titles = [&RANDOM_TITLE; GAMES.len() + 1];
+    let mut idx = 0;
+    while idx < GAMES.len() {
+        titles[idx] = GAMES[idx].title;
+        idx += 1;
+    }
+    titles
+}
+
+/// Description of a game for host UIs that draw their own menu
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GameInfo {
+    pub name: &'static str,
+    /// Index into `GAME_TITLES`, also the menu position
+    pub id: u8,
+    /// Short help on the controls
+    pub controls: &'static str,
+    /// Menu title graphic
+    pub title: &'static [u32; 8],
+}
+
+// Games in menu order, the random entry is not a game of its own. A new
+// game goes here and in `run_game`.
+const GAMES: [GameInfo; 10] = [
+    GameInfo {
+        name: "Tetris",
+        id: 0,
+        controls: "Left/right move, press rotates, down drops faster, A hard drops",
+        title: &TETRIS_TITLE,
+    },
+    GameInfo {
+        name: "Snake",
+        id: 1,
+        controls: "Stick steers",
+        title: &SNAKE_TITLE,
+    },
+    GameInfo {
+        name: "Tanks",
+        id: 2,
+        controls: "Stick moves and aims, press fires",
+        title: &TANKS_TITLE,
+    },
+    GameInfo {
+        name: "Races",
+        id: 3,
+        controls: "Stick steers, press fires",
+        title: &RACES_TITLE,
+    },
+    GameInfo {
+        name: "Life",
+        id: 4,
+        controls: "Press pauses, A next pattern and rules, B draws; while paused A speed, B steps",
+        title: &LIFE_TITLE,
+    },
+    GameInfo {
+        name: "Pong",
+        id: 5,
+        controls: "Left/right moves the bottom paddle",
+        title: &PONG_TITLE,
+    },
+    GameInfo {
+        name: "Breakout",
+        id: 6,
+        controls: "Left/right moves the paddle",
+        title: &BREAKOUT_TITLE,
+    },
+    GameInfo {
+        name: "Flappy",
+        id: 7,
+        controls: "Press flaps",
+        title: &FLAPPY_TITLE,
+    },
+    GameInfo {
+        name: "Simon",
+        id: 8,
+        controls: "Stick picks a quadrant, press repeats it",
+        title: &SIMON_TITLE,
+    },
+    GameInfo {
+        name: "Maze",
+        id: 9,
+        controls: "Stick walks to the exit before the time runs out",
+        title: &MAZE_TITLE,
+    },
 ];
 
+/// Games that can be launched, in menu order
+pub fn available_games() -> &'static [GameInfo] {
+    &GAMES
+}
+
+/// Index into `GAME_TITLES` of the entry that launches one of the games
+/// before it at random
+pub const RANDOM_GAME: u8 = GAMES.len() as u8;
+
+/// Unpack every entry of `GAME_TITLES` once, so the menu loop only copies
+pub fn render_titles() -> [FrameBuffer; GAME_TITLES.len()] {
+    core::array::from_fn(|idx| FrameBuffer::from_rows(GAME_TITLES[idx], GREEN_IDX))
+}
+
+// The bottom row is blank in every title and holds one dot per game
+const MENU_DOTS_ROW: usize = SCREEN_HEIGHT - 1;
+
+/// Draw one dot per game, centered, with the selected one highlighted.
+/// With more games than fit across the screen the dots scroll to keep the
+/// selected one in view.
+pub fn draw_menu_dots(screen: &mut FrameBuffer, game_idx: usize, num_games: usize) {
+    let visible = num_games.min(SCREEN_WIDTH);
+    let first = game_idx
+        .saturating_sub(visible - 1)
+        .min(num_games - visible);
+    let start = (SCREEN_WIDTH - visible) / 2;
+    for idx in 0..visible {
+        let color = if first + idx == game_idx {
+            YELLOW_IDX
+        } else {
+            DARK_GREEN_IDX
+        };
+        screen.set(start + idx, MENU_DOTS_ROW, color);
+    }
+}
+
+// Hue shift step applied by the menu palette option, in degrees
+const MENU_HUE_STEP: u16 = 60;
+
+// Colors cycled by the LED self-test, ending with the panel off
+pub const SELF_TEST_COLORS: [u8; 4] = [RED_IDX, GREEN_IDX, BLUE_IDX, BLACK_IDX];
+const SELF_TEST_STEP_MS: u64 = 500;
+
+/// Light the whole panel with each of `SELF_TEST_COLORS` in turn
+pub async fn run_self_test<D: LedDisplay, T: Timer>(display: &mut D, timer: &T) {
+    let mut screen = FrameBuffer::new();
+    for color in SELF_TEST_COLORS {
+        screen.fill(color);
+        present(display, &screen).await;
+        timer.sleep_millis(SELF_TEST_STEP_MS).await;
+    }
+}
+
+/// Run the game with `id`, see `available_games`, until it returns to the
+/// menu. Games that keep a high score record it in `scores`. Returns false
+/// for an unknown id.
+pub async fn run_game<D, C, T>(
+    id: u8,
+    prng: Prng,
+    display: &mut D,
+    controller: &mut C,
+    timer: &T,
+    motion: Motion,
+    scores: Option<&mut dyn ScoreStore>,
+) -> bool
+where
+    D: LedDisplay,
+    C: GameController,
+    T: Timer,
+{
+    match id {
+        0 => {
+            let config = TetrisConfig {
+                motion,
+                ..TetrisConfig::default()
+            };
+            let mut tetris = TetrisGame::with_config(prng, display, controller, timer, config);
+            if let Some(scores) = scores {
+                tetris = tetris.with_score_store(scores);
+            }
+            tetris.run().await;
+        }
+        1 => {
+            let config = SnakeConfig {
+                motion,
+                ..SnakeConfig::default()
+            };
+            let mut snake = SnakeGame::with_config(prng, display, controller, timer, config);
+            if let Some(scores) = scores {
+                snake = snake.with_score_store(scores);
+            }
+            snake.run().await;
+        }
+        2 => {
+            let config = TanksConfig {
+                motion,
+                ..TanksConfig::default()
+            };
+            let mut tanks = TanksGame::with_config(prng, display, controller, timer, config);
+            tanks.run().await;
+        }
+        3 => {
+            let config = RacesConfig {
+                motion,
+                ..RacesConfig::default()
+            };
+            let mut races = RacesGame::with_config(prng, display, controller, timer, config);
+            races.run().await;
+        }
+        4 => {
+            let config = LifeConfig {
+                motion,
+                ..LifeConfig::default()
+            };
+            let mut life = LifeGame::with_config(prng, display, controller, timer, config);
+            life.run().await;
+        }
+        5 => {
+            let config = PongConfig {
+                motion,
+                ..PongConfig::default()
+            };
+            let mut pong = PongGame::with_config(prng, display, controller, timer, config);
+            pong.run().await;
+        }
+        6 => {
+            let config = BreakoutConfig {
+                motion,
+                ..BreakoutConfig::default()
+            };
+            let mut breakout = BreakoutGame::with_config(prng, display, controller, timer, config);
+            if let Some(scores) = scores {
+                breakout = breakout.with_score_store(scores);
+            }
+            breakout.run().await;
+        }
+        7 => {
+            let config = FlappyConfig {
+                motion,
+                ..FlappyConfig::default()
+            };
+            let mut flappy = FlappyGame::with_config(prng, display, controller, timer, config);
+            if let Some(scores) = scores {
+                flappy = flappy.with_score_store(scores);
+            }
+            flappy.run().await;
+        }
+        8 => {
+            let config = SimonConfig {
+                motion,
+                ..SimonConfig::default()
+            };
+            let mut simon = SimonGame::with_config(prng, display, controller, timer, config);
+            if let Some(scores) = scores {
+                simon = simon.with_score_store(scores);
+            }
+            simon.run().await;
+        }
+        9 => {
+            let config = MazeConfig {
+                motion,
+                ..MazeConfig::default()
+            };
+            let mut maze = MazeGame::with_config(prng, display, controller, timer, config);
+            if let Some(scores) = scores {
+                maze = maze.with_score_store(scores);
+            }
+            maze.run().await;
+        }
+        _ => return false,
+    }
+    true
+}
+
+// Duration of one menu frame
+const MENU_FRAME_MS: u64 = 200;
+
+#[derive(Clone, Copy, Debug)]
+pub struct MenuConfig {
+    /// Launch `idle_game` after the menu saw no input for this long, for
+    /// kiosk setups. `None` waits for the player forever.
+    pub idle_timeout_ms: Option<u64>,
+    /// Index into `GAME_TITLES` of the game launched on idle timeout
+    pub idle_game: u8,
+    /// A launched game ignores the joystick until it was centered and
+    /// released once, see `NeutralStart`
+    pub first_move_grace: bool,
+    /// Passed on to every launched game
+    pub motion: Motion,
+}
+
+impl Default for MenuConfig {
+    fn default() -> Self {
+        Self {
+            idle_timeout_ms: None,
+            idle_game: 0,
+            first_move_grace: true,
+            motion: Motion::Full,
+        }
+    }
+}
+
 /// Run a game menu loop that allows selecting and starting games
 pub async fn run_game_menu<D, C, T, F>(display: &mut D, controller: &mut C, timer: &T, seed_fn: F)
 where
@@ -99,13 +454,82 @@ where
     C: GameController,
     T: Timer,
     F: Fn() -> u32,
+{
+    run_game_menu_with_config(display, controller, timer, seed_fn, MenuConfig::default()).await
+}
+
+pub async fn run_game_menu_with_config<D, C, T, F>(
+    display: &mut D,
+    controller: &mut C,
+    timer: &T,
+    seed_fn: F,
+    config: MenuConfig,
+) where
+    D: LedDisplay,
+    C: GameController,
+    T: Timer,
+    F: Fn() -> u32,
+{
+    menu_loop(display, controller, timer, seed_fn, config, |_| {}).await
+}
+
+/// Same as `run_game_menu_with_config`, reporting every frame of the menu
+/// and the games to `observer`
+#[cfg(feature = "frame-observer")]
+pub async fn run_game_menu_observed<D, C, T, F, O>(
+    display: &mut D,
+    controller: &mut C,
+    timer: &T,
+    seed_fn: F,
+    config: MenuConfig,
+    observer: &mut O,
+) where
+    D: LedDisplay,
+    C: GameController,
+    T: Timer,
+    F: Fn() -> u32,
+    O: FrameObserver,
+{
+    let game = core::cell::Cell::new(None);
+    let mut observed = ObservedDisplay::new(display, observer, &game);
+    menu_loop(&mut observed, controller, timer, seed_fn, config, |idx| {
+        game.set(idx)
+    })
+    .await
+}
+
+// `select_game` is told which game runs, `None` when back in the menu
+async fn menu_loop<D, C, T, F, S>(
+    display: &mut D,
+    controller: &mut C,
+    timer: &T,
+    seed_fn: F,
+    config: MenuConfig,
+    select_game: S,
+) where
+    D: LedDisplay,
+    C: GameController,
+    T: Timer,
+    F: Fn() -> u32,
+    S: Fn(Option<u8>),
 {
     let mut leds: [RGB8; 256] = [RGB8::default(); 256];
     let mut game_idx: u8 = 0;
     let num_games = GAME_TITLES.len() as u8;
+    let titles = render_titles();
+    let mut screen = FrameBuffer::new();
+    // Navigate only when the joystick leaves the center, so a stick resting
+    // off-center doesn't keep scrolling the menu
+    let mut last_x: i8 = 0;
+    // Time since the last input, counted in menu frames
+    let mut idle_ms: u64 = 0;
+    // Best score of each game this session
+    let mut high_scores = HighScores::<{ GAMES.len() }>::new();
 
     loop {
-        let delta = controller.read_x().await;
+        let x = controller.read_x().await;
+        let delta = if last_x == 0 { x } else { 0 };
+        last_x = x;
         if delta != 0 {
             game_idx = match delta {
                 -1 => (game_idx + num_games - 1) % num_games,
@@ -118,40 +542,532 @@ where
             );
         }
 
-        if controller.joystick_was_pressed() {
-            let seed = seed_fn();
-            let prng = Prng::new(seed);
-            match game_idx {
-                0 => {
-                    let mut tetris = TetrisGame::new(prng, display, controller, timer);
-                    tetris.run().await;
-                }
-                1 => {
-                    let mut snake = SnakeGame::new(prng, display, controller, timer);
-                    snake.run().await;
-                }
-                2 => {
-                    let mut tanks = TanksGame::new(prng, display, controller, timer);
-                    tanks.run().await;
-                }
-                3 => {
-                    let mut races = RacesGame::new(prng, display, controller, timer);
-                    races.run().await;
-                }
-                4 => {
-                    let mut life = LifeGame::new(prng, display, controller, timer);
-                    life.run().await;
-                }
-                _ => {}
+        // Rotate the palette hue for every game with the A button
+        let a_pressed = controller.a_was_pressed();
+        if a_pressed {
+            set_hue_shift(hue_shift() + MENU_HUE_STEP);
+            info!("Hue shift set to {}", hue_shift());
+        }
+
+        if x != 0 || a_pressed || controller.b_was_pressed() {
+            idle_ms = 0;
+        }
+        let idle_launch = match config.idle_timeout_ms {
+            Some(timeout_ms) if idle_ms >= timeout_ms => {
+                game_idx = config.idle_game % num_games;
+                info!("Menu idle, launching game {}", game_idx);
+                true
             }
+            _ => false,
+        };
+
+        if controller.joystick_was_pressed() || idle_launch {
+            let seed = seed_fn();
+            let mut prng = Prng::new(seed);
+            let launched = if game_idx == RANDOM_GAME {
+                prng.next_range(RANDOM_GAME)
+            } else {
+                game_idx
+            };
+            info!("Launching game {}", launched);
+            select_game(Some(launched));
+            let controller = &mut NeutralStart::new(&mut *controller, config.first_move_grace);
+            let mut scores = high_scores.store(launched as usize);
+            run_game(
+                launched,
+                prng,
+                display,
+                controller,
+                timer,
+                config.motion,
+                Some(&mut scores),
+            )
+            .await;
+            select_game(None);
+            controller.flush_presses();
+            idle_ms = 0;
         }
 
         // Display menu - show game index
-        let title = GAME_TITLES[game_idx as usize];
-        let screen = FrameBuffer::from_rows(title, GREEN_IDX);
+        screen.copy_from(&titles[game_idx as usize]);
+        draw_menu_dots(&mut screen, game_idx as usize, titles.len());
         screen.render(&mut leds);
         display.write(&leds).await;
 
-        timer.sleep_millis(200).await;
+        timer.sleep_millis(MENU_FRAME_MS).await;
+        idle_ms += MENU_FRAME_MS;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::COLORS;
+    use crate::testing::{CaptureDisplay, Runner, ScriptedController, VirtualTimer};
+
+    #[test]
+    fn pre_rendered_titles_match_from_rows() {
+        let titles = render_titles();
+        for (idx, title) in GAME_TITLES.iter().enumerate() {
+            assert!(
+                titles[idx] == FrameBuffer::from_rows(title, GREEN_IDX),
+                "title {}",
+                idx
+            );
+        }
+    }
+
+    #[test]
+    fn self_test_writes_solid_frames() {
+        let mut display = CaptureDisplay::new();
+        let timer = VirtualTimer::new();
+        let capture = display.clone();
+
+        let mut runner = Runner::new(run_self_test(&mut display, &timer));
+        while runner.step() {}
+
+        assert_eq!(capture.frame_count(), SELF_TEST_COLORS.len());
+        for (idx, &color) in SELF_TEST_COLORS.iter().enumerate() {
+            let frame = capture.frame(idx);
+            assert!(
+                frame.iter().all(|&led| led == COLORS[color as usize]),
+                "frame {}",
+                idx
+            );
+        }
+    }
+
+    fn menu_frame(game_idx: usize) -> FrameBuffer {
+        let mut screen = render_titles()[game_idx].clone();
+        draw_menu_dots(&mut screen, game_idx, GAME_TITLES.len());
+        screen
+    }
+
+    #[test]
+    fn held_joystick_advances_menu_once() {
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let capture = display.clone();
+        let input = controller.clone();
+
+        let mut runner = Runner::new(run_game_menu(&mut display, &mut controller, &timer, || 1));
+        input.set_x(1);
+        runner.steps(20);
+        for idx in 0..capture.frame_count() {
+            assert!(capture.frame_buffer(idx) == menu_frame(1), "frame {}", idx);
+        }
+
+        input.set_x(0);
+        runner.step();
+        input.set_x(1);
+        runner.step();
+        assert!(capture.last_frame_buffer() == menu_frame(2));
+    }
+
+    #[test]
+    fn idle_menu_launches_configured_game() {
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let config = MenuConfig {
+            idle_timeout_ms: Some(5 * MENU_FRAME_MS),
+            idle_game: 1,
+            ..MenuConfig::default()
+        };
+
+        let mut runner = Runner::new(run_game_menu_with_config(
+            &mut display,
+            &mut controller,
+            &timer,
+            || 1,
+            config,
+        ));
+        runner.steps(8);
+        drop(runner);
+
+        // Five idle menu frames, then Snake ticks at 20 ms
+        let sleeps = timer.sleeps();
+        assert!(sleeps.len() > 5);
+        assert!(sleeps[..5].iter().all(|&ms| ms == MENU_FRAME_MS));
+        assert!(sleeps[5..].iter().all(|&ms| ms == 20));
+    }
+
+    // Launch Snake from the menu with up held for 100 game ticks, then
+    // release and hold it again for `release_after` ticks if not zero.
+    // Returns the position of the snake head.
+    fn snake_head_holding_up(first_move_grace: bool, release_after: usize) -> (usize, usize) {
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let capture = display.clone();
+        let input = controller.clone();
+        let config = MenuConfig {
+            first_move_grace,
+            ..MenuConfig::default()
+        };
+
+        let mut runner = Runner::new(run_game_menu_with_config(
+            &mut display,
+            &mut controller,
+            &timer,
+            || 1,
+            config,
+        ));
+        input.set_x(1);
+        runner.step();
+        input.set_x(0);
+        input.set_y(-1);
+        input.press_joystick();
+        runner.steps(100);
+        if release_after > 0 {
+            input.set_y(0);
+            runner.step();
+            input.set_y(-1);
+            runner.steps(release_after);
+        }
+        drop(runner);
+
+        let frame = capture.last_frame_buffer();
+        let head = (SCREEN_HEIGHT / 4..SCREEN_HEIGHT)
+            .flat_map(|y| (0..SCREEN_WIDTH).map(move |x| (x, y)))
+            .find(|&(x, y)| frame.get(x, y) == crate::common::LIGHT_GREEN_IDX);
+        head.unwrap()
+    }
+
+    #[test]
+    fn direction_held_at_launch_waits_for_release() {
+        // The snake starts at (3, 15) heading right
+        let (x, y) = snake_head_holding_up(true, 0);
+        assert!(x > 3 && y == 15, "({}, {})", x, y);
+
+        // Up counts once released and pushed again
+        let (x, y) = snake_head_holding_up(true, 20);
+        assert!(y != 15, "({}, {})", x, y);
+
+        // Without the grace the snake turns up right away
+        let (x, y) = snake_head_holding_up(false, 0);
+        assert!(x == 3 && y != 15, "({}, {})", x, y);
+    }
+
+    #[test]
+    fn random_entry_launches_a_registered_game() {
+        const SEED: u32 = 7;
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let input = controller.clone();
+        let launched = core::cell::Cell::new(None);
+
+        let mut runner = Runner::new(menu_loop(
+            &mut display,
+            &mut controller,
+            &timer,
+            || SEED,
+            MenuConfig::default(),
+            |idx| {
+                if launched.get().is_none() {
+                    launched.set(idx);
+                }
+            },
+        ));
+        // Wrap around from the first game to the random entry and start it
+        input.set_x(-1);
+        input.press_joystick();
+        runner.steps(3);
+        drop(runner);
+
+        let expected = Prng::new(SEED).next_range(RANDOM_GAME);
+        assert!(expected < RANDOM_GAME);
+        assert_eq!(launched.get(), Some(expected));
+    }
+
+    #[test]
+    fn games_share_the_default_hud_separator() {
+        use crate::common::HudSeparator;
+        use races::RacesTheme;
+        use tanks::TanksTheme;
+
+        let separators = [
+            TetrisConfig::default().hud_separator,
+            SnakeConfig::default().hud_separator,
+            TanksConfig::default().theme.hud_separator,
+            TanksTheme::high_contrast().hud_separator,
+            RacesConfig::default().theme.hud_separator,
+            RacesTheme::high_contrast().hud_separator,
+            PongConfig::default().hud_separator,
+            BreakoutConfig::default().hud_separator,
+            FlappyConfig::default().hud_separator,
+            SimonConfig::default().hud_separator,
+        ];
+        for (idx, separator) in separators.into_iter().enumerate() {
+            assert_eq!(separator, HudSeparator::default(), "separator {}", idx);
+        }
+    }
+
+    #[test]
+    fn available_games_have_contiguous_ids() {
+        let games = available_games();
+        assert!(!games.is_empty());
+        for (idx, game) in games.iter().enumerate() {
+            assert_eq!(game.id as usize, idx, "{}", game.name);
+            assert!(!game.name.is_empty() && !game.controls.is_empty());
+        }
+        // Every game has a title, followed by the random entry
+        assert_eq!(games.len(), RANDOM_GAME as usize);
+        assert_eq!(GAME_TITLES.len(), games.len() + 1);
+        for game in games {
+            assert_eq!(GAME_TITLES[game.id as usize], game.title, "{}", game.name);
+        }
+        assert_eq!(GAME_TITLES[RANDOM_GAME as usize], &RANDOM_TITLE);
+    }
+
+    #[test]
+    fn run_game_launches_every_listed_id() {
+        for game in available_games() {
+            let mut display = CaptureDisplay::new();
+            let mut controller = ScriptedController::new();
+            let timer = VirtualTimer::new();
+            let capture = display.clone();
+            let mut runner = Runner::new(async {
+                let known = run_game(
+                    game.id,
+                    Prng::new(1),
+                    &mut display,
+                    &mut controller,
+                    &timer,
+                    Motion::Full,
+                    None,
+                )
+                .await;
+                assert!(known);
+            });
+            runner.steps(20);
+            drop(runner);
+            assert!(capture.frame_count() > 0, "{}", game.name);
+        }
+
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let capture = display.clone();
+        let mut runner = Runner::new(async {
+            let known = run_game(
+                RANDOM_GAME,
+                Prng::new(1),
+                &mut display,
+                &mut controller,
+                &timer,
+                Motion::Full,
+                None,
+            )
+            .await;
+            assert!(!known);
+        });
+        assert!(!runner.step());
+        drop(runner);
+        assert_eq!(capture.frame_count(), 0);
+    }
+
+    #[test]
+    fn menu_dots_highlight_selected_game() {
+        for game_idx in 0..GAME_TITLES.len() {
+            let mut screen = FrameBuffer::new();
+            draw_menu_dots(&mut screen, game_idx, GAME_TITLES.len());
+
+            let lit: usize = (0..SCREEN_WIDTH)
+                .filter(|&x| screen.get(x, MENU_DOTS_ROW) != BLACK_IDX)
+                .count();
+            assert_eq!(lit, GAME_TITLES.len().min(SCREEN_WIDTH));
+            let highlighted = (0..SCREEN_WIDTH)
+                .find(|&x| screen.get(x, MENU_DOTS_ROW) == YELLOW_IDX)
+                .unwrap();
+            let start = SCREEN_WIDTH.saturating_sub(GAME_TITLES.len()) / 2;
+            let first = game_idx.saturating_sub(SCREEN_WIDTH - 1);
+            assert_eq!(highlighted, start + game_idx - first);
+        }
+
+        // Past the screen width the selected dot stays at the right edge
+        let mut screen = FrameBuffer::new();
+        draw_menu_dots(&mut screen, 10, 12);
+        assert_eq!(screen.row_bits(MENU_DOTS_ROW), 0xff);
+        assert_eq!(screen.get(SCREEN_WIDTH - 1, MENU_DOTS_ROW), YELLOW_IDX);
+        draw_menu_dots(&mut screen, 0, 12);
+        assert_eq!(screen.get(0, MENU_DOTS_ROW), YELLOW_IDX);
+    }
+
+    #[test]
+    fn menu_dots_row_is_free_in_all_titles() {
+        for title in render_titles().iter() {
+            assert!(title.row_is_empty(MENU_DOTS_ROW));
+        }
+    }
+
+    #[cfg(feature = "frame-observer")]
+    #[test]
+    fn observer_sees_every_frame_of_menu_and_game() {
+        #[derive(Default)]
+        struct Recorder {
+            frames: u32,
+            first_game: Option<Option<u8>>,
+            last_game: Option<u8>,
+        }
+
+        impl FrameObserver for Recorder {
+            fn on_frame(&mut self, game: Option<u8>, frame: u32, _checksum: u32) {
+                assert_eq!(frame, self.frames);
+                self.frames += 1;
+                self.first_game.get_or_insert(game);
+                self.last_game = game;
+            }
+        }
+
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let capture = display.clone();
+        let input = controller.clone();
+        let mut recorder = Recorder::default();
+
+        let mut runner = Runner::new(run_game_menu_observed(
+            &mut display,
+            &mut controller,
+            &timer,
+            || 1,
+            MenuConfig::default(),
+            &mut recorder,
+        ));
+        runner.steps(3);
+        input.press_joystick();
+        runner.steps(20);
+        drop(runner);
+
+        assert_eq!(recorder.frames as usize, capture.frame_count());
+        assert_eq!(recorder.first_game, Some(None));
+        assert_eq!(recorder.last_game, Some(0));
+    }
+
+    // Seeds and frames per game of `games_survive_random_input`
+    const FUZZ_SEEDS: u32 = 32;
+    const FUZZ_FRAMES: u64 = 400;
+
+    // Run a game to its return, failing if it keeps going far past its
+    // frame limit
+    fn run_to_return(name: &str, seed: u32, game: impl core::future::Future<Output = ()>) {
+        let mut runner = Runner::new(game);
+        let mut steps = 0;
+        while runner.step() {
+            steps += 1;
+            assert!(
+                steps < FUZZ_FRAMES * 50,
+                "{} did not return with seed {}",
+                name,
+                seed
+            );
+        }
+    }
+
+    fn fuzz_games(seed: u32) {
+        use crate::testing::RandomController;
+        use breakout::BreakoutConfig;
+        use flappy::FlappyConfig;
+        use life::LifeConfig;
+        use maze::MazeConfig;
+        use pong::PongConfig;
+        use simon::SimonConfig;
+
+        let mut display = CaptureDisplay::new();
+        let mut controller = RandomController::new(seed);
+        let timer = VirtualTimer::new();
+        let prng = || Prng::new(seed);
+        let max_frames = Some(FUZZ_FRAMES);
+
+        let config = TetrisConfig {
+            max_frames,
+            ..TetrisConfig::default()
+        };
+        let mut tetris =
+            TetrisGame::with_config(prng(), &mut display, &mut controller, &timer, config);
+        run_to_return("Tetris", seed, tetris.run());
+
+        let config = SnakeConfig {
+            max_frames,
+            ..SnakeConfig::default()
+        };
+        let mut snake =
+            SnakeGame::with_config(prng(), &mut display, &mut controller, &timer, config);
+        run_to_return("Snake", seed, snake.run());
+
+        let config = TanksConfig {
+            max_frames,
+            ..TanksConfig::default()
+        };
+        let mut tanks =
+            TanksGame::with_config(prng(), &mut display, &mut controller, &timer, config);
+        run_to_return("Tanks", seed, tanks.run());
+
+        let config = RacesConfig {
+            max_frames,
+            ..RacesConfig::default()
+        };
+        let mut races =
+            RacesGame::with_config(prng(), &mut display, &mut controller, &timer, config);
+        run_to_return("Races", seed, races.run());
+
+        let config = LifeConfig {
+            max_frames,
+            ..LifeConfig::default()
+        };
+        let mut life = LifeGame::with_config(prng(), &mut display, &mut controller, &timer, config);
+        run_to_return("Life", seed, life.run());
+
+        let config = PongConfig {
+            max_frames,
+            ..PongConfig::default()
+        };
+        let mut pong = PongGame::with_config(prng(), &mut display, &mut controller, &timer, config);
+        run_to_return("Pong", seed, pong.run());
+
+        let config = BreakoutConfig {
+            max_frames,
+            ..BreakoutConfig::default()
+        };
+        let mut breakout =
+            BreakoutGame::with_config(prng(), &mut display, &mut controller, &timer, config);
+        run_to_return("Breakout", seed, breakout.run());
+
+        let config = FlappyConfig {
+            max_frames,
+            ..FlappyConfig::default()
+        };
+        let mut flappy =
+            FlappyGame::with_config(prng(), &mut display, &mut controller, &timer, config);
+        run_to_return("Flappy", seed, flappy.run());
+
+        let config = SimonConfig {
+            max_frames,
+            ..SimonConfig::default()
+        };
+        let mut simon =
+            SimonGame::with_config(prng(), &mut display, &mut controller, &timer, config);
+        run_to_return("Simon", seed, simon.run());
+
+        let config = MazeConfig {
+            max_frames,
+            ..MazeConfig::default()
+        };
+        let mut maze = MazeGame::with_config(prng(), &mut display, &mut controller, &timer, config);
+        run_to_return("Maze", seed, maze.run());
+    }
+
+    #[test]
+    fn games_survive_random_input() {
+        extern crate std;
+
+        // Index overflows trip the debug assertions in `coords` as panics
+        for seed in 0..FUZZ_SEEDS {
+            let result = std::panic::catch_unwind(|| fuzz_games(seed));
+            assert!(result.is_ok(), "a game panicked with seed {}", seed);
+        }
     }
 }
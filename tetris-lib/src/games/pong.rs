@@ -0,0 +1,370 @@
+use smart_leds::RGB8;
+
+use crate::{
+    common::{
+        Background, Dot, FrameBuffer, FrameLimit, Game, GameController, HudSeparator, LedDisplay,
+        Motion, Prng, Rng, ScoreStore, StatusEvent, StatusSink, Timer, GREEN_IDX, HUD_SEPARATOR_Y,
+        RED_IDX, SCREEN_HEIGHT, SCREEN_WIDTH, YELLOW_IDX,
+    },
+    digits::DIGITS,
+    games::reveal::reveal_and_wait,
+};
+
+const PADDLE_WIDTH: i8 = 3;
+/// Row of the AI paddle, right below the HUD
+const AI_ROW: i8 = HUD_SEPARATOR_Y as i8 + 1;
+/// Row of the player paddle, the bottom edge
+const PLAYER_ROW: i8 = SCREEN_HEIGHT as i8 - 1;
+const PADDLE_MAX_X: i8 = SCREEN_WIDTH as i8 - PADDLE_WIDTH;
+/// Where the ball waits before each serve, halfway between the paddles
+const SERVE_Y: i8 = (AI_ROW + PLAYER_ROW) / 2;
+/// Frames the ball waits at the center before it is served
+const SERVE_FRAMES: u8 = 15;
+const FRAME_MS: u64 = 40;
+
+#[derive(Clone, Copy, Debug)]
+pub struct PongConfig {
+    /// Points that win the match, the HUD shows one digit per side
+    pub points_to_win: u8,
+    /// Frames between two moves of the ball, lower is faster
+    pub ball_frames: u8,
+    /// Frames between two moves of the AI paddle. Above `ball_frames` the
+    /// AI can't follow a diagonal ball across the whole court.
+    pub ai_frames: u8,
+    pub background: Background,
+    pub hud_separator: HudSeparator,
+    pub motion: Motion,
+    /// Return from `run` after this many frames, for tests and benchmarks.
+    /// `None` plays until game over.
+    pub max_frames: Option<u64>,
+}
+
+impl Default for PongConfig {
+    fn default() -> Self {
+        Self {
+            points_to_win: 5,
+            ball_frames: 3,
+            ai_frames: 4,
+            background: Background::None,
+            hud_separator: HudSeparator::default(),
+            motion: Motion::Full,
+            max_frames: None,
+        }
+    }
+}
+
+/// Outcome of one ball move
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum BallMove {
+    Flying,
+    PlayerScored,
+    AiScored,
+}
+
+pub struct PongGame<'a, D, C, T, R = Prng> {
+    screen: FrameBuffer,
+    display: &'a mut D,
+    controller: &'a mut C,
+    timer: &'a T,
+    prng: R,
+
+    // Leftmost cell of each paddle
+    player_x: i8,
+    ai_x: i8,
+    ball: Dot,
+    // One row up or down per move, and at most one column sideways
+    velocity: Dot,
+    serve_frames: u8,
+    player_points: u8,
+    ai_points: u8,
+    config: PongConfig,
+    status: Option<&'a mut dyn StatusSink>,
+}
+
+impl<'a, D: LedDisplay, C: GameController, T: Timer, R: Rng> PongGame<'a, D, C, T, R> {
+    pub fn new(prng: R, display: &'a mut D, controller: &'a mut C, timer: &'a T) -> Self {
+        Self::with_config(prng, display, controller, timer, PongConfig::default())
+    }
+
+    pub fn with_config(
+        prng: R,
+        display: &'a mut D,
+        controller: &'a mut C,
+        timer: &'a T,
+        config: PongConfig,
+    ) -> Self {
+        let mut game = Self {
+            screen: FrameBuffer::new(),
+            display,
+            controller,
+            timer,
+            prng,
+            player_x: PADDLE_MAX_X / 2,
+            ai_x: PADDLE_MAX_X / 2,
+            ball: Dot::new(0, 0),
+            velocity: Dot::new(0, 0),
+            serve_frames: 0,
+            player_points: 0,
+            ai_points: 0,
+            config,
+            status: None,
+        };
+        // The player receives the first serve
+        game.serve(1);
+        game
+    }
+
+    /// Report the player's points and game over to `sink`
+    pub fn with_status_sink(mut self, sink: &'a mut dyn StatusSink) -> Self {
+        self.status = Some(sink);
+        self
+    }
+
+    fn notify(&mut self, event: StatusEvent) {
+        if let Some(status) = self.status.as_mut() {
+            status.notify(event);
+        }
+    }
+
+    /// Put the ball back at the center, to fly towards the player when
+    /// `towards` is 1 or towards the AI when it is -1
+    fn serve(&mut self, towards: i8) {
+        let x = SCREEN_WIDTH as i8 / 2 - 1 + self.prng.next_range(2) as i8;
+        let dx = if self.prng.next_bool() { 1 } else { -1 };
+        self.ball = Dot::new(x, SERVE_Y);
+        self.velocity = Dot::new(dx, towards);
+        self.serve_frames = SERVE_FRAMES;
+    }
+
+    /// Bounce `velocity` off a paddle starting at `paddle_x` if it covers
+    /// `x`. The paddle edges send the ball off to their side, the middle
+    /// keeps its angle. True on a hit.
+    fn bounce_off_paddle(&mut self, paddle_x: i8, x: i8) -> bool {
+        let offset = x - paddle_x;
+        if !(0..PADDLE_WIDTH).contains(&offset) {
+            return false;
+        }
+        self.velocity.y = -self.velocity.y;
+        if offset == 0 {
+            self.velocity.x = -1;
+        } else if offset == PADDLE_WIDTH - 1 {
+            self.velocity.x = 1;
+        }
+        true
+    }
+
+    /// Move the ball one step, bouncing off the side walls and the paddles.
+    /// A ball reaching a paddle row past the paddle is a point for the
+    /// other side.
+    fn move_ball(&mut self) -> BallMove {
+        let mut next = self.ball.move_by(self.velocity);
+        if !(0..SCREEN_WIDTH as i8).contains(&next.x) {
+            self.velocity.x = -self.velocity.x;
+            next = self.ball.move_by(self.velocity);
+        }
+
+        if next.y == PLAYER_ROW {
+            if !self.bounce_off_paddle(self.player_x, next.x) {
+                return BallMove::AiScored;
+            }
+        } else if next.y == AI_ROW {
+            if !self.bounce_off_paddle(self.ai_x, next.x) {
+                return BallMove::PlayerScored;
+            }
+        } else {
+            self.ball = next;
+            return BallMove::Flying;
+        }
+
+        // Bounced, leave the paddle row the other way. A new angle may
+        // point into a side wall, which the next move bounces off.
+        self.ball.y += self.velocity.y;
+        self.ball.x = (self.ball.x + self.velocity.x).clamp(0, SCREEN_WIDTH as i8 - 1);
+        BallMove::Flying
+    }
+
+    /// Step the AI paddle one cell towards the ball while it comes closer,
+    /// and back towards the middle otherwise
+    fn move_ai(&mut self) {
+        let target = if self.velocity.y < 0 {
+            self.ball.x - PADDLE_WIDTH / 2
+        } else {
+            PADDLE_MAX_X / 2
+        };
+        self.ai_x = (self.ai_x + (target - self.ai_x).signum()).clamp(0, PADDLE_MAX_X);
+    }
+
+    fn match_over(&self) -> bool {
+        self.player_points >= self.config.points_to_win
+            || self.ai_points >= self.config.points_to_win
+    }
+
+    fn draw(&mut self) {
+        self.screen.clear();
+        self.screen
+            .draw_figure(0, 0, DIGITS.wrapping_at(self.player_points), GREEN_IDX);
+        self.screen
+            .draw_figure(5, 0, DIGITS.wrapping_at(self.ai_points), RED_IDX);
+        self.screen
+            .draw_hud_separator(HUD_SEPARATOR_Y, self.config.hud_separator);
+
+        for dx in 0..PADDLE_WIDTH {
+            self.screen.set(
+                (self.player_x + dx) as usize,
+                PLAYER_ROW as usize,
+                GREEN_IDX,
+            );
+            self.screen
+                .set((self.ai_x + dx) as usize, AI_ROW as usize, RED_IDX);
+        }
+        self.screen
+            .set(self.ball.x as usize, self.ball.y as usize, YELLOW_IDX);
+    }
+
+    async fn game_over(&mut self) {
+        reveal_and_wait(
+            &mut *self.display,
+            &*self.controller,
+            self.timer,
+            u16::from(self.player_points),
+            None::<&mut dyn ScoreStore>,
+        )
+        .await;
+    }
+}
+
+impl<'a, D: LedDisplay, C: GameController, T: Timer, R: Rng> Game for PongGame<'a, D, C, T, R> {
+    async fn run(&mut self) {
+        let mut leds = [RGB8::new(0, 0, 0); 256];
+        let mut frame: u16 = 0;
+
+        let mut frames = FrameLimit::new(self.config.max_frames);
+        loop {
+            let x = self.controller.read_x().await;
+            self.player_x = (self.player_x + x.signum()).clamp(0, PADDLE_MAX_X);
+
+            frame = frame.wrapping_add(1);
+            if frame.is_multiple_of(u16::from(self.config.ai_frames.max(1))) {
+                self.move_ai();
+            }
+
+            if self.serve_frames > 0 {
+                self.serve_frames -= 1;
+            } else if frame.is_multiple_of(u16::from(self.config.ball_frames.max(1))) {
+                match self.move_ball() {
+                    BallMove::Flying => {}
+                    BallMove::PlayerScored => {
+                        self.player_points += 1;
+                        self.notify(StatusEvent::Score(u16::from(self.player_points)));
+                        self.serve(-1);
+                    }
+                    BallMove::AiScored => {
+                        self.ai_points += 1;
+                        self.serve(1);
+                    }
+                }
+            }
+
+            self.draw();
+            self.screen.render_over(&mut leds, self.config.background);
+            self.display.write(&leds).await;
+
+            if self.match_over() {
+                self.notify(StatusEvent::GameOver);
+                self.game_over().await;
+                break;
+            }
+
+            self.timer.sleep_millis(FRAME_MS).await;
+            if frames.tick() {
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{CaptureDisplay, Runner, ScriptedController, VirtualTimer};
+
+    #[test]
+    fn ball_bounces_straight_back_off_the_paddle_middle() {
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let mut game = PongGame::new(Prng::new(1), &mut display, &mut controller, &timer);
+
+        // Straight down onto the middle of the player paddle
+        game.player_x = 2;
+        game.ball = Dot::new(3, PLAYER_ROW - 1);
+        game.velocity = Dot::new(0, 1);
+        assert_eq!(game.move_ball(), BallMove::Flying);
+        assert_eq!(game.velocity, Dot::new(0, -1));
+        assert_eq!(game.ball, Dot::new(3, PLAYER_ROW - 2));
+
+        // Straight up onto the AI paddle
+        game.ai_x = 2;
+        game.ball = Dot::new(3, AI_ROW + 1);
+        assert_eq!(game.move_ball(), BallMove::Flying);
+        assert_eq!(game.velocity, Dot::new(0, 1));
+        assert_eq!(game.ball, Dot::new(3, AI_ROW + 2));
+
+        // The paddle edges angle the ball away
+        game.ball = Dot::new(2, PLAYER_ROW - 1);
+        game.velocity = Dot::new(0, 1);
+        assert_eq!(game.move_ball(), BallMove::Flying);
+        assert_eq!(game.velocity, Dot::new(-1, -1));
+
+        // Past the paddle it is a point for the other side
+        game.ball = Dot::new(6, PLAYER_ROW - 1);
+        game.velocity = Dot::new(1, 1);
+        assert_eq!(game.move_ball(), BallMove::AiScored);
+        game.ball = Dot::new(0, AI_ROW + 1);
+        game.velocity = Dot::new(-1, -1);
+        assert_eq!(game.move_ball(), BallMove::PlayerScored);
+    }
+
+    #[test]
+    fn ball_bounces_off_the_side_walls() {
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let mut game = PongGame::new(Prng::new(1), &mut display, &mut controller, &timer);
+
+        game.ball = Dot::new(SCREEN_WIDTH as i8 - 1, SERVE_Y);
+        game.velocity = Dot::new(1, 1);
+        assert_eq!(game.move_ball(), BallMove::Flying);
+        assert_eq!(game.ball, Dot::new(SCREEN_WIDTH as i8 - 2, SERVE_Y + 1));
+        assert_eq!(game.velocity, Dot::new(-1, 1));
+    }
+
+    #[test]
+    fn match_ends_when_a_side_reaches_the_winning_points() {
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let input = controller.clone();
+        let config = PongConfig {
+            points_to_win: 1,
+            ..PongConfig::default()
+        };
+        let mut game =
+            PongGame::with_config(Prng::new(1), &mut display, &mut controller, &timer, config);
+        // Keep the player paddle in the left corner, away from the ball
+        input.set_x(-1);
+        game.player_x = 0;
+        game.ball = Dot::new(6, SERVE_Y);
+        game.velocity = Dot::new(0, 1);
+        game.serve_frames = 0;
+
+        let mut runner = Runner::new(game.run());
+        while runner.step() {
+            input.press_a();
+        }
+        drop(runner);
+
+        assert_eq!((game.player_points, game.ai_points), (0, 1));
+        assert!(game.match_over());
+    }
+}
@@ -0,0 +1,122 @@
+use crate::common::{present, FrameBuffer, GameController, LedDisplay, Timer, YELLOW_IDX};
+use crate::digits::DIGITS;
+
+// The continue offer counts down from CONTINUE_SECONDS, polling for B
+// CONTINUE_POLLS times per second
+pub const CONTINUE_SECONDS: u8 = 9;
+const CONTINUE_POLLS: u8 = 10;
+const CONTINUE_POLL_MS: u64 = 100;
+// Slow animations check the buttons this often, well within a press hold
+const PRESS_POLL_MS: u64 = 50;
+const COUNTDOWN_X: i8 = 3;
+const COUNTDOWN_Y: i8 = 13;
+
+/// Single countdown digit centered on the screen
+pub fn countdown_frame(seconds: u8) -> FrameBuffer {
+    let mut screen = FrameBuffer::new();
+    screen.draw_figure(
+        COUNTDOWN_X,
+        COUNTDOWN_Y,
+        DIGITS.wrapping_at(seconds),
+        YELLOW_IDX,
+    );
+    screen
+}
+
+/// Count down `CONTINUE_SECONDS` and return true if B was pressed before
+/// it ran out. Presses made before the countdown started are ignored.
+pub async fn offer_continue<D, C, T>(display: &mut D, controller: &C, timer: &T) -> bool
+where
+    D: LedDisplay,
+    C: GameController,
+    T: Timer,
+{
+    controller.b_was_pressed();
+    for seconds in (1..=CONTINUE_SECONDS).rev() {
+        present(display, &countdown_frame(seconds)).await;
+        for _ in 0..CONTINUE_POLLS {
+            timer.sleep_millis(CONTINUE_POLL_MS).await;
+            if controller.b_was_pressed() {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+/// Sleep for `millis` checking every button along the way, true as soon as
+/// one is pressed
+pub async fn sleep_unless_pressed<C, T>(controller: &C, timer: &T, millis: u64) -> bool
+where
+    C: GameController,
+    T: Timer,
+{
+    for _ in 0..millis.div_ceil(PRESS_POLL_MS) {
+        if controller.any_pressed() {
+            return true;
+        }
+        timer.sleep_millis(PRESS_POLL_MS).await;
+    }
+    controller.any_pressed()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{CaptureDisplay, Runner, ScriptedController, VirtualTimer};
+    use core::cell::Cell;
+
+    #[test]
+    fn countdown_runs_out_without_b() {
+        let mut display = CaptureDisplay::new();
+        let controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let capture = display.clone();
+        // A stale press from gameplay must not accept the offer
+        controller.press_b();
+
+        let accepted = Cell::new(true);
+        let mut runner = Runner::new(async {
+            accepted.set(offer_continue(&mut display, &controller, &timer).await);
+        });
+        while runner.step() {}
+        drop(runner);
+
+        assert!(!accepted.get());
+        assert_eq!(capture.frame_count(), CONTINUE_SECONDS as usize);
+        assert!(capture.frame_buffer(0) == countdown_frame(CONTINUE_SECONDS));
+        assert!(capture.last_frame_buffer() == countdown_frame(1));
+        assert_eq!(
+            timer.elapsed(),
+            CONTINUE_SECONDS as u64 * CONTINUE_POLLS as u64 * CONTINUE_POLL_MS
+        );
+    }
+
+    #[test]
+    fn slow_animations_stop_at_the_first_press() {
+        let controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let pressed = Cell::new(false);
+
+        let mut runner = Runner::new(async {
+            pressed.set(sleep_unless_pressed(&controller, &timer, 500).await);
+        });
+        runner.steps(3);
+        controller.press_a();
+        while runner.step() {}
+        drop(runner);
+
+        assert!(pressed.get());
+        assert!(timer.elapsed() < 500);
+
+        // Without a press it sleeps the whole time
+        let timer = VirtualTimer::new();
+        let mut runner = Runner::new(async {
+            pressed.set(sleep_unless_pressed(&controller, &timer, 500).await);
+        });
+        while runner.step() {}
+        drop(runner);
+        assert!(!pressed.get());
+        assert_eq!(timer.elapsed(), 500);
+    }
+}
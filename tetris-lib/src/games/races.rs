@@ -2,46 +2,163 @@ use core::option::Option;
 use smart_leds::RGB8;
 
 use crate::{
-    common::{Dot, FrameBuffer, Prng},
+    common::{
+        blink, hud_swap_frames, Difficulty, Dot, FrameBuffer, FrameLimit, HudSeparator, Motion,
+        Prng, Rng, Shake, ShakeConfig, HUD_HEART_SLOTS, HUD_LIVES_COLOR, HUD_SCORE_COLOR,
+        HUD_SEPARATOR_Y,
+    },
     common::{
         Game, GameController, LedDisplay, Timer, BLACK_IDX, BLUE_IDX, BRICK_IDX, DARK_GREEN_IDX,
-        GREEN_IDX, PINK_IDX, RED_IDX, SCREEN_HEIGHT, SCREEN_WIDTH, YELLOW_IDX,
+        GREEN_IDX, LIGHT_BLUE_IDX, ORANGE_IDX, PINK_IDX, RED_IDX, SCREEN_HEIGHT, SCREEN_WIDTH,
+        YELLOW_IDX,
     },
     digits::DIGITS,
+    games::reveal::reveal_score,
+    input::{FireButton, Shot},
 };
 
 static ROAD_UPDATE_STEP_SIZE: u8 = 10;
 static UPDATE_STEP_SIZE: u8 = ROAD_UPDATE_STEP_SIZE * 2;
 
+/// Colors of the scene entities. Collisions never look at colors, so any
+/// combination works, but distinct ones keep the scene readable.
+#[derive(Clone, Copy, Debug)]
+pub struct RacesTheme {
+    pub player_car: u8,
+    pub player_bullet: u8,
+    pub obstacle: u8,
+    pub racing_car: u8,
+    pub powerup: u8,
+    pub hud_separator: HudSeparator,
+}
+
+impl Default for RacesTheme {
+    fn default() -> Self {
+        Self {
+            player_car: GREEN_IDX,
+            player_bullet: RED_IDX,
+            obstacle: DARK_GREEN_IDX,
+            racing_car: BLUE_IDX,
+            powerup: PINK_IDX,
+            hud_separator: HudSeparator::default(),
+        }
+    }
+}
+
+impl RacesTheme {
+    /// Accessibility colors that never rely on telling red from green
+    pub fn high_contrast() -> Self {
+        Self {
+            player_car: YELLOW_IDX,
+            player_bullet: PINK_IDX,
+            obstacle: ORANGE_IDX,
+            racing_car: BLUE_IDX,
+            powerup: LIGHT_BLUE_IDX,
+            hud_separator: HudSeparator::default(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct RacesConfig {
+    pub theme: RacesTheme,
+    /// Holding fire this many frames charges a bullet that pierces one
+    /// obstacle, fired on release. `None` fires on press.
+    pub charge_frames: Option<u16>,
+    /// Holding fire shoots every this many frames, unless charging is on.
+    /// `None` fires once per press.
+    pub auto_fire_frames: Option<u16>,
+    pub difficulty: Difficulty,
+    /// Every point scored counts this many times, e.g. to reward a harder
+    /// difficulty. Can be changed mid-game with `set_score_multiplier`.
+    pub score_multiplier: u8,
+    /// The car blinks every this many frames while invulnerable
+    pub invulnerable_blink_frames: u8,
+    /// Shakes the screen when the car crashes
+    pub shake: ShakeConfig,
+    pub motion: Motion,
+    /// Return from `run` after this many frames, for tests and benchmarks.
+    /// `None` plays until game over.
+    pub max_frames: Option<u64>,
+}
+
+impl Default for RacesConfig {
+    fn default() -> Self {
+        Self {
+            theme: RacesTheme::default(),
+            charge_frames: None,
+            auto_fire_frames: None,
+            difficulty: Difficulty::default(),
+            score_multiplier: 1,
+            invulnerable_blink_frames: 4,
+            shake: ShakeConfig::default(),
+            motion: Motion::Full,
+            max_frames: None,
+        }
+    }
+}
+
+const FRAME_MS: u64 = 20;
+/// Frames the HUD shows the score, then the lives, in turn
+const HUD_SWAP_FRAMES: u16 = hud_swap_frames(FRAME_MS);
+/// Range of the car position, its bottom center, that keeps it on screen
+const CAR_MIN: Dot = Dot::new(1, 3);
+const CAR_MAX: Dot = Dot::new(SCREEN_WIDTH as i8 - 2, SCREEN_HEIGHT as i8 - 1);
+
+/// AI cars sharing the road, 1 plays the original single rival
+pub const NUM_RACERS: usize = 2;
+/// Hits an AI car takes before it is destroyed
+const RACER_HEALTH: u8 = 3;
+// Starting lanes of the AI cars, the later ones start further up
+const RACER_LANES: [i8; 3] = [3, 5, 1];
+const RACER_START_Y: i8 = 10;
+const RACER_SPACING: i8 = 8;
+
 // Races game implementation
-pub struct RacesGame<'a, D, C, T> {
+pub struct RacesGame<'a, D, C, T, R = Prng> {
     screen: FrameBuffer,
     display: &'a mut D,
     controller: &'a mut C,
     timer: &'a T,
 
     update_step: u8,
-    cars_destroyed: u8,
+    cars_destroyed: u16,
     car_pos: Dot,
     obstacles: [Dot; 2],
     obstacle_count: usize,
     bullets: [Dot; 4],
+    // Obstacles each bullet may still pass through
+    bullet_pierce: [u8; 4],
     bullet_count: usize,
     max_bullets: u8,
+    fire_button: FireButton,
     lives: u8,
+    hud_frames: u16,
     invulnerable_time: u8,
-    racing_cars: [Dot; 1],
-    racing_speeds: [i8; 1],
-    racing_car_health: u8,
+    shake: Shake,
+    racing_cars: [Dot; NUM_RACERS],
+    racing_speeds: [i8; NUM_RACERS],
+    racing_car_health: [u8; NUM_RACERS],
     update_road: u8,
     road_animation: u8,
     bullet_powerup: Option<Dot>,
-    prng: Prng,
+    prng: R,
+    config: RacesConfig,
 }
 
-impl<'a, D: LedDisplay, C: GameController, T: Timer> RacesGame<'a, D, C, T> {
-    pub fn new(prng: Prng, display: &'a mut D, controller: &'a mut C, timer: &'a T) -> Self {
-        let mut game = Self {
+impl<'a, D: LedDisplay, C: GameController, T: Timer, R: Rng> RacesGame<'a, D, C, T, R> {
+    pub fn new(prng: R, display: &'a mut D, controller: &'a mut C, timer: &'a T) -> Self {
+        Self::with_config(prng, display, controller, timer, RacesConfig::default())
+    }
+
+    pub fn with_config(
+        prng: R,
+        display: &'a mut D,
+        controller: &'a mut C,
+        timer: &'a T,
+        config: RacesConfig,
+    ) -> Self {
+        Self {
             screen: FrameBuffer::new(),
             display,
             controller,
@@ -53,23 +170,35 @@ impl<'a, D: LedDisplay, C: GameController, T: Timer> RacesGame<'a, D, C, T> {
             obstacles: [Dot::new(0, 0); 2],
             obstacle_count: 0,
             bullets: [Dot::new(0, 0); 4],
+            bullet_pierce: [0; 4],
             bullet_count: 0,
             max_bullets: 5,
-            lives: 3,
+            fire_button: FireButton::new(config.charge_frames, config.auto_fire_frames),
+            lives: config.difficulty.lives(),
+            hud_frames: 0,
             invulnerable_time: 0,
-            racing_cars: [Dot::new(0, 0); 1],
-            racing_speeds: [1],
-            racing_car_health: 3,
+            shake: Shake::new(config.motion.shake(config.shake)),
+            racing_cars: core::array::from_fn(|i| {
+                let lane = RACER_LANES[i % RACER_LANES.len()];
+                Dot::new(lane, RACER_START_Y - RACER_SPACING * i as i8)
+            }),
+            racing_speeds: [1; NUM_RACERS],
+            racing_car_health: [RACER_HEALTH; NUM_RACERS],
             update_road: 0,
             road_animation: 0,
             bullet_powerup: None,
             prng,
-        };
+            config,
+        }
+    }
 
-        // Initialize racing car at the top
-        game.racing_cars[0] = Dot::new(3, 10);
+    pub fn set_score_multiplier(&mut self, multiplier: u8) {
+        self.config.score_multiplier = multiplier;
+    }
 
-        game
+    fn add_score(&mut self, base: u8) {
+        let points = u16::from(base).saturating_mul(u16::from(self.config.score_multiplier));
+        self.cars_destroyed = self.cars_destroyed.saturating_add(points);
     }
 
     fn spawn_obstacles(&mut self) {
@@ -91,31 +220,37 @@ impl<'a, D: LedDisplay, C: GameController, T: Timer> RacesGame<'a, D, C, T> {
     fn update_bullet_powerup(&mut self) {
         if let Some(mut powerup) = self.bullet_powerup.take() {
             powerup.y += 1;
-
-            // Check collision with player car
-            if (powerup.x == self.car_pos.x
-                || powerup.x == self.car_pos.x - 1
-                || powerup.x == self.car_pos.x + 1)
-                && (powerup.y >= self.car_pos.y - 3 && powerup.y <= self.car_pos.y)
-            {
-                if self.max_bullets < 5 {
-                    self.max_bullets += 1;
-                }
-            } else if powerup.y < SCREEN_HEIGHT as i8 {
-                // Only keep powerup if not collected and still on screen
+            if powerup.y < SCREEN_HEIGHT as i8 {
+                // Only keep powerup while still on screen
                 self.bullet_powerup.replace(powerup);
             }
         }
     }
 
+    fn collect_bullet_powerup(&mut self) {
+        // The powerup is drawn as two vertical dots, either one picks it up
+        let Some(powerup) = self.bullet_powerup else {
+            return;
+        };
+        let below = Dot::new(powerup.x, powerup.y + 1);
+        if self.check_car_obstacle_collision(&powerup) || self.check_car_obstacle_collision(&below)
+        {
+            self.bullet_powerup = None;
+            if self.max_bullets < 5 {
+                self.max_bullets += 1;
+            }
+        }
+    }
+
     fn draw_bullet_powerup(&mut self) {
+        let color = self.config.theme.powerup;
         if let Some(powerup) = self.bullet_powerup {
             if powerup.y >= 0 && powerup.y < SCREEN_HEIGHT as i8 {
-                // Draw two vertical dots in pink
+                // Draw two vertical dots
                 self.screen
-                    .set(powerup.x as usize, powerup.y as usize, PINK_IDX);
+                    .set(powerup.x as usize, powerup.y as usize, color);
                 self.screen
-                    .set(powerup.x as usize, (powerup.y + 1) as usize, PINK_IDX);
+                    .set(powerup.x as usize, (powerup.y + 1) as usize, color);
             }
         }
     }
@@ -138,61 +273,73 @@ impl<'a, D: LedDisplay, C: GameController, T: Timer> RacesGame<'a, D, C, T> {
                     }
                 }
 
-                // Only move if no obstacle collision
-                if can_move {
+                // Nor drive into the car ahead
+                let moved = Dot::new(self.racing_cars[i].x, new_y);
+                if can_move && !self.overlaps_racer(i, moved) {
                     self.racing_cars[i].y = new_y;
                 }
             }
 
             // If car goes off screen at bottom, reset it to top with random x position
             if self.racing_cars[i].y >= SCREEN_HEIGHT as i8 {
-                self.racing_cars[i].y = 0;
-                // Try to find a position that doesn't overlap with obstacles
-                loop {
-                    let new_x = self.prng.next_range(5) as i8 + 1;
-                    let mut valid_position = true;
-
-                    for j in 0..self.obstacle_count {
-                        let obs = self.obstacles[j];
-                        if (obs.x == new_x || obs.x == new_x + 1) && (obs.y == 0 || obs.y == 1) {
-                            valid_position = false;
-                            break;
-                        }
-                    }
+                self.respawn_racer(i);
+            }
+        }
+    }
 
-                    if valid_position {
-                        self.racing_cars[i].x = new_x;
-                        break;
-                    }
-                }
+    /// Put racer `i` back at the top, in a lane clear of obstacles and the
+    /// other racers. With every lane taken it waits below the screen and
+    /// tries again on the next update.
+    fn respawn_racer(&mut self, i: usize) {
+        self.racing_car_health[i] = RACER_HEALTH;
+        let first = self.prng.next_range(5);
+        for lane in 0..5 {
+            let new_x = ((first + lane) % 5) as i8 + 1;
+            let candidate = Dot::new(new_x, 0);
+            let blocked = self.obstacles[..self.obstacle_count]
+                .iter()
+                .any(|obs| (obs.x == new_x || obs.x == new_x + 1) && (obs.y == 0 || obs.y == 1));
+            if !blocked && !self.overlaps_racer(i, candidate) {
+                self.racing_cars[i] = candidate;
+                return;
             }
         }
+        self.racing_cars[i].y = SCREEN_HEIGHT as i8;
+    }
+
+    /// True if racer `i` placed at `pos` would overlap another racer
+    fn overlaps_racer(&self, i: usize, pos: Dot) -> bool {
+        self.racing_cars.iter().enumerate().any(|(j, other)| {
+            // Cars are 3 wide and 4 tall, anchored at their bottom center
+            j != i && (other.x - pos.x).abs() < 3 && (other.y - pos.y).abs() < 4
+        })
     }
 
     fn draw_racing_cars(&mut self) {
+        let color = self.config.theme.racing_car;
         for i in 0..self.racing_cars.len() {
             let car = self.racing_cars[i];
-            if car.y >= 0 && car.y < SCREEN_HEIGHT as i8 && self.racing_car_health > 0 {
-                // Draw racing car (same shape as player car but in blue)
+            if car.y >= 0 && car.y < SCREEN_HEIGHT as i8 && self.racing_car_health[i] > 0 {
+                // Draw racing car (same shape as player car)
                 let x = car.x as usize;
                 let y = car.y as usize;
 
                 // Draw car body (check bounds to prevent underflow)
                 if x > 0 && x < SCREEN_WIDTH - 1 && y < SCREEN_HEIGHT {
-                    self.screen.set(x - 1, y, BLUE_IDX);
-                    self.screen.set(x, y, BLUE_IDX);
-                    self.screen.set(x + 1, y, BLUE_IDX);
+                    self.screen.set(x - 1, y, color);
+                    self.screen.set(x, y, color);
+                    self.screen.set(x + 1, y, color);
                 }
                 if y > 0 && x < SCREEN_WIDTH {
-                    self.screen.set(x, y - 1, BLUE_IDX);
+                    self.screen.set(x, y - 1, color);
                 }
                 if y > 1 && x > 0 && x < SCREEN_WIDTH - 1 {
-                    self.screen.set(x, y - 2, BLUE_IDX);
-                    self.screen.set(x - 1, y - 2, BLUE_IDX);
-                    self.screen.set(x + 1, y - 2, BLUE_IDX);
+                    self.screen.set(x, y - 2, color);
+                    self.screen.set(x - 1, y - 2, color);
+                    self.screen.set(x + 1, y - 2, color);
                 }
                 if y > 2 && x < SCREEN_WIDTH {
-                    self.screen.set(x, y - 3, BLUE_IDX);
+                    self.screen.set(x, y - 3, color);
                 }
             }
         }
@@ -227,11 +374,7 @@ impl<'a, D: LedDisplay, C: GameController, T: Timer> RacesGame<'a, D, C, T> {
 
             // Remove bullets that are off screen
             if self.bullets[i].y < 0 {
-                // Remove by swapping with last bullet
-                self.bullet_count -= 1;
-                if i < self.bullet_count {
-                    self.bullets[i] = self.bullets[self.bullet_count];
-                }
+                self.remove_bullet(i);
             } else {
                 i += 1;
             }
@@ -263,6 +406,27 @@ impl<'a, D: LedDisplay, C: GameController, T: Timer> RacesGame<'a, D, C, T> {
         false
     }
 
+    fn fire(&mut self, shot: Shot) {
+        if self.bullet_count < self.bullets.len() && self.max_bullets > 0 {
+            self.bullets[self.bullet_count] = Dot::new(self.car_pos.x, self.car_pos.y - 4);
+            self.bullet_pierce[self.bullet_count] = match shot {
+                Shot::Normal => 0,
+                Shot::Charged => 1,
+            };
+            self.bullet_count += 1;
+            self.max_bullets -= 1; // Decrement available bullets when firing
+        }
+    }
+
+    fn remove_bullet(&mut self, idx: usize) {
+        // Remove by swapping with last bullet
+        self.bullet_count -= 1;
+        if idx < self.bullet_count {
+            self.bullets[idx] = self.bullets[self.bullet_count];
+            self.bullet_pierce[idx] = self.bullet_pierce[self.bullet_count];
+        }
+    }
+
     fn check_bullet_obstacle_collision(&self, bullet: &Dot, obs: &Dot) -> bool {
         (bullet.x == obs.x || bullet.x == obs.x + 1) && (bullet.y == obs.y || bullet.y == obs.y + 1)
     }
@@ -289,6 +453,7 @@ impl<'a, D: LedDisplay, C: GameController, T: Timer> RacesGame<'a, D, C, T> {
             if self.check_car_obstacle_collision(&obs) {
                 self.lives -= 1;
                 self.invulnerable_time = 20; // 1 second of invulnerability
+                self.shake.start();
                 return;
             }
         }
@@ -309,35 +474,37 @@ impl<'a, D: LedDisplay, C: GameController, T: Timer> RacesGame<'a, D, C, T> {
                     if j < self.obstacle_count {
                         self.obstacles[j] = self.obstacles[self.obstacle_count];
                     }
-                    hit = true;
+                    // A charged bullet flies on through its first obstacle
+                    if self.bullet_pierce[i] > 0 {
+                        self.bullet_pierce[i] -= 1;
+                    } else {
+                        hit = true;
+                    }
                     break;
                 }
                 j += 1;
             }
 
             // Check bullet-racing car collisions
-            if !hit && self.racing_car_health > 0 {
-                let racing_car = self.racing_cars[0];
+            for car in 0..NUM_RACERS {
+                if hit || self.racing_car_health[car] == 0 {
+                    continue;
+                }
+                let racing_car = self.racing_cars[car];
                 if self.check_bullet_racing_car_collision(&bullet, &racing_car) {
-                    self.racing_car_health -= 1;
+                    self.racing_car_health[car] -= 1;
                     hit = true;
 
                     // If racing car is destroyed, increment counter and respawn it
-                    if self.racing_car_health == 0 {
-                        self.cars_destroyed += 1;
-                        self.racing_cars[0].y = 0;
-                        self.racing_cars[0].x = self.prng.next_range(5) as i8 + 1;
-                        self.racing_car_health = 3;
+                    if self.racing_car_health[car] == 0 {
+                        self.add_score(1);
+                        self.respawn_racer(car);
                     }
                 }
             }
 
             if hit {
-                // Remove bullet
-                self.bullet_count -= 1;
-                if i < self.bullet_count {
-                    self.bullets[i] = self.bullets[self.bullet_count];
-                }
+                self.remove_bullet(i);
             } else {
                 i += 1;
             }
@@ -369,91 +536,116 @@ impl<'a, D: LedDisplay, C: GameController, T: Timer> RacesGame<'a, D, C, T> {
     fn draw_car(&mut self) {
         let x = self.car_pos.x as usize;
         let y = self.car_pos.y as usize;
+        let color = self.config.theme.player_car;
 
-        if self.invulnerable_time > 0 && (self.invulnerable_time / 4) % 2 == 0 {
+        let blink_frames = self.config.invulnerable_blink_frames.into();
+        let blinking = self.invulnerable_time > 0 && !self.config.motion.is_reduced();
+        if blinking && blink(self.invulnerable_time.into(), blink_frames) {
             // Blink car when invulnerable
             return;
         }
 
         // Draw car body (check bounds to prevent underflow)
         if x > 0 && x < SCREEN_WIDTH - 1 && y < SCREEN_HEIGHT {
-            self.screen.set(x - 1, y, GREEN_IDX);
-            self.screen.set(x, y, GREEN_IDX);
-            self.screen.set(x + 1, y, GREEN_IDX);
+            self.screen.set(x - 1, y, color);
+            self.screen.set(x, y, color);
+            self.screen.set(x + 1, y, color);
         }
         if y > 0 && x < SCREEN_WIDTH {
-            self.screen.set(x, y - 1, GREEN_IDX);
+            self.screen.set(x, y - 1, color);
         }
         if y > 1 && x > 0 && x < SCREEN_WIDTH - 1 {
-            self.screen.set(x, y - 2, GREEN_IDX);
-            self.screen.set(x - 1, y - 2, GREEN_IDX);
-            self.screen.set(x + 1, y - 2, GREEN_IDX);
+            self.screen.set(x, y - 2, color);
+            self.screen.set(x - 1, y - 2, color);
+            self.screen.set(x + 1, y - 2, color);
         }
         if y > 2 && x < SCREEN_WIDTH {
-            self.screen.set(x, y - 3, GREEN_IDX);
+            self.screen.set(x, y - 3, color);
         }
     }
 
     fn draw_obstacles(&mut self) {
+        let color = self.config.theme.obstacle;
         for i in 0..self.obstacle_count {
             let obs = self.obstacles[i];
             if obs.y >= 0 && obs.y < SCREEN_HEIGHT as i8 {
-                // Draw bigger obstacle (2x2) in dark green
-                self.screen
-                    .set(obs.x as usize, obs.y as usize, DARK_GREEN_IDX);
-                self.screen
-                    .set(obs.x as usize + 1, obs.y as usize, DARK_GREEN_IDX);
-                self.screen
-                    .set(obs.x as usize, obs.y as usize + 1, DARK_GREEN_IDX);
+                // Draw bigger obstacle (2x2)
+                self.screen.set(obs.x as usize, obs.y as usize, color);
+                self.screen.set(obs.x as usize + 1, obs.y as usize, color);
+                self.screen.set(obs.x as usize, obs.y as usize + 1, color);
                 self.screen
-                    .set(obs.x as usize + 1, obs.y as usize + 1, DARK_GREEN_IDX);
+                    .set(obs.x as usize + 1, obs.y as usize + 1, color);
             }
         }
     }
 
     fn draw_bullets(&mut self) {
+        let color = self.config.theme.player_bullet;
         for i in 0..self.bullet_count {
             let bullet = self.bullets[i];
             if bullet.y >= 0 && bullet.y < SCREEN_HEIGHT as i8 {
-                self.screen
-                    .set(bullet.x as usize, bullet.y as usize, RED_IDX);
+                self.screen.set(bullet.x as usize, bullet.y as usize, color);
+                // Charged bullets have a tail
+                if self.bullet_pierce[i] > 0 {
+                    self.screen
+                        .set(bullet.x as usize, bullet.y as usize + 1, color);
+                }
             }
         }
     }
 
     fn draw_score(&mut self) {
+        self.screen
+            .draw_hud_separator(HUD_SEPARATOR_Y, self.config.theme.hud_separator);
+
+        // The score and the lives take turns
+        self.hud_frames = self.hud_frames.wrapping_add(1);
+        if !blink(self.hud_frames, HUD_SWAP_FRAMES) {
+            self.screen
+                .draw_hud_lives(self.lives, &HUD_HEART_SLOTS, HUD_LIVES_COLOR);
+            return;
+        }
+
         let score = self.cars_destroyed;
 
         // Draw left digit (tens)
-        let tens = score / 10;
+        let tens = (score / 10 % 10) as u8;
         let tens_figure = DIGITS.wrapping_at(tens);
         // Add extra space for digit one
         let tens_x = if tens == 1 { 1 } else { 0 };
-        self.screen.draw_figure(tens_x, 0, tens_figure, YELLOW_IDX);
+        self.screen
+            .draw_figure(tens_x, 0, tens_figure, HUD_SCORE_COLOR);
 
         // Draw right digit (ones)
-        let ones = score % 10;
+        let ones = (score % 10) as u8;
         let ones_figure = DIGITS.wrapping_at(ones);
         // Add extra space for digit one
         let ones_x = if ones == 1 { 6 } else { 5 };
-        self.screen.draw_figure(ones_x, 0, ones_figure, YELLOW_IDX);
+        self.screen
+            .draw_figure(ones_x, 0, ones_figure, HUD_SCORE_COLOR);
 
-        // Draw vertical line of lives in the middle
-        for y in 0..self.lives {
-            self.screen.set(3, y as usize, GREEN_IDX);
-        }
-
-        // Draw bullet count to the right of lives in pink
+        // Draw bullet count between the digits, colored like the powerup
         for y in 0..self.max_bullets {
-            self.screen.set(4, y as usize, PINK_IDX);
+            self.screen.set(4, y as usize, self.config.theme.powerup);
         }
     }
 
     async fn game_over(&mut self, mut leds: [RGB8; 256]) {
+        reveal_score(
+            &mut *self.display,
+            &*self.controller,
+            self.timer,
+            self.cars_destroyed,
+        )
+        .await;
+
         for _ in 0..3 {
             self.screen.clear();
-            self.screen.render(&mut leds);
-            self.display.write(&leds).await;
+            // With reduced motion the final screen stays on instead of flashing
+            if !self.config.motion.is_reduced() {
+                self.screen.render(&mut leds);
+                self.display.write(&leds).await;
+            }
             self.timer.sleep_millis(200).await;
 
             self.draw_score();
@@ -463,17 +655,17 @@ impl<'a, D: LedDisplay, C: GameController, T: Timer> RacesGame<'a, D, C, T> {
         }
 
         // Wait for button press
-        while !self.controller.joystick_was_pressed() {
+        while !self.controller.any_pressed() {
             self.timer.sleep_millis(50).await;
         }
     }
 
     fn road_should_update(&mut self) -> bool {
         self.update_step = (self.update_step + 1) % UPDATE_STEP_SIZE;
-        self.update_step % ROAD_UPDATE_STEP_SIZE == 0
+        self.update_step.is_multiple_of(ROAD_UPDATE_STEP_SIZE)
     }
     fn can_move_car_horizontally(&mut self) -> bool {
-        self.update_step % (ROAD_UPDATE_STEP_SIZE / 4) == 0
+        self.update_step.is_multiple_of(ROAD_UPDATE_STEP_SIZE / 4)
     }
 
     fn should_update(&mut self) -> bool {
@@ -481,19 +673,18 @@ impl<'a, D: LedDisplay, C: GameController, T: Timer> RacesGame<'a, D, C, T> {
     }
 }
 
-impl<'a, D: LedDisplay, C: GameController, T: Timer> Game for RacesGame<'a, D, C, T> {
+impl<'a, D: LedDisplay, C: GameController, T: Timer, R: Rng> Game for RacesGame<'a, D, C, T, R> {
     async fn run(&mut self) {
         let mut leds = [RGB8::new(0, 0, 0); 256];
 
+        let mut frames = FrameLimit::new(self.config.max_frames);
         loop {
-            // Fire bullet on button press
-            if self.controller.joystick_was_pressed()
-                && self.bullet_count < self.bullets.len()
-                && self.max_bullets > 0
-            {
-                self.bullets[self.bullet_count] = Dot::new(self.car_pos.x, self.car_pos.y - 4);
-                self.bullet_count += 1;
-                self.max_bullets -= 1; // Decrement available bullets when firing
+            // Fire bullet on button press, on release when charging or
+            // repeatedly while held with auto-fire
+            let pressed = self.controller.joystick_was_pressed();
+            let held = self.controller.joystick_held();
+            if let Some(shot) = self.fire_button.update(pressed, held) {
+                self.fire(shot);
             }
 
             self.spawn_obstacles();
@@ -505,20 +696,20 @@ impl<'a, D: LedDisplay, C: GameController, T: Timer> Game for RacesGame<'a, D, C
             if self.can_move_car_horizontally() {
                 // Move car horizontally
                 if x != 0 {
-                    let new_x = self.car_pos.x + x;
-                    if new_x >= 1 && new_x <= SCREEN_WIDTH as i8 - 2 {
-                        self.car_pos.x = new_x;
-                    }
+                    self.car_pos = self
+                        .car_pos
+                        .move_by(Dot::new(x, 0))
+                        .clamped(CAR_MIN, CAR_MAX);
                 }
             }
 
             if self.road_should_update() {
                 // Move car vertically
                 if y != 0 {
-                    let new_y = self.car_pos.y + y;
-                    if new_y >= 3 && new_y < SCREEN_HEIGHT as i8 {
-                        self.car_pos.y = new_y;
-                    }
+                    self.car_pos = self
+                        .car_pos
+                        .move_by(Dot::new(0, y))
+                        .clamped(CAR_MIN, CAR_MAX);
                 }
 
                 self.update_obstacles();
@@ -532,6 +723,7 @@ impl<'a, D: LedDisplay, C: GameController, T: Timer> Game for RacesGame<'a, D, C
             }
             self.update_bullets();
             self.check_collisions();
+            self.collect_bullet_powerup();
 
             // Check game over
             if self.lives == 0 {
@@ -550,10 +742,246 @@ impl<'a, D: LedDisplay, C: GameController, T: Timer> Game for RacesGame<'a, D, C
             self.draw_score();
 
             // Update display
-            self.screen.render(&mut leds);
+            let offset = self.shake.next_offset(&mut self.prng);
+            self.screen.render_offset(&mut leds, offset);
             self.display.write(&leds).await;
 
-            self.timer.sleep_millis(20).await;
+            self.timer.sleep_millis(FRAME_MS).await;
+            if frames.tick() {
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{CaptureDisplay, Runner, ScriptedController, VirtualTimer};
+
+    #[test]
+    fn bullets_use_theme_color_distinct_from_obstacles() {
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let config = RacesConfig {
+            theme: RacesTheme {
+                player_bullet: ORANGE_IDX,
+                ..RacesTheme::default()
+            },
+            ..RacesConfig::default()
+        };
+        let mut game =
+            RacesGame::with_config(Prng::new(1), &mut display, &mut controller, &timer, config);
+
+        game.bullets[0] = Dot::new(3, 12);
+        game.bullet_count = 1;
+        game.obstacles[0] = Dot::new(2, 20);
+        game.obstacle_count = 1;
+        game.screen.clear();
+        game.draw_obstacles();
+        game.draw_bullets();
+
+        assert_eq!(game.screen.get(3, 12), ORANGE_IDX);
+        assert_eq!(game.screen.get(2, 20), config.theme.obstacle);
+        assert_ne!(config.theme.player_bullet, config.theme.obstacle);
+    }
+
+    /// Always the largest value, so shakes go down-right and nothing spawns
+    struct MaxRng;
+
+    impl Rng for MaxRng {
+        fn next_range(&mut self, max: u8) -> u8 {
+            max.saturating_sub(1)
+        }
+
+        fn next_range_u16(&mut self, max: u16) -> u16 {
+            max.saturating_sub(1)
+        }
+    }
+
+    #[test]
+    fn crash_shakes_rendered_frames_then_settles() {
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let config = RacesConfig {
+            shake: ShakeConfig {
+                intensity: 1,
+                frames: 2,
+            },
+            max_frames: Some(1),
+            ..RacesConfig::default()
+        };
+        let mut game =
+            RacesGame::with_config(MaxRng, &mut display, &mut controller, &timer, config);
+        game.obstacles[0] = game.car_pos;
+        game.obstacle_count = 1;
+
+        // One frame per run, keeping the static board of each
+        let offsets = [Dot::new(1, 1), Dot::new(1, 1), Dot::new(0, 0)];
+        let mut shaken = [[RGB8::default(); 256]; 3];
+        let mut still = [[RGB8::default(); 256]; 3];
+        for i in 0..offsets.len() {
+            let mut runner = Runner::new(game.run());
+            while runner.step() {}
+            drop(runner);
+            game.screen.render_offset(&mut shaken[i], offsets[i]);
+            game.screen.render(&mut still[i]);
+        }
+        assert_eq!(game.lives, config.difficulty.lives() - 1);
+
+        assert_eq!(display.frame_count(), 3);
+        for (i, leds) in shaken.iter().enumerate() {
+            assert_eq!(display.frame(i), *leds, "frame {}", i);
+        }
+        assert_ne!(display.frame(0), still[0]);
+        assert_ne!(display.frame(1), still[1]);
+        assert_eq!(display.frame(2), still[2]);
+    }
+
+    fn fired_pierce(hold_frames: usize) -> u8 {
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let input = controller.clone();
+        let config = RacesConfig {
+            charge_frames: Some(10),
+            ..RacesConfig::default()
+        };
+        let mut game =
+            RacesGame::with_config(Prng::new(1), &mut display, &mut controller, &timer, config);
+
+        let mut runner = Runner::new(game.run());
+        input.press_joystick();
+        input.set_joystick_held(hold_frames > 0);
+        runner.steps(hold_frames);
+        input.set_joystick_held(false);
+        runner.step();
+        drop(runner);
+
+        assert_eq!(game.bullet_count, 1);
+        game.bullet_pierce[0]
+    }
+
+    #[test]
+    fn long_hold_fires_piercing_bullet_and_tap_a_normal_one() {
+        assert_eq!(fired_pierce(0), 0);
+        assert_eq!(fired_pierce(3), 0);
+        assert_eq!(fired_pierce(12), 1);
+    }
+
+    fn auto_fired(hold_frames: usize) -> u8 {
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let input = controller.clone();
+        let config = RacesConfig {
+            auto_fire_frames: Some(3),
+            ..RacesConfig::default()
+        };
+        let mut game =
+            RacesGame::with_config(Prng::new(1), &mut display, &mut controller, &timer, config);
+        let ammo = game.max_bullets;
+
+        let mut runner = Runner::new(game.run());
+        input.press_joystick();
+        input.set_joystick_held(true);
+        runner.steps(hold_frames);
+        drop(runner);
+
+        ammo - game.max_bullets
+    }
+
+    #[test]
+    fn held_fire_repeats_until_out_of_ammo() {
+        assert_eq!(auto_fired(1), 1);
+        assert_eq!(auto_fired(7), 3);
+        // Five bullets of ammo, with a shot due every third frame
+        assert_eq!(auto_fired(40), 5);
+    }
+
+    #[test]
+    fn powerup_is_collected_only_when_a_cell_touches_the_car() {
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let mut game = RacesGame::new(Prng::new(1), &mut display, &mut controller, &timer);
+        let car = game.car_pos;
+        game.max_bullets = 2;
+
+        // Beside the car's nose, both dots sit on empty cells
+        game.bullet_powerup = Some(Dot::new(car.x + 1, car.y - 4));
+        game.collect_bullet_powerup();
+        assert!(game.bullet_powerup.is_some());
+        assert_eq!(game.max_bullets, 2);
+
+        // The lower dot lands on the car's wing
+        game.bullet_powerup = Some(Dot::new(car.x + 1, car.y - 3));
+        game.collect_bullet_powerup();
+        assert!(game.bullet_powerup.is_none());
+        assert_eq!(game.max_bullets, 3);
+
+        // Exactly over the car body
+        game.bullet_powerup = Some(Dot::new(car.x, car.y - 1));
+        game.collect_bullet_powerup();
+        assert!(game.bullet_powerup.is_none());
+        assert_eq!(game.max_bullets, 4);
+    }
+
+    #[test]
+    fn large_score_multiplier_saturates() {
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let mut game = RacesGame::new(Prng::new(1), &mut display, &mut controller, &timer);
+        game.set_score_multiplier(u8::MAX);
+
+        game.add_score(2);
+        assert_eq!(game.cars_destroyed, 2 * 255);
+        for _ in 0..300 {
+            game.add_score(1);
+        }
+        assert_eq!(game.cars_destroyed, u16::MAX);
+    }
+
+    #[test]
+    fn racers_are_hit_separately_and_respawn_apart() {
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let mut game = RacesGame::new(Prng::new(1), &mut display, &mut controller, &timer);
+        for i in 0..NUM_RACERS {
+            assert!(!game.overlaps_racer(i, game.racing_cars[i]));
+        }
+
+        // Shoot the last racer down, the others keep their health
+        let last = NUM_RACERS - 1;
+        game.racing_cars[last] = Dot::new(3, 20);
+        for hits in 1..=RACER_HEALTH {
+            game.bullets[0] = game.racing_cars[last];
+            game.bullet_count = 1;
+            game.check_collisions();
+            assert_eq!(game.bullet_count, 0);
+            if hits < RACER_HEALTH {
+                assert_eq!(game.racing_car_health[last], RACER_HEALTH - hits);
+            }
+        }
+        assert_eq!(game.cars_destroyed, 1);
+        assert_eq!(game.racing_car_health, [RACER_HEALTH; NUM_RACERS]);
+        assert!(game.racing_cars[last].y == 0 || game.racing_cars[last].y == SCREEN_HEIGHT as i8);
+
+        // A car right at the top blocks every lane, respawns wait for it
+        if NUM_RACERS > 1 {
+            for _ in 0..20 {
+                game.racing_cars[0] = Dot::new(3, 20);
+                game.racing_cars[1] = Dot::new(2, 12);
+                game.respawn_racer(0);
+                assert!(!game.overlaps_racer(0, game.racing_cars[0]));
+            }
+            game.racing_cars[1] = Dot::new(3, 1);
+            game.respawn_racer(0);
+            assert_eq!(game.racing_cars[0].y, SCREEN_HEIGHT as i8);
         }
     }
 }
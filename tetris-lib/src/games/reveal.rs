@@ -0,0 +1,233 @@
+use crate::common::{
+    present, FrameBuffer, GameController, LedDisplay, ScoreStore, Timer, BLACK_IDX, GREEN_IDX,
+    SCREEN_HEIGHT, YELLOW_IDX,
+};
+use crate::digits::DIGITS;
+
+// The count-up takes REVEAL_FRAMES * REVEAL_FRAME_MS, about one second
+pub const REVEAL_FRAMES: u16 = 20;
+const REVEAL_FRAME_MS: u64 = 50;
+const REVEAL_Y: i8 = 13;
+
+// "NEW HIGH" in the menu title layout, scrolled in one row per frame
+pub const NEW_HIGH_BANNER: [u32; 8] = [
+    0b_00000000000000000000000000000000,
+    0b_00000000000000000000000000000000,
+    0b_00101011101010001010100110101000,
+    0b_00111010001010001010101000101000,
+    0b_00111011001110001110101010111000,
+    0b_00111010001110001010101010101000,
+    0b_00101011101010001010100110101000,
+    0b_00000000000000000000000000000000,
+];
+const NEW_HIGH_FRAME_MS: u64 = 30;
+// The game over screen checks the buttons this often
+const GAME_OVER_POLL_MS: u64 = 50;
+
+/// Value shown on the given frame of the count-up to `score`
+pub fn reveal_value(score: u16, frame: u16) -> u16 {
+    (score as u32 * frame.min(REVEAL_FRAMES) as u32 / REVEAL_FRAMES as u32) as u16
+}
+
+/// Two-digit score centered on the screen
+pub fn score_frame(value: u16) -> FrameBuffer {
+    let value = value % 100;
+    let mut screen = FrameBuffer::new();
+    screen.draw_figure(
+        0,
+        REVEAL_Y,
+        DIGITS.wrapping_at((value / 10) as u8),
+        GREEN_IDX,
+    );
+    screen.draw_figure(
+        5,
+        REVEAL_Y,
+        DIGITS.wrapping_at((value % 10) as u8),
+        GREEN_IDX,
+    );
+    screen
+}
+
+/// Count the displayed score up from 0 to `score`, one step per frame.
+/// Any button press skips straight to the final value.
+pub async fn reveal_score<D, C, T>(display: &mut D, controller: &C, timer: &T, score: u16)
+where
+    D: LedDisplay,
+    C: GameController,
+    T: Timer,
+{
+    for frame in 0..=REVEAL_FRAMES {
+        let value = if controller.any_pressed() {
+            score
+        } else {
+            reveal_value(score, frame)
+        };
+        present(display, &score_frame(value)).await;
+        timer.sleep_millis(REVEAL_FRAME_MS).await;
+        if value == score {
+            break;
+        }
+    }
+}
+
+/// The banner scrolled up from the bottom edge by `frame` rows, it comes
+/// to rest once `frame` reaches `SCREEN_HEIGHT`
+pub fn new_high_frame(frame: usize) -> FrameBuffer {
+    let mut screen = FrameBuffer::from_rows(&NEW_HIGH_BANNER, YELLOW_IDX);
+    screen.shift_down(SCREEN_HEIGHT - frame.min(SCREEN_HEIGHT), BLACK_IDX);
+    screen
+}
+
+/// `reveal_score`, then record a score that beats the one in `store` and
+/// celebrate it with the scrolling "NEW HIGH" banner. Returns true on a
+/// new high score.
+pub async fn reveal_and_record<D, C, T, S>(
+    display: &mut D,
+    controller: &C,
+    timer: &T,
+    score: u16,
+    store: Option<&mut S>,
+) -> bool
+where
+    D: LedDisplay,
+    C: GameController,
+    T: Timer,
+    S: ScoreStore + ?Sized,
+{
+    reveal_score(display, controller, timer, score).await;
+    let Some(store) = store else {
+        return false;
+    };
+    if score <= store.high_score() {
+        return false;
+    }
+    store.set_high_score(score);
+
+    for frame in 0..=SCREEN_HEIGHT {
+        present(display, &new_high_frame(frame)).await;
+        timer.sleep_millis(NEW_HIGH_FRAME_MS).await;
+    }
+    true
+}
+
+/// Game over for the simple games: `reveal_and_record`, then wait for any
+/// button to go back to the menu
+pub async fn reveal_and_wait<D, C, T, S>(
+    display: &mut D,
+    controller: &C,
+    timer: &T,
+    score: u16,
+    store: Option<&mut S>,
+) where
+    D: LedDisplay,
+    C: GameController,
+    T: Timer,
+    S: ScoreStore + ?Sized,
+{
+    reveal_and_record(display, controller, timer, score, store).await;
+    while !controller.any_pressed() {
+        timer.sleep_millis(GAME_OVER_POLL_MS).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{BestScore, CaptureDisplay, Runner, ScriptedController, VirtualTimer};
+    use core::cell::Cell;
+
+    fn reveal(score: u16, skip: bool) -> CaptureDisplay {
+        let mut display = CaptureDisplay::new();
+        let controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let capture = display.clone();
+        if skip {
+            controller.press_joystick();
+        }
+
+        let mut runner = Runner::new(reveal_score(&mut display, &controller, &timer, score));
+        while runner.step() {}
+        capture
+    }
+
+    #[test]
+    fn score_counts_up_each_frame() {
+        let frames = reveal(40, false);
+        assert_eq!(frames.frame_count(), REVEAL_FRAMES as usize + 1);
+        for frame in 0..=REVEAL_FRAMES {
+            let expected = score_frame(2 * frame);
+            assert!(
+                frames.frame_buffer(frame as usize) == expected,
+                "frame {}",
+                frame
+            );
+        }
+    }
+
+    #[test]
+    fn press_skips_to_final_score() {
+        let frames = reveal(40, true);
+        assert_eq!(frames.frame_count(), 1);
+        assert!(frames.last_frame_buffer() == score_frame(40));
+    }
+
+    fn reveal_with_store(score: u16, store: &mut BestScore) -> (bool, CaptureDisplay) {
+        let mut display = CaptureDisplay::new();
+        let controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let capture = display.clone();
+        controller.press_joystick();
+
+        let recorded = Cell::new(false);
+        let mut runner = Runner::new(async {
+            let new_high =
+                reveal_and_record(&mut display, &controller, &timer, score, Some(store)).await;
+            recorded.set(new_high);
+        });
+        while runner.step() {}
+        drop(runner);
+        (recorded.get(), capture)
+    }
+
+    #[test]
+    fn beating_high_score_shows_banner_and_records_it() {
+        let mut store = BestScore(40);
+        let (new_high, frames) = reveal_with_store(41, &mut store);
+        assert!(new_high);
+        assert_eq!(store.0, 41);
+        // The skipped count-up, then the banner scrolling in
+        assert_eq!(frames.frame_count(), 1 + SCREEN_HEIGHT + 1);
+        assert!(frames.frame_buffer(1) == new_high_frame(0));
+        assert!(frames.last_frame_buffer() == new_high_frame(SCREEN_HEIGHT));
+        assert!(frames.last_frame_buffer() == FrameBuffer::from_rows(&NEW_HIGH_BANNER, YELLOW_IDX));
+
+        // Matching the record is not a new one
+        let (new_high, frames) = reveal_with_store(41, &mut store);
+        assert!(!new_high);
+        assert_eq!(store.0, 41);
+        assert_eq!(frames.frame_count(), 1);
+    }
+
+    #[test]
+    fn game_over_waits_for_a_press() {
+        let mut display = CaptureDisplay::new();
+        let controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let capture = display.clone();
+        let mut store = BestScore(0);
+
+        let mut runner = Runner::new(reveal_and_wait(
+            &mut display,
+            &controller,
+            &timer,
+            0,
+            Some(&mut store),
+        ));
+        assert!(runner.steps(50));
+        // Only the reveal was drawn, the wait keeps the screen
+        assert_eq!(capture.frame_count(), 1);
+
+        controller.press_b();
+        assert!(!runner.steps(2));
+    }
+}
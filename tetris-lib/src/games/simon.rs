@@ -0,0 +1,421 @@
+use smart_leds::RGB8;
+
+use crate::{
+    common::{
+        fade, Background, Dot, FrameBuffer, FrameLimit, Game, GameController, HudSeparator,
+        LedDisplay, Motion, Prng, Rng, ScoreStore, StatusEvent, StatusSink, Timer, HUD_SCORE_COLOR,
+        HUD_SEPARATOR_Y, LIGHT_BLUE_IDX, LIGHT_GREEN_IDX, ORANGE_IDX, PURPLE_IDX, SCREEN_HEIGHT,
+        SCREEN_WIDTH,
+    },
+    games::reveal::reveal_and_wait,
+};
+
+/// Longest sequence, repeating it wins the game
+pub const MAX_SEQUENCE: usize = 99;
+/// Quadrant colors in reading order, each shows dimmed by `fade` until lit
+const QUADRANT_COLORS: [u8; 4] = [ORANGE_IDX, LIGHT_GREEN_IDX, LIGHT_BLUE_IDX, PURPLE_IDX];
+const QUADRANT_WIDTH: usize = SCREEN_WIDTH / 2;
+const PLAY_TOP: usize = HUD_SEPARATOR_Y + 1;
+const QUADRANT_HEIGHT: usize = (SCREEN_HEIGHT - PLAY_TOP) / 2;
+const FRAME_MS: u64 = 20;
+/// Times every quadrant flashes together after the longest sequence
+const WIN_FLASHES: usize = 3;
+/// Lit mask with every quadrant on
+const ALL_QUADRANTS: u8 = 0b1111;
+
+/// Lit mask with only `quadrant` on
+const fn quadrant_bit(quadrant: u8) -> u8 {
+    1 << quadrant
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct SimonConfig {
+    /// How long each step of the sequence lights up
+    pub flash_ms: u64,
+    /// Dark time between two flashes
+    pub pause_ms: u64,
+    pub background: Background,
+    pub hud_separator: HudSeparator,
+    pub motion: Motion,
+    /// Return from `run` after this many frames, for tests and benchmarks.
+    /// `None` plays until game over.
+    pub max_frames: Option<u64>,
+}
+
+impl Default for SimonConfig {
+    fn default() -> Self {
+        Self {
+            flash_ms: 400,
+            pause_ms: 200,
+            background: Background::None,
+            hud_separator: HudSeparator::default(),
+            motion: Motion::Full,
+            max_frames: None,
+        }
+    }
+}
+
+/// Result of the player choosing a quadrant
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Answer {
+    /// Matches the sequence, more steps to go
+    Correct,
+    /// Matches the last step of the sequence
+    Repeated,
+    Wrong,
+}
+
+pub struct SimonGame<'a, D, C, T, R = Prng> {
+    screen: FrameBuffer,
+    display: &'a mut D,
+    controller: &'a mut C,
+    timer: &'a T,
+    prng: R,
+
+    // Quadrant indices, the first `length` are in play
+    sequence: [u8; MAX_SEQUENCE],
+    length: usize,
+    // Step of the sequence the player repeats next
+    position: usize,
+    // Quadrant chosen by the joystick, in reading order
+    cursor: u8,
+    // Longest sequence repeated
+    score: u16,
+    config: SimonConfig,
+    status: Option<&'a mut dyn StatusSink>,
+    scores: Option<&'a mut dyn ScoreStore>,
+    // Frames drawn, scrolls a score too long for the HUD
+    hud_frames: u16,
+}
+
+impl<'a, D: LedDisplay, C: GameController, T: Timer, R: Rng> SimonGame<'a, D, C, T, R> {
+    pub fn new(prng: R, display: &'a mut D, controller: &'a mut C, timer: &'a T) -> Self {
+        Self::with_config(prng, display, controller, timer, SimonConfig::default())
+    }
+
+    pub fn with_config(
+        prng: R,
+        display: &'a mut D,
+        controller: &'a mut C,
+        timer: &'a T,
+        config: SimonConfig,
+    ) -> Self {
+        Self {
+            screen: FrameBuffer::new(),
+            display,
+            controller,
+            timer,
+            prng,
+            sequence: [0; MAX_SEQUENCE],
+            length: 0,
+            position: 0,
+            cursor: 0,
+            score: 0,
+            config,
+            status: None,
+            scores: None,
+            hud_frames: 0,
+        }
+    }
+
+    /// Report score changes and game over to `sink`
+    pub fn with_status_sink(mut self, sink: &'a mut dyn StatusSink) -> Self {
+        self.status = Some(sink);
+        self
+    }
+
+    /// Record a new high score in `store` at game over
+    pub fn with_score_store(mut self, store: &'a mut dyn ScoreStore) -> Self {
+        self.scores = Some(store);
+        self
+    }
+
+    fn notify(&mut self, event: StatusEvent) {
+        if let Some(status) = self.status.as_mut() {
+            status.notify(event);
+        }
+    }
+
+    /// Add a random quadrant to the end of the sequence, false once it is
+    /// already `MAX_SEQUENCE` long
+    fn extend_sequence(&mut self) -> bool {
+        if self.length == MAX_SEQUENCE {
+            return false;
+        }
+        self.sequence[self.length] = self.prng.next_range(QUADRANT_COLORS.len() as u8);
+        self.length += 1;
+        self.position = 0;
+        true
+    }
+
+    /// Check the player's choice against the next step of the sequence
+    fn answer(&mut self, quadrant: u8) -> Answer {
+        if self.sequence[self.position] != quadrant {
+            return Answer::Wrong;
+        }
+        self.position += 1;
+        if self.position < self.length {
+            return Answer::Correct;
+        }
+        self.position = 0;
+        Answer::Repeated
+    }
+
+    /// Move the cursor to the neighbor quadrant in `direction`, the grid
+    /// doesn't wrap
+    fn move_cursor(&mut self, direction: Dot) {
+        let col = (self.cursor % 2) as i8 + direction.x;
+        let row = (self.cursor / 2) as i8 + direction.y;
+        if (0..2).contains(&col) && (0..2).contains(&row) {
+            self.cursor = (row * 2 + col) as u8;
+        }
+    }
+
+    /// Draw every quadrant dimmed except those set in the `lit` mask, and
+    /// the outline of the one under `cursor`
+    fn draw(&mut self, lit: u8, cursor: Option<u8>) {
+        self.screen.clear();
+        self.hud_frames = self.hud_frames.wrapping_add(1);
+        self.screen
+            .draw_hud_score(self.score, self.hud_frames, HUD_SCORE_COLOR);
+        self.screen
+            .draw_hud_separator(HUD_SEPARATOR_Y, self.config.hud_separator);
+
+        for (quadrant, &color) in QUADRANT_COLORS.iter().enumerate() {
+            let quadrant = quadrant as u8;
+            let x0 = (quadrant % 2) as i8 * QUADRANT_WIDTH as i8;
+            let y0 = (PLAY_TOP + usize::from(quadrant / 2) * QUADRANT_HEIGHT) as i8;
+            let (w, h) = (QUADRANT_WIDTH as u8, QUADRANT_HEIGHT as u8);
+            let fill = if lit & quadrant_bit(quadrant) != 0 {
+                color
+            } else {
+                fade(color)
+            };
+            self.screen.fill_rect(x0, y0, w, h, fill);
+            if cursor == Some(quadrant) {
+                self.screen.draw_rect(x0, y0, w, h, color);
+            }
+        }
+    }
+
+    async fn present(&mut self, leds: &mut [RGB8; 256]) {
+        self.screen.render_over(leds, self.config.background);
+        self.display.write(leds).await;
+    }
+
+    /// Flash the sequence so far, one quadrant at a time
+    async fn show_sequence(&mut self, leds: &mut [RGB8; 256]) {
+        for step in 0..self.length {
+            self.draw(0, None);
+            self.present(leds).await;
+            self.timer.sleep_millis(self.config.pause_ms).await;
+
+            self.draw(quadrant_bit(self.sequence[step]), None);
+            self.present(leds).await;
+            self.timer.sleep_millis(self.config.flash_ms).await;
+        }
+        self.draw(0, None);
+        self.present(leds).await;
+        self.timer.sleep_millis(self.config.pause_ms).await;
+    }
+
+    /// Flash every quadrant at once, the whole sequence was repeated
+    async fn celebrate(&mut self, leds: &mut [RGB8; 256]) {
+        for _ in 0..WIN_FLASHES {
+            self.draw(ALL_QUADRANTS, None);
+            self.present(leds).await;
+            self.timer.sleep_millis(self.config.flash_ms).await;
+
+            self.draw(0, None);
+            self.present(leds).await;
+            self.timer.sleep_millis(self.config.pause_ms).await;
+        }
+    }
+
+    async fn game_over(&mut self) {
+        reveal_and_wait(
+            &mut *self.display,
+            &*self.controller,
+            self.timer,
+            self.score,
+            self.scores.as_deref_mut(),
+        )
+        .await;
+    }
+}
+
+impl<'a, D: LedDisplay, C: GameController, T: Timer, R: Rng> Game for SimonGame<'a, D, C, T, R> {
+    async fn run(&mut self) {
+        let mut leds = [RGB8::new(0, 0, 0); 256];
+        // The cursor moves once per push, like the menu
+        let mut last_direction = Dot::new(0, 0);
+
+        let mut frames = FrameLimit::new(self.config.max_frames);
+        'game: loop {
+            if !self.extend_sequence() {
+                self.celebrate(&mut leds).await;
+                break;
+            }
+            self.show_sequence(&mut leds).await;
+            // Presses made while the sequence played don't count
+            self.controller.flush_presses();
+
+            loop {
+                let x = self.controller.read_x().await;
+                let y = self.controller.read_y().await;
+                let direction = Dot::new(x, y).to_direction();
+                if last_direction.is_zero() {
+                    self.move_cursor(direction);
+                }
+                last_direction = direction;
+
+                if self.controller.joystick_was_pressed() | self.controller.a_was_pressed() {
+                    let quadrant = self.cursor;
+                    self.draw(quadrant_bit(quadrant), Some(quadrant));
+                    self.present(&mut leds).await;
+                    self.timer.sleep_millis(self.config.pause_ms).await;
+
+                    match self.answer(quadrant) {
+                        Answer::Correct => {}
+                        Answer::Repeated => {
+                            self.score = self.length as u16;
+                            self.notify(StatusEvent::Score(self.score));
+                            break;
+                        }
+                        Answer::Wrong => break 'game,
+                    }
+                }
+
+                self.draw(0, Some(self.cursor));
+                self.present(&mut leds).await;
+                self.timer.sleep_millis(FRAME_MS).await;
+                if frames.tick() {
+                    return;
+                }
+            }
+        }
+
+        self.notify(StatusEvent::GameOver);
+        self.game_over().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{
+        BestScore, CaptureDisplay, Runner, ScriptedController, StatusRecorder, VirtualTimer,
+    };
+
+    #[test]
+    fn sequence_grows_by_one_random_quadrant_per_round() {
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let mut game = SimonGame::new(Prng::new(7), &mut display, &mut controller, &timer);
+
+        let mut prng = Prng::new(7);
+        for length in 1..=MAX_SEQUENCE {
+            assert!(game.extend_sequence());
+            assert_eq!(game.length, length);
+            assert_eq!(game.sequence[length - 1], prng.next_range(4));
+        }
+        assert!(!game.extend_sequence());
+        assert_eq!(game.length, MAX_SEQUENCE);
+        // Every quadrant shows up
+        for quadrant in 0..4 {
+            assert!(game.sequence.contains(&quadrant));
+        }
+    }
+
+    #[test]
+    fn answers_are_matched_step_by_step() {
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let mut game = SimonGame::new(Prng::new(1), &mut display, &mut controller, &timer);
+        game.sequence[..3].copy_from_slice(&[2, 0, 3]);
+        game.length = 3;
+
+        assert_eq!(game.answer(2), Answer::Correct);
+        assert_eq!(game.answer(0), Answer::Correct);
+        assert_eq!(game.answer(3), Answer::Repeated);
+        // The next round starts over from the first step
+        assert_eq!(game.answer(0), Answer::Wrong);
+        assert_eq!(game.position, 0);
+        assert_eq!(game.answer(2), Answer::Correct);
+        assert_eq!(game.answer(2), Answer::Wrong);
+    }
+
+    #[test]
+    fn repeating_the_longest_sequence_wins() {
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let mut status = StatusRecorder::new();
+        let mut best = BestScore(0);
+        let input = controller.clone();
+        let capture = display.clone();
+        let mut game = SimonGame::new(Prng::new(5), &mut display, &mut controller, &timer)
+            .with_status_sink(&mut status)
+            .with_score_store(&mut best);
+        // One round short of the longest sequence, all on the quadrant the
+        // last round adds, so every press of A answers right
+        let last = Prng::new(5).next_range(4);
+        game.sequence = [last; MAX_SEQUENCE];
+        game.length = MAX_SEQUENCE - 1;
+        game.cursor = last;
+
+        let mut runner = Runner::new(game.run());
+        let mut steps = 0;
+        while runner.step() {
+            steps += 1;
+            assert!(steps < 10_000);
+            input.press_a();
+        }
+        drop(runner);
+        assert_eq!(game.score, MAX_SEQUENCE as u16);
+
+        assert_eq!(
+            status.events,
+            [
+                StatusEvent::Score(MAX_SEQUENCE as u16),
+                StatusEvent::GameOver
+            ]
+        );
+        assert_eq!(best.0, MAX_SEQUENCE as u16);
+        // Every quadrant lit at once, which no round of the sequence does
+        let all_lit = (0..capture.frame_count()).any(|idx| {
+            let frame = capture.frame_buffer(idx);
+            QUADRANT_COLORS
+                .iter()
+                .enumerate()
+                .all(|(quadrant, &color)| {
+                    let x = (quadrant % 2) * QUADRANT_WIDTH + QUADRANT_WIDTH / 2;
+                    let y = PLAY_TOP + (quadrant / 2) * QUADRANT_HEIGHT + QUADRANT_HEIGHT / 2;
+                    frame.get(x, y) == color
+                })
+        });
+        assert!(all_lit);
+    }
+
+    #[test]
+    fn cursor_moves_within_the_quadrant_grid() {
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let mut game = SimonGame::new(Prng::new(1), &mut display, &mut controller, &timer);
+
+        game.move_cursor(Dot::new(-1, 0));
+        game.move_cursor(Dot::new(0, -1));
+        assert_eq!(game.cursor, 0);
+        game.move_cursor(Dot::new(1, 0));
+        assert_eq!(game.cursor, 1);
+        game.move_cursor(Dot::new(0, 1));
+        assert_eq!(game.cursor, 3);
+        game.move_cursor(Dot::new(1, 0));
+        assert_eq!(game.cursor, 3);
+        game.move_cursor(Dot::new(-1, 0));
+        assert_eq!(game.cursor, 2);
+    }
+}
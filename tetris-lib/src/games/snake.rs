@@ -2,13 +2,117 @@ use smart_leds::RGB8;
 
 use crate::{
     common::{
-        Dot, FrameBuffer, Game, GameController, LedDisplay, Prng, Timer, DARK_GREEN_IDX, GREEN_IDX,
-        LIGHT_GREEN_IDX, PINK_IDX, RED_IDX, SCREEN_HEIGHT, SCREEN_WIDTH,
+        blink, hud_swap_frames, Background, Difficulty, Dot, FrameBuffer, FrameLimit, Game,
+        GameController, HudSeparator, LedDisplay, Motion, Prng, Rng, ScoreStore, StatusEvent,
+        StatusSink, Timer, BRICK_IDX, DARK_GREEN_IDX, GREEN_IDX, HUD_HEART_SLOTS, HUD_LIVES_COLOR,
+        HUD_SCORE_COLOR, HUD_SEPARATOR_Y, LIGHT_GREEN_IDX, RED_IDX, SCREEN_HEIGHT, SCREEN_WIDTH,
     },
     digits::DIGITS,
+    games::{prompt::offer_continue, reveal::reveal_and_record},
 };
 
-pub struct SnakeGame<'a, D, C, T> {
+const FRAME_MS: u64 = 20;
+const HUD_SWAP_FRAMES: u16 = hud_swap_frames(FRAME_MS);
+
+/// Most apples `SnakeConfig::apples` can put on the board at once
+pub const MAX_APPLES: usize = 3;
+
+/// Where an apple waits while the snake leaves no free cell, it can't be
+/// drawn or eaten there
+const NO_APPLE: Dot = Dot::new(-1, -1);
+
+/// Two short pillars, clear of the row the snake starts on
+const PILLARS: [Dot; 6] = [
+    Dot::new(2, 9),
+    Dot::new(2, 10),
+    Dot::new(2, 11),
+    Dot::new(5, 22),
+    Dot::new(5, 23),
+    Dot::new(5, 24),
+];
+
+/// Blocks in the playfield corners, which a wrapping snake reaches from
+/// the opposite edge
+const CORNERS: [Dot; 8] = [
+    Dot::new(0, 6),
+    Dot::new(1, 6),
+    Dot::new(6, 6),
+    Dot::new(7, 6),
+    Dot::new(0, 31),
+    Dot::new(1, 31),
+    Dot::new(6, 31),
+    Dot::new(7, 31),
+];
+
+/// Static blocks on the playfield that crash the snake like its own body
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Obstacles {
+    #[default]
+    None,
+    Pillars,
+    Corners,
+}
+
+impl Obstacles {
+    pub fn cells(self) -> &'static [Dot] {
+        match self {
+            Obstacles::None => &[],
+            Obstacles::Pillars => &PILLARS,
+            Obstacles::Corners => &CORNERS,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct SnakeConfig {
+    /// Upper bound for the per-tick step increment. A move happens once the
+    /// accumulated steps reach 30, so 30 would mean a move every tick.
+    pub max_speedup: u8,
+    pub background: Background,
+    /// Score to start from for practice, the speed follows it as usual
+    pub start_score: u16,
+    /// Score paid to continue with the same snake after a crash, `None`
+    /// disables the offer
+    pub continue_cost: Option<u16>,
+    /// Sets the lives, after losing one the snake stays where it crashed
+    pub difficulty: Difficulty,
+    /// Every point scored counts this many times, e.g. to reward a harder
+    /// difficulty. Can be changed mid-game with `set_score_multiplier`.
+    pub score_multiplier: u8,
+    pub hud_separator: HudSeparator,
+    /// The snake leaves one edge and comes back at the opposite one. When
+    /// off, the screen edges and the HUD separator are walls that crash
+    /// the snake like its own body.
+    pub wrap: bool,
+    /// Apples on the board at once, 1 to `MAX_APPLES`
+    pub apples: u8,
+    pub obstacles: Obstacles,
+    pub motion: Motion,
+    /// Return from `run` after this many frames, for tests and benchmarks.
+    /// `None` plays until game over.
+    pub max_frames: Option<u64>,
+}
+
+impl Default for SnakeConfig {
+    fn default() -> Self {
+        Self {
+            max_speedup: 15,
+            background: Background::None,
+            start_score: 0,
+            continue_cost: None,
+            difficulty: Difficulty::default(),
+            score_multiplier: 1,
+            hud_separator: HudSeparator::default(),
+            wrap: true,
+            apples: 1,
+            obstacles: Obstacles::None,
+            motion: Motion::Full,
+            max_frames: None,
+        }
+    }
+}
+
+pub struct SnakeGame<'a, D, C, T, R = Prng> {
     screen: FrameBuffer,
     display: &'a mut D,
     controller: &'a mut C,
@@ -17,14 +121,33 @@ pub struct SnakeGame<'a, D, C, T> {
     body: [Dot; 256],
     body_len: usize,
     direction: Dot,
-    next_direction: Dot,
-    apple: Dot,
-    prng: Prng,
-    score: u8,
+    // First turn requested since the last move, applied by the next one
+    pending_turn: Option<Dot>,
+    // The first `apple_count` are on the board
+    apples: [Dot; MAX_APPLES],
+    apple_count: usize,
+    prng: R,
+    // Only the last two digits are displayed, difficulty uses the full value
+    score: u16,
+    lives: u8,
+    hud_frames: u16,
+    config: SnakeConfig,
+    status: Option<&'a mut dyn StatusSink>,
+    scores: Option<&'a mut dyn ScoreStore>,
 }
 
-impl<'a, D: LedDisplay, C: GameController, T: Timer> SnakeGame<'a, D, C, T> {
-    pub fn new(prng: Prng, display: &'a mut D, controller: &'a mut C, timer: &'a T) -> Self {
+impl<'a, D: LedDisplay, C: GameController, T: Timer, R: Rng> SnakeGame<'a, D, C, T, R> {
+    pub fn new(prng: R, display: &'a mut D, controller: &'a mut C, timer: &'a T) -> Self {
+        Self::with_config(prng, display, controller, timer, SnakeConfig::default())
+    }
+
+    pub fn with_config(
+        prng: R,
+        display: &'a mut D,
+        controller: &'a mut C,
+        timer: &'a T,
+        config: SnakeConfig,
+    ) -> Self {
         let mut game = Self {
             screen: FrameBuffer::new(),
             display,
@@ -33,10 +156,16 @@ impl<'a, D: LedDisplay, C: GameController, T: Timer> SnakeGame<'a, D, C, T> {
             body: [Dot::new(0, 0); 256],
             body_len: 3,
             direction: Dot::new(1, 0),
-            next_direction: Dot::new(1, 0),
-            apple: Dot::new(0, 0),
+            pending_turn: None,
+            apples: [Dot::new(0, 0); MAX_APPLES],
+            apple_count: usize::from(config.apples).clamp(1, MAX_APPLES),
             prng,
-            score: 0,
+            score: config.start_score,
+            lives: config.difficulty.lives(),
+            hud_frames: 0,
+            config,
+            status: None,
+            scores: None,
         };
 
         // Initialize snake body
@@ -44,11 +173,59 @@ impl<'a, D: LedDisplay, C: GameController, T: Timer> SnakeGame<'a, D, C, T> {
         game.body[1] = Dot::new(2, 15);
         game.body[2] = Dot::new(1, 15);
 
-        game.respawn_apple();
+        for slot in 0..game.apple_count {
+            game.respawn_apple(slot);
+        }
         game
     }
 
-    fn respawn_apple(&mut self) {
+    /// Classic Snake where hitting a wall costs a life
+    pub fn with_walls(prng: R, display: &'a mut D, controller: &'a mut C, timer: &'a T) -> Self {
+        let config = SnakeConfig {
+            wrap: false,
+            ..SnakeConfig::default()
+        };
+        Self::with_config(prng, display, controller, timer, config)
+    }
+
+    /// Report score changes and game over to `sink`
+    pub fn with_status_sink(mut self, sink: &'a mut dyn StatusSink) -> Self {
+        self.status = Some(sink);
+        self
+    }
+
+    /// Record a new high score in `store` at game over
+    pub fn with_score_store(mut self, store: &'a mut dyn ScoreStore) -> Self {
+        self.scores = Some(store);
+        self
+    }
+
+    pub fn set_score_multiplier(&mut self, multiplier: u8) {
+        self.config.score_multiplier = multiplier;
+    }
+
+    fn add_score(&mut self, base: u16) {
+        let points = base.saturating_mul(u16::from(self.config.score_multiplier));
+        self.score = self.score.saturating_add(points);
+    }
+
+    fn notify(&mut self, event: StatusEvent) {
+        if let Some(status) = self.status.as_mut() {
+            status.notify(event);
+        }
+    }
+
+    /// Put the apple in `slot` on a free cell, away from the snake and the
+    /// other apples. With no free cell left the apple goes off the board.
+    fn respawn_apple(&mut self, slot: usize) {
+        let free = (6..SCREEN_HEIGHT as i8)
+            .flat_map(|y| (0..SCREEN_WIDTH as i8).map(move |x| Dot::new(x, y)))
+            .any(|dot| self.apple_fits(slot, dot));
+        if !free {
+            self.apples[slot] = NO_APPLE;
+            return;
+        }
+
         loop {
             let x = self.prng.next_range(SCREEN_WIDTH as u8) as i8;
             let y = self
@@ -57,55 +234,92 @@ impl<'a, D: LedDisplay, C: GameController, T: Timer> SnakeGame<'a, D, C, T> {
                 .clamp(6, SCREEN_HEIGHT as u8) as i8;
             let new_apple = Dot::new(x, y);
 
-            // Check if apple spawns on snake body
-            let mut valid = true;
-            for i in 0..self.body_len {
-                if self.body[i] == new_apple {
-                    valid = false;
-                    break;
-                }
-            }
-
-            if valid {
-                self.apple = new_apple;
+            if self.apple_fits(slot, new_apple) {
+                self.apples[slot] = new_apple;
                 break;
             }
         }
     }
 
+    // True if `dot` is clear of the snake body, obstacles and other apples
+    fn apple_fits(&self, slot: usize, dot: Dot) -> bool {
+        !self.body[..self.body_len].contains(&dot)
+            && !self.config.obstacles.cells().contains(&dot)
+            && !self.apples[..self.apple_count]
+                .iter()
+                .enumerate()
+                .any(|(i, apple)| i != slot && *apple == dot)
+    }
+
+    /// Buffer a turn for the next move. Only the first turn of each move
+    /// counts, so quick inputs in between can't chain into a reversal.
+    fn queue_turn(&mut self, direction: Dot) {
+        if self.pending_turn.is_none()
+            && !direction.is_zero()
+            && direction != self.direction
+            && !direction.is_opposite(&self.direction)
+        {
+            self.pending_turn = Some(direction);
+        }
+    }
+
     fn move_forward(&mut self) -> bool {
-        if !self.direction.is_opposite(&self.next_direction) {
-            self.direction = self.next_direction;
+        if let Some(turn) = self.pending_turn.take() {
+            self.direction = turn;
         }
         let head = self.body[0];
-        let new_head = head.move_wrap(self.direction);
+        let new_head = if self.config.wrap {
+            head.move_wrap(self.direction)
+        } else {
+            let new_head = head.move_by(self.direction);
+            if !new_head.in_bounds() || new_head.y <= HUD_SEPARATOR_Y as i8 {
+                return false;
+            }
+            new_head
+        };
 
-        // Check collision with self
+        // Check collision with obstacles and self
+        if self.config.obstacles.cells().contains(&new_head) {
+            return false;
+        }
         for i in 0..self.body_len {
             if self.body[i] == new_head {
                 return false;
             }
         }
 
-        // Move body
+        // Move body, the tail cell it leaves is where a grown snake ends
+        let tail = self.body[self.body_len - 1];
         for i in (1..self.body_len).rev() {
             self.body[i] = self.body[i - 1];
         }
         self.body[0] = new_head;
 
-        // Check if apple is eaten
-        if new_head == self.apple {
-            if self.body_len < 32 {
+        // Check if an apple is eaten
+        let eaten = self.apples[..self.apple_count]
+            .iter()
+            .position(|&apple| apple == new_head);
+        if let Some(slot) = eaten {
+            if self.body_len < self.body.len() {
+                self.body[self.body_len] = tail;
                 self.body_len += 1;
-                self.body[self.body_len - 1] = self.body[self.body_len - 2];
             }
-            self.score += 1;
-            self.respawn_apple();
+            self.add_score(1);
+            self.notify(StatusEvent::Score(self.score));
+            self.respawn_apple(slot);
         }
 
         true
     }
 
+    /// Step increment per tick, faster while the player holds the current
+    /// direction and as the score grows, clamped to `max_speedup`
+    fn speedup(&self, boosting: bool) -> u8 {
+        let base: u16 = if boosting { 5 } else { 1 };
+        let speedup = base + self.score / 10;
+        speedup.min(self.config.max_speedup as u16) as u8
+    }
+
     fn draw_snake(&mut self) {
         for i in 0..self.body_len {
             let dot = self.body[i];
@@ -119,69 +333,99 @@ impl<'a, D: LedDisplay, C: GameController, T: Timer> SnakeGame<'a, D, C, T> {
     }
 
     fn draw_score(&mut self) {
-        let score_display = (self.score % 100) as usize;
-        let tens = score_display / 10;
-        let ones = score_display % 10;
+        // The score and the lives take turns, shown at the next move
+        if blink(self.hud_frames, HUD_SWAP_FRAMES) {
+            let score_display = (self.score % 100) as usize;
+            let tens = score_display / 10;
+            let ones = score_display % 10;
+
+            self.screen
+                .draw_figure(0, 0, &DIGITS[tens], HUD_SCORE_COLOR);
+            self.screen
+                .draw_figure(4, 0, &DIGITS[ones], HUD_SCORE_COLOR);
+        } else {
+            self.screen
+                .draw_hud_lives(self.lives, &HUD_HEART_SLOTS, HUD_LIVES_COLOR);
+        }
+        self.screen
+            .draw_hud_separator(HUD_SEPARATOR_Y, self.config.hud_separator);
+    }
 
-        self.screen.draw_figure(0, 0, &DIGITS[tens], GREEN_IDX);
-        self.screen.draw_figure(4, 0, &DIGITS[ones], GREEN_IDX);
-        for x in 0..SCREEN_WIDTH {
-            self.screen.set(x, 5, PINK_IDX);
+    /// Take a life, true if any are left to keep playing
+    fn lose_life(&mut self) -> bool {
+        self.lives = self.lives.saturating_sub(1);
+        self.lives > 0
+    }
+
+    /// Offer to continue when enabled and affordable, paying for it on accept
+    async fn try_continue(&mut self) -> bool {
+        let Some(cost) = self.config.continue_cost else {
+            return false;
+        };
+        if self.score < cost
+            || !offer_continue(&mut *self.display, &*self.controller, self.timer).await
+        {
+            return false;
         }
+        self.score -= cost;
+        true
     }
 
     async fn game_over(&mut self, mut leds: [RGB8; 256]) {
+        reveal_and_record(
+            &mut *self.display,
+            &*self.controller,
+            self.timer,
+            self.score,
+            self.scores.as_deref_mut(),
+        )
+        .await;
+
         for _ in 0..3 {
             self.screen.clear();
-            self.screen.render(&mut leds);
-            self.display.write(&leds).await;
+            // With reduced motion the final screen stays on instead of flashing
+            if !self.config.motion.is_reduced() {
+                self.screen.render_over(&mut leds, self.config.background);
+                self.display.write(&leds).await;
+            }
             self.timer.sleep_millis(200).await;
 
             self.draw_snake();
             self.draw_score();
-            self.screen.render(&mut leds);
+            self.screen.render_over(&mut leds, self.config.background);
             self.display.write(&leds).await;
             self.timer.sleep_millis(200).await;
         }
 
         // Wait for button press
-        while !self.controller.joystick_was_pressed() {
+        while !self.controller.any_pressed() {
             self.timer.sleep_millis(50).await;
         }
     }
 }
 
-impl<'a, D: LedDisplay, C: GameController, T: Timer> Game for SnakeGame<'a, D, C, T> {
+impl<'a, D: LedDisplay, C: GameController, T: Timer, R: Rng> Game for SnakeGame<'a, D, C, T, R> {
     async fn run(&mut self) {
         let mut leds = [RGB8::new(0, 0, 0); 256];
         let mut step = 30;
-        let mut speedup;
 
+        let mut frames = FrameLimit::new(self.config.max_frames);
         loop {
             // Handle joystick input
             let x = self.controller.read_x().await;
             let y = self.controller.read_y().await;
             let direction = Dot::new(x, y).to_direction();
-            if !direction.is_zero() {
-                self.next_direction = direction;
-            }
+            self.queue_turn(direction);
 
-            if self.direction == direction {
-                speedup = 5;
-            } else {
-                // Reset to normal speed when no direction is pressed
-                speedup = 1;
-            }
-            // Adjust the snake's speed based on the score.
-            speedup += self.score / 10;
-            if self.score > 99 {
-                self.score = 0;
-            }
+            // Speed up while the current direction is held
+            let speedup = self.speedup(self.direction == direction);
 
             if step >= 30 {
                 step = 0;
-                // Move snake
-                if !self.move_forward() {
+                // Move snake. After a lost life or a continue it stays
+                // where it crashed and the player has to steer away.
+                if !self.move_forward() && !self.lose_life() && !self.try_continue().await {
+                    self.notify(StatusEvent::GameOver);
                     self.game_over(leds).await;
                     break;
                 }
@@ -190,14 +434,345 @@ impl<'a, D: LedDisplay, C: GameController, T: Timer> Game for SnakeGame<'a, D, C
                 self.screen.clear();
                 self.draw_score();
                 self.draw_snake();
-                // Draw apple
-                self.screen
-                    .set(self.apple.x as usize, self.apple.y as usize, RED_IDX);
-                self.screen.render(&mut leds);
+                for obstacle in self.config.obstacles.cells() {
+                    self.screen
+                        .set(obstacle.x as usize, obstacle.y as usize, BRICK_IDX);
+                }
+                // Draw apples
+                for apple in &self.apples[..self.apple_count] {
+                    self.screen.set(apple.x as usize, apple.y as usize, RED_IDX);
+                }
+                self.screen.render_over(&mut leds, self.config.background);
                 self.display.write(&leds).await;
             }
             step += speedup;
-            self.timer.sleep_millis(20).await;
+            self.hud_frames = self.hud_frames.wrapping_add(1);
+            self.timer.sleep_millis(FRAME_MS).await;
+            if frames.tick() {
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{
+        BestScore, CaptureDisplay, Runner, ScriptedController, StatusRecorder, VirtualTimer,
+    };
+
+    #[test]
+    fn speedup_is_capped() {
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let config = SnakeConfig {
+            max_speedup: 8,
+            ..SnakeConfig::default()
+        };
+        let mut game =
+            SnakeGame::with_config(Prng::new(1), &mut display, &mut controller, &timer, config);
+
+        let mut last = 0;
+        for score in 0..300 {
+            game.score = score;
+            let speedup = game.speedup(false);
+            assert!(speedup >= last, "speed dropped at score {}", score);
+            assert!(speedup <= 8);
+            last = speedup;
+        }
+        assert_eq!(last, 8);
+
+        // The displayed score wraps, but difficulty keeps the full value
+        game.score = 120;
+        assert_eq!(game.speedup(false), 8);
+        assert!(game.speedup(true) <= 8);
+    }
+
+    #[test]
+    fn difficulty_sets_lives_and_last_life_ends_game() {
+        assert!(Difficulty::Easy.lives() > Difficulty::Hard.lives());
+
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let mut status = StatusRecorder::new();
+        let input = controller.clone();
+        let config = SnakeConfig {
+            difficulty: Difficulty::Easy,
+            ..SnakeConfig::default()
+        };
+        let mut game =
+            SnakeGame::with_config(Prng::new(1), &mut display, &mut controller, &timer, config)
+                .with_status_sink(&mut status);
+        assert_eq!(game.lives, Difficulty::Easy.lives());
+
+        // Coil the snake so every step runs into its own body
+        game.body[..5].copy_from_slice(&[
+            Dot::new(3, 15),
+            Dot::new(3, 16),
+            Dot::new(4, 16),
+            Dot::new(4, 15),
+            Dot::new(4, 14),
+        ]);
+        game.body_len = 5;
+        let mut runner = Runner::new(game.run());
+        while runner.step() {
+            input.press_a();
+        }
+        drop(runner);
+
+        assert_eq!(game.lives, 0);
+        assert_eq!(status.events, [StatusEvent::GameOver]);
+    }
+
+    #[test]
+    fn walls_crash_where_wrapping_goes_around() {
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let mut game = SnakeGame::new(Prng::new(1), &mut display, &mut controller, &timer);
+        // Heading right from the right edge
+        game.body[0] = Dot::new(SCREEN_WIDTH as i8 - 1, 15);
+        assert!(game.move_forward());
+        assert_eq!(game.body[0], Dot::new(0, 15));
+
+        let mut game = SnakeGame::with_walls(Prng::new(1), &mut display, &mut controller, &timer);
+        game.body[0] = Dot::new(SCREEN_WIDTH as i8 - 1, 15);
+        assert!(!game.move_forward());
+
+        // The HUD separator is the top wall, the bottom edge the other one
+        for (head, direction) in [
+            (Dot::new(3, HUD_SEPARATOR_Y as i8 + 1), Dot::new(0, -1)),
+            (Dot::new(3, SCREEN_HEIGHT as i8 - 1), Dot::new(0, 1)),
+            (Dot::new(0, 15), Dot::new(-1, 0)),
+        ] {
+            game.body[0] = head;
+            game.direction = direction;
+            assert!(!game.move_forward(), "{:?}", head);
+            assert_eq!(game.body[0], head);
+        }
+        game.body[0] = Dot::new(3, 20);
+        game.direction = Dot::new(1, 0);
+        assert!(game.move_forward());
+    }
+
+    #[test]
+    fn each_eaten_apple_respawns_on_its_own() {
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        for (apples, count) in [(0, 1), (1, 1), (3, 3), (9, MAX_APPLES)] {
+            let config = SnakeConfig {
+                apples,
+                ..SnakeConfig::default()
+            };
+            let game =
+                SnakeGame::with_config(Prng::new(1), &mut display, &mut controller, &timer, config);
+            assert_eq!(game.apple_count, count);
+        }
+
+        let config = SnakeConfig {
+            apples: 3,
+            ..SnakeConfig::default()
+        };
+        let mut game =
+            SnakeGame::with_config(Prng::new(1), &mut display, &mut controller, &timer, config);
+        // Head down the column, eating one apple per step
+        game.direction = Dot::new(0, 1);
+        for _ in 0..20 {
+            let ahead = game.body[0].move_wrap(game.direction);
+            if !game.apples.contains(&ahead) {
+                game.apples[1] = ahead;
+            }
+            let slot = game
+                .apples
+                .iter()
+                .position(|&apple| apple == ahead)
+                .unwrap();
+            let before = game.apples;
+            assert!(game.move_forward());
+
+            for (i, apple) in game.apples.iter().enumerate() {
+                assert_eq!(*apple == before[i], i != slot, "slot {}", i);
+                assert!(apple.y > HUD_SEPARATOR_Y as i8);
+                assert!(!game.body[..game.body_len].contains(apple));
+                assert_eq!(game.apples.iter().filter(|&a| a == apple).count(), 1);
+            }
+        }
+        assert_eq!(game.score, 20);
+    }
+
+    #[test]
+    fn obstacles_crash_the_snake_even_across_the_wrap() {
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let config = SnakeConfig {
+            obstacles: Obstacles::Corners,
+            ..SnakeConfig::default()
+        };
+        let mut game =
+            SnakeGame::with_config(Prng::new(1), &mut display, &mut controller, &timer, config);
+
+        // Up out of the HUD wraps to the bottom corner block
+        game.body[0] = Dot::new(1, 0);
+        game.direction = Dot::new(0, -1);
+        assert!(!game.move_forward());
+        game.body[0] = Dot::new(2, 0);
+        assert!(game.move_forward());
+        assert_eq!(game.body[0], Dot::new(2, 31));
+
+        for _ in 0..200 {
+            game.respawn_apple(0);
+            assert!(!Obstacles::Corners.cells().contains(&game.apples[0]));
+        }
+
+        let mut runner = Runner::new(game.run());
+        runner.steps(10);
+        drop(runner);
+        for obstacle in Obstacles::Corners.cells() {
+            let (x, y) = (obstacle.x as usize, obstacle.y as usize);
+            assert_eq!(display.last_frame_buffer().get(x, y), BRICK_IDX);
+        }
+    }
+
+    #[test]
+    fn body_grows_to_fill_the_whole_board() {
+        use crate::common::SCREEN_SIZE;
+
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let mut game = SnakeGame::new(Prng::new(1), &mut display, &mut controller, &timer);
+
+        // Zigzag through every row, a cycle over the whole wrapping board
+        let path: [Dot; SCREEN_SIZE] = core::array::from_fn(|k| {
+            let y = k / SCREEN_WIDTH;
+            let x = if y.is_multiple_of(2) {
+                k % SCREEN_WIDTH
+            } else {
+                SCREEN_WIDTH - 1 - k % SCREEN_WIDTH
+            };
+            Dot::new(x as i8, y as i8)
+        });
+        game.body[..3].copy_from_slice(&[path[2], path[1], path[0]]);
+
+        for (k, &next) in path.iter().enumerate().skip(3) {
+            let head = game.body[0];
+            game.direction = Dot::new(next.x - head.x, next.y - head.y);
+            game.apples[0] = next;
+            assert!(game.move_forward(), "move {}", k);
+            assert_eq!(game.body_len, k + 1);
+            // The tail stays put while the snake grows
+            assert_eq!(game.body[game.body_len - 1], path[0]);
+        }
+        assert_eq!(game.body_len, SCREEN_SIZE);
+        assert_eq!(game.apples[0], NO_APPLE);
+
+        // Down from the last row wraps onto the tail, the snake has filled
+        // itself in
+        game.direction = Dot::new(0, 1);
+        assert!(!game.move_forward());
+    }
+
+    #[test]
+    fn reduced_motion_game_over_is_static() {
+        fn game_over_capture(motion: Motion) -> CaptureDisplay {
+            let mut display = CaptureDisplay::new();
+            let mut controller = ScriptedController::new();
+            let timer = VirtualTimer::new();
+            let capture = display.clone();
+            let config = SnakeConfig {
+                motion,
+                ..SnakeConfig::default()
+            };
+            let mut game =
+                SnakeGame::with_config(Prng::new(1), &mut display, &mut controller, &timer, config);
+            let mut runner = Runner::new(game.game_over([RGB8::default(); 256]));
+            runner.steps(20);
+            capture
+        }
+
+        // The score reveal is a single frame at score 0, the flashes follow
+        let blank = FrameBuffer::new();
+        let full = game_over_capture(Motion::Full);
+        assert_eq!(full.frame_count(), 7);
+        for idx in [1, 3, 5] {
+            assert!(full.frame_buffer(idx) == blank, "frame {}", idx);
+        }
+
+        let reduced = game_over_capture(Motion::Reduced);
+        assert_eq!(reduced.frame_count(), 4);
+        let last = reduced.last_frame_buffer();
+        assert!(last != blank);
+        for idx in 1..reduced.frame_count() {
+            assert!(reduced.frame_buffer(idx) == last, "frame {}", idx);
         }
     }
+
+    #[test]
+    fn only_first_turn_between_moves_counts() {
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let mut game = SnakeGame::new(Prng::new(1), &mut display, &mut controller, &timer);
+        assert_eq!(game.direction, Dot::new(1, 0));
+
+        // Up and then back against the old direction within one move
+        game.queue_turn(Dot::new(0, -1));
+        game.queue_turn(Dot::new(-1, 0));
+        assert!(game.move_forward());
+        assert_eq!(game.direction, Dot::new(0, -1));
+
+        // A reversal is rejected right away and doesn't take the slot of a
+        // valid turn
+        game.queue_turn(Dot::new(0, 1));
+        assert_eq!(game.pending_turn, None);
+        game.queue_turn(Dot::new(1, 0));
+        assert!(game.move_forward());
+        assert_eq!(game.direction, Dot::new(1, 0));
+        assert_eq!(game.body[0], Dot::new(4, 14));
+    }
+
+    #[test]
+    fn score_past_100_keeps_speeding_up_and_is_recorded() {
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let mut status = StatusRecorder::new();
+        let mut best = BestScore(0);
+        let input = controller.clone();
+        let config = SnakeConfig {
+            start_score: 95,
+            ..SnakeConfig::default()
+        };
+        let mut game =
+            SnakeGame::with_config(Prng::new(1), &mut display, &mut controller, &timer, config)
+                .with_status_sink(&mut status)
+                .with_score_store(&mut best);
+
+        // Head down the column and feed an apple on every step
+        game.direction = Dot::new(0, 1);
+        let mut last = game.speedup(false);
+        for _ in 0..20 {
+            game.apples[0] = game.body[0].move_wrap(game.direction);
+            assert!(game.move_forward());
+            let speedup = game.speedup(false);
+            assert!(speedup >= last, "speed dropped at score {}", game.score);
+            last = speedup;
+        }
+        assert_eq!(game.score, 115);
+        assert_eq!(last, 12);
+
+        let mut runner = Runner::new(game.game_over([RGB8::default(); 256]));
+        while runner.step() {
+            input.press_a();
+        }
+        drop(runner);
+
+        assert_eq!(status.events.last(), Some(&StatusEvent::Score(115)));
+        assert_eq!(best.0, 115);
+    }
 }
@@ -2,12 +2,17 @@ use core::default::Default;
 use smart_leds::RGB8;
 
 use crate::common::{
-    Dot, FrameBuffer, Game, GameController, LedDisplay, Prng, Timer, BRICK_IDX, COLORS, GREEN_IDX,
-    PINK_IDX, RED_IDX, SCREEN_HEIGHT, SCREEN_WIDTH,
+    blink, hud_swap_frames, Difficulty, Dot, FrameBuffer, FrameLimit, Game, GameController,
+    HudSeparator, LedDisplay, Motion, Prng, Rng, Shake, ShakeConfig, Timer, Trail, BLUE_IDX,
+    BRICK_IDX, COLORS, GREEN_IDX, HUD_HEART_SLOTS, HUD_LIVES_COLOR, HUD_SCORE_COLOR,
+    HUD_SEPARATOR_Y, LIGHT_BLUE_IDX, ORANGE_IDX, PINK_IDX, PURPLE_IDX, RED_IDX, SCREEN_HEIGHT,
+    SCREEN_WIDTH, YELLOW_IDX,
 };
+use crate::games::prompt::sleep_unless_pressed;
+use crate::games::reveal::reveal_score;
 
-use crate::digits::DIGITS;
 use crate::figure::{Figure, TANK};
+use crate::input::{FireButton, Shot};
 
 #[derive(Clone, Copy)]
 struct Missile {
@@ -15,11 +20,19 @@ struct Missile {
     y: i8,
     dx: i8,
     dy: i8,
+    // Enemies the missile may still pass through
+    pierce: u8,
 }
 
 impl Missile {
     fn new(x: i8, y: i8, dx: i8, dy: i8) -> Self {
-        Self { x, y, dx, dy }
+        Self {
+            x,
+            y,
+            dx,
+            dy,
+            pierce: 0,
+        }
     }
 
     fn move_(&mut self) {
@@ -37,9 +50,16 @@ impl Missile {
     }
 }
 
+// Missiles a tank can have in flight, and how many of them it may use
+// without rapid fire
+const MAX_MISSILES: usize = 4;
+const NORMAL_MISSILES: usize = 2;
+
 #[derive(Clone, Copy)]
 struct Tank {
-    missiles: [Missile; 2],
+    missiles: [Missile; MAX_MISSILES],
+    // Leading missile slots `fire` may use
+    missile_slots: usize,
     pos: Dot,
     origin: i8,
     rotation: u8,
@@ -51,7 +71,8 @@ struct Tank {
 impl Tank {
     pub fn new(pos: Dot, origin: i8, lives: i8) -> Self {
         Self {
-            missiles: [Missile::new(-1, -1, 0, 0); 2],
+            missiles: [Missile::new(-1, -1, 0, 0); MAX_MISSILES],
+            missile_slots: NORMAL_MISSILES,
             pos,
             origin,
             rotation: 2,
@@ -89,9 +110,9 @@ impl Tank {
         rotated > 0
     }
 
-    fn fire(&mut self) {
+    fn fire(&mut self, shot: Shot) {
         let direction = self.direction();
-        for m in &mut self.missiles {
+        for m in &mut self.missiles[..self.missile_slots] {
             if !m.visible() {
                 m.x = self.pos.x + 1 + direction.x;
                 m.y = self.pos.y + 1 + direction.y;
@@ -105,6 +126,10 @@ impl Tank {
 
                 m.dx = direction.x;
                 m.dy = direction.y;
+                m.pierce = match shot {
+                    Shot::Normal => 0,
+                    Shot::Charged => 1,
+                };
                 break;
             }
         }
@@ -122,59 +147,192 @@ impl Tank {
     }
 
     fn collides(&self, pos: Dot) -> bool {
-        for row in 0..self.figure.height() {
-            for col in 0..self.figure.width() {
-                if self.figure.get_bit(col, row) {
-                    let tank_pixel_x = self.pos.x + col as i8;
-                    let tank_pixel_y = self.pos.y + row as i8;
-
-                    if tank_pixel_x == pos.x && tank_pixel_y == pos.y {
-                        return true;
-                    }
-                }
-            }
-        }
-        false
+        self.figure
+            .cells()
+            .any(|(col, row)| self.pos.x + col as i8 == pos.x && self.pos.y + row as i8 == pos.y)
     }
 
     fn overlaps_figure(&self, x: i8, y: i8, figure: &Figure) -> bool {
-        for row in 0..self.figure.height() {
-            for col in 0..self.figure.width() {
-                if self.figure.get_bit(col, row) {
-                    let pixel = Dot::new(self.pos.x + col as i8, self.pos.y + row as i8);
-
-                    for other_row in 0..figure.height() {
-                        for other_col in 0..figure.width() {
-                            if figure.get_bit(other_col, other_row) {
-                                let other_pixel =
-                                    Dot::new(x + other_col as i8, y + other_row as i8);
-                                if pixel.x == other_pixel.x && pixel.y == other_pixel.y {
-                                    return true;
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        }
-        false
+        self.figure.cells().any(|(col, row)| {
+            let pixel = Dot::new(self.pos.x + col as i8, self.pos.y + row as i8);
+            figure.cells().any(|(other_col, other_row)| {
+                pixel.x == x + other_col as i8 && pixel.y == y + other_row as i8
+            })
+        })
     }
 
     fn hit(&mut self) {
-        self.lives -= 1;
+        self.lives = self.lives.saturating_sub(1).max(0);
     }
 
     fn is_dead(&self) -> bool {
-        self.lives <= 0
+        self.lives == 0
+    }
+}
+
+// Positions kept for each player missile trail
+const MISSILE_TRAIL_LEN: usize = 3;
+/// Range of enemy positions, their top-left corner, below the score line
+const ENEMY_MIN: Dot = Dot::new(0, 6);
+const ENEMY_MAX: Dot = Dot::new(SCREEN_WIDTH as i8 - 3, SCREEN_HEIGHT as i8 - 3);
+
+/// Powerups the player tank picks up by driving over them
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PowerUp {
+    /// Absorbs the next hit instead of a life
+    Shield,
+    /// Allows `MAX_MISSILES` in flight for a while
+    RapidFire,
+}
+
+/// Colors of the tanks, their missiles and the powerups
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TanksTheme {
+    pub player: u8,
+    pub enemy: u8,
+    /// Its trail is drawn in the `fade`d shades of this color, so it should
+    /// be one that fades to something visible
+    pub player_missile: u8,
+    pub enemy_missile: u8,
+    /// Shield powerup, and the player tank while shielded
+    pub shield: u8,
+    pub rapid_fire: u8,
+    pub hud_separator: HudSeparator,
+}
+
+impl Default for TanksTheme {
+    fn default() -> Self {
+        Self {
+            player: GREEN_IDX,
+            enemy: BRICK_IDX,
+            player_missile: ORANGE_IDX,
+            enemy_missile: RED_IDX,
+            shield: LIGHT_BLUE_IDX,
+            rapid_fire: YELLOW_IDX,
+            hud_separator: HudSeparator::default(),
+        }
+    }
+}
+
+impl TanksTheme {
+    /// Accessibility colors that never rely on telling red from green
+    pub fn high_contrast() -> Self {
+        Self {
+            player: YELLOW_IDX,
+            enemy: BLUE_IDX,
+            player_missile: YELLOW_IDX,
+            enemy_missile: LIGHT_BLUE_IDX,
+            shield: PINK_IDX,
+            rapid_fire: PURPLE_IDX,
+            hud_separator: HudSeparator::default(),
+        }
+    }
+}
+
+/// Tanks timing knobs. All cadences are counted in frames, so difficulty
+/// tuning never changes how often the screen is redrawn.
+#[derive(Clone, Copy, Debug)]
+pub struct TanksConfig {
+    /// Duration of one frame. Input, missiles and rendering run every frame.
+    pub frame_millis: u64,
+    /// The player tank may rotate or step once every this many frames
+    pub player_move_frames: u8,
+    /// Enemies decide (spawn, fire, move) once their step counter reaches
+    /// this value. The counter grows by one per frame plus the score bonus.
+    pub ai_period: u8,
+    /// Every this many points add one more AI step per frame, so enemies
+    /// think faster as the score grows. 0 disables the speedup.
+    pub ai_speedup_score: u8,
+    /// On each AI decision an enemy moves or turns with a 1 in N chance
+    pub enemy_move_chance: u8,
+    /// Enemies on the field at once, at most one per spawn corner (4).
+    /// Three by default, the cap the game always had; four fills every
+    /// corner.
+    pub max_active_enemies: usize,
+    pub difficulty: Difficulty,
+    /// Every point scored counts this many times, e.g. to reward a harder
+    /// difficulty. Can be changed mid-game with `set_score_multiplier`.
+    pub score_multiplier: u8,
+    pub theme: TanksTheme,
+    /// Player missiles leave a short fading trail
+    pub missile_trails: bool,
+    /// Holding fire this many frames charges a missile that passes through
+    /// one enemy, fired on release. `None` fires on press.
+    pub charge_frames: Option<u16>,
+    /// Holding fire shoots every this many frames, unless charging is on.
+    /// `None` fires once per press.
+    pub auto_fire_frames: Option<u16>,
+    /// On each AI decision a powerup appears with a 1 in N chance while
+    /// none is on the field. 0 disables powerups.
+    pub powerup_chance: u8,
+    /// Frames rapid fire lasts once picked up
+    pub rapid_fire_frames: u16,
+    /// Shakes the screen when the player is hit
+    pub shake: ShakeConfig,
+    pub motion: Motion,
+    /// Return from `run` after this many frames, for tests and benchmarks.
+    /// `None` plays until game over.
+    pub max_frames: Option<u64>,
+}
+
+impl Default for TanksConfig {
+    fn default() -> Self {
+        Self {
+            frame_millis: 100,
+            player_move_frames: 1,
+            ai_period: 10,
+            ai_speedup_score: 10,
+            enemy_move_chance: 3,
+            max_active_enemies: 3,
+            difficulty: Difficulty::default(),
+            score_multiplier: 1,
+            theme: TanksTheme::default(),
+            missile_trails: false,
+            charge_frames: None,
+            auto_fire_frames: None,
+            powerup_chance: 20,
+            rapid_fire_frames: 50,
+            shake: ShakeConfig::default(),
+            motion: Motion::Full,
+            max_frames: None,
+        }
     }
+}
 
-    fn is_dying(&self) -> bool {
-        self.lives < 0
+impl TanksConfig {
+    /// AI steps accumulated per frame at the given score
+    fn ai_step(&self, score: u16) -> u8 {
+        let bonus = score.checked_div(self.ai_speedup_score.into()).unwrap_or(0);
+        u8::try_from(bonus).unwrap_or(u8::MAX).saturating_add(1)
+    }
+}
+
+/// Fires once the accumulated steps reach `period`, starting due
+struct Cadence {
+    period: u8,
+    acc: u8,
+}
+
+impl Cadence {
+    fn new(period: u8) -> Self {
+        Self {
+            period,
+            acc: period,
+        }
+    }
+
+    fn tick(&mut self, step: u8) -> bool {
+        let due = self.acc >= self.period;
+        if due {
+            self.acc = 0;
+        }
+        self.acc = self.acc.saturating_add(step);
+        due
     }
 }
 
 // Tanks game implementation
-pub struct TanksGame<'a, D, C, T> {
+pub struct TanksGame<'a, D, C, T, R = Prng> {
     screen: FrameBuffer,
     display: &'a mut D,
     controller: &'a mut C,
@@ -183,26 +341,64 @@ pub struct TanksGame<'a, D, C, T> {
     tank: Tank,
     enemies: [Tank; 4],
     enemy_count: usize,
-    score: u8,
-    prng: Prng,
+    // Trails of the player missiles, by missile slot
+    missile_trails: [Trail<MISSILE_TRAIL_LEN>; MAX_MISSILES],
+    // Powerup waiting on the field
+    powerup: Option<(Dot, PowerUp)>,
+    shield: bool,
+    // Frames of rapid fire left
+    rapid_fire: u16,
+    fire_button: FireButton,
+    shake: Shake,
+    hud_frames: u16,
+    score: u16,
+    prng: R,
+    config: TanksConfig,
 }
 
-impl<'a, D: LedDisplay, C: GameController, T: Timer> TanksGame<'a, D, C, T> {
-    pub fn new(prng: Prng, display: &'a mut D, controller: &'a mut C, timer: &'a T) -> Self {
+impl<'a, D: LedDisplay, C: GameController, T: Timer, R: Rng> TanksGame<'a, D, C, T, R> {
+    pub fn new(prng: R, display: &'a mut D, controller: &'a mut C, timer: &'a T) -> Self {
+        Self::with_config(prng, display, controller, timer, TanksConfig::default())
+    }
+
+    pub fn with_config(
+        prng: R,
+        display: &'a mut D,
+        controller: &'a mut C,
+        timer: &'a T,
+        config: TanksConfig,
+    ) -> Self {
         Self {
             screen: FrameBuffer::new(),
             display,
             controller,
             timer,
 
-            tank: Tank::new(Dot::new(3, 16), -1, 3),
+            tank: Tank::new(Dot::new(3, 16), -1, config.difficulty.lives() as i8),
             enemies: [Tank::new(Dot::new(0, 0), 0, 1); 4],
             enemy_count: 0,
+            missile_trails: [Trail::new(); MAX_MISSILES],
+            powerup: None,
+            shield: false,
+            rapid_fire: 0,
+            fire_button: FireButton::new(config.charge_frames, config.auto_fire_frames),
+            shake: Shake::new(config.motion.shake(config.shake)),
+            hud_frames: 0,
             score: 0,
             prng,
+            config,
         }
     }
 
+    pub fn set_score_multiplier(&mut self, multiplier: u8) {
+        self.config.score_multiplier = multiplier;
+    }
+
+    fn add_score(&mut self, base: u8) {
+        let points = u16::from(base) * u16::from(self.config.score_multiplier);
+        self.score = self.score.saturating_add(points);
+    }
+
     fn collides(
         &self,
         x: i8,
@@ -226,13 +422,50 @@ impl<'a, D: LedDisplay, C: GameController, T: Timer> TanksGame<'a, D, C, T> {
     }
 
     fn ai(&mut self) {
+        self.spawn_powerup();
         self.spawn_enemies();
         self.move_enemies();
         self.remove_dead_enemies();
     }
 
+    fn spawn_powerup(&mut self) {
+        let chance = self.config.powerup_chance;
+        if chance == 0 || self.powerup.is_some() || self.prng.next_range(chance) != 0 {
+            return;
+        }
+        let pos = Dot::new(
+            self.prng.next_range(SCREEN_WIDTH as u8) as i8,
+            self.prng
+                .next_range(SCREEN_HEIGHT as u8)
+                .max(ENEMY_MIN.y as u8) as i8,
+        );
+        let kind = if self.prng.next_bool() {
+            PowerUp::Shield
+        } else {
+            PowerUp::RapidFire
+        };
+        // Skip the turn rather than hide a powerup under a tank
+        let covered = self.tank.collides(pos)
+            || self.enemies[..self.enemy_count]
+                .iter()
+                .any(|e| e.collides(pos));
+        if !covered {
+            self.powerup = Some((pos, kind));
+        }
+    }
+
+    /// Count down rapid fire, once per frame
+    fn tick_powerups(&mut self) {
+        if self.rapid_fire > 0 {
+            self.rapid_fire -= 1;
+            if self.rapid_fire == 0 {
+                self.tank.missile_slots = NORMAL_MISSILES;
+            }
+        }
+    }
+
     fn spawn_enemies(&mut self) {
-        if self.enemy_count >= 3 || self.enemy_count >= self.enemies.len() {
+        if self.enemy_count >= self.config.max_active_enemies.min(self.enemies.len()) {
             return;
         }
 
@@ -295,16 +528,16 @@ impl<'a, D: LedDisplay, C: GameController, T: Timer> TanksGame<'a, D, C, T> {
 
     fn move_enemies(&mut self) {
         for i in 0..self.enemy_count {
-            if self.enemies[i].is_dying() {
+            // Shot down this frame, it goes at the end of the frame
+            if self.enemies[i].is_dead() {
                 continue;
             }
 
             if self.prng.next_range(10) == 0 {
-                self.enemies[i].fire();
+                self.enemies[i].fire(Shot::Normal);
             }
 
-            if self.prng.next_range(3) == 0 {
-                // Increased movement frequency from 20% to 33%
+            if self.prng.next_range(self.config.enemy_move_chance.max(1)) == 0 {
                 let mut enemy = self.enemies[i];
                 if self.prng.next_range(2) == 0 {
                     self.try_move_enemy(&mut enemy, i);
@@ -321,7 +554,7 @@ impl<'a, D: LedDisplay, C: GameController, T: Timer> TanksGame<'a, D, C, T> {
         let new_pos = enemy.pos.move_by(direction);
 
         // Check screen boundaries first
-        if new_pos.x < 0 || new_pos.x > 5 || new_pos.y < 6 || new_pos.y > 29 {
+        if new_pos.clamped(ENEMY_MIN, ENEMY_MAX) != new_pos {
             enemy.rotate(&Dot::new(0, 0));
             return;
         }
@@ -388,26 +621,47 @@ impl<'a, D: LedDisplay, C: GameController, T: Timer> TanksGame<'a, D, C, T> {
     }
 
     fn move_missiles(&mut self) {
+        for (m, trail) in self.tank.missiles.iter().zip(&mut self.missile_trails) {
+            if m.visible() {
+                trail.push(Dot::new(m.x, m.y));
+            } else {
+                trail.clear();
+            }
+        }
         self.tank.move_missiles();
         self.enemies.iter_mut().for_each(|e| e.move_missiles());
     }
 
     fn draw_player(&mut self) {
-        self.screen.draw_figure(
-            self.tank.pos.x,
-            self.tank.pos.y,
-            &self.tank.figure,
-            GREEN_IDX,
-        );
+        let theme = self.config.theme;
+        let color = if self.shield {
+            theme.shield
+        } else {
+            theme.player
+        };
+        self.screen
+            .draw_figure(self.tank.pos.x, self.tank.pos.y, &self.tank.figure, color);
+    }
+
+    fn draw_powerup(&mut self) {
+        if let Some((pos, kind)) = self.powerup {
+            let color = match kind {
+                PowerUp::Shield => self.config.theme.shield,
+                PowerUp::RapidFire => self.config.theme.rapid_fire,
+            };
+            self.screen.set(pos.x as usize, pos.y as usize, color);
+        }
     }
 
     fn draw_enemy(&mut self, idx: usize) {
         let enemy = &self.enemies[idx];
+        let theme = self.config.theme;
         self.screen
-            .draw_figure(enemy.pos.x, enemy.pos.y, &enemy.figure, BRICK_IDX);
+            .draw_figure(enemy.pos.x, enemy.pos.y, &enemy.figure, theme.enemy);
         for m in &enemy.missiles {
             if m.visible() {
-                self.screen.set(m.x as usize, m.y as usize, RED_IDX);
+                self.screen
+                    .set(m.x as usize, m.y as usize, theme.enemy_missile);
             }
         }
     }
@@ -415,29 +669,35 @@ impl<'a, D: LedDisplay, C: GameController, T: Timer> TanksGame<'a, D, C, T> {
     fn draw_player_missiles(&mut self) {
         for m in &self.tank.missiles {
             if m.visible() {
-                self.screen.set(m.x as usize, m.y as usize, RED_IDX);
+                self.screen
+                    .set(m.x as usize, m.y as usize, self.config.theme.player_missile);
+            }
+        }
+        if self.config.missile_trails {
+            for trail in &self.missile_trails {
+                trail.draw(&mut self.screen, self.config.theme.player_missile);
             }
         }
     }
 
     fn draw_score_delimiter(&mut self) {
-        for x in 0..SCREEN_WIDTH {
-            self.screen.set(x, 5, PINK_IDX);
-        }
+        self.screen
+            .draw_hud_separator(HUD_SEPARATOR_Y, self.config.theme.hud_separator);
     }
 
     fn draw_score(&mut self) {
-        let score_display = (self.score % 100) as usize;
-        let tens = score_display / 10;
-        let ones = score_display % 10;
-
-        self.screen.draw_figure(0, 0, &DIGITS[tens], GREEN_IDX);
-        self.screen.draw_figure(4, 0, &DIGITS[ones], GREEN_IDX);
-    }
-
-    fn draw_lives(&mut self) {
-        for i in 0..self.tank.lives {
-            self.screen.set(7, i as usize, PINK_IDX);
+        // The score and the lives take turns
+        self.hud_frames = self.hud_frames.wrapping_add(1);
+        let swap_frames = hud_swap_frames(self.config.frame_millis);
+        if blink(self.hud_frames, swap_frames) {
+            // A long score scrolls from its start every time it shows
+            let frame = self.hud_frames % swap_frames;
+            self.screen
+                .draw_hud_score(self.score, frame, HUD_SCORE_COLOR);
+        } else {
+            let lives = self.tank.lives as u8;
+            self.screen
+                .draw_hud_lives(lives, &HUD_HEART_SLOTS, HUD_LIVES_COLOR);
         }
     }
 
@@ -446,42 +706,80 @@ impl<'a, D: LedDisplay, C: GameController, T: Timer> TanksGame<'a, D, C, T> {
             let enemy = &mut self.enemies[i];
             for m in &mut enemy.missiles {
                 if m.visible() && self.tank.collides(Dot::new(m.x, m.y)) {
-                    self.tank.hit();
+                    if self.shield {
+                        self.shield = false;
+                    } else {
+                        self.tank.hit();
+                        self.shake.start();
+                    }
                     m.hide();
                 }
             }
         }
 
+        if let Some((pos, kind)) = self.powerup {
+            if self.tank.collides(pos) {
+                self.powerup = None;
+                match kind {
+                    PowerUp::Shield => self.shield = true,
+                    PowerUp::RapidFire => {
+                        self.rapid_fire = self.config.rapid_fire_frames;
+                        self.tank.missile_slots = MAX_MISSILES;
+                    }
+                }
+            }
+        }
+
+        let mut kills = 0;
         for m in &mut self.tank.missiles {
             if m.visible() {
                 for j in 0..self.enemy_count {
                     let enemy = &mut self.enemies[j];
-                    if enemy.collides(Dot::new(m.x, m.y)) {
+                    if !enemy.is_dead() && enemy.collides(Dot::new(m.x, m.y)) {
                         enemy.hit();
-                        m.hide();
+                        if m.pierce > 0 {
+                            m.pierce -= 1;
+                        } else {
+                            m.hide();
+                        }
                         if enemy.is_dead() {
-                            self.score += 1;
+                            kills += 1;
                         }
                     }
                 }
             }
         }
+        self.add_score(kills);
     }
 
     async fn game_over(&mut self, mut leds: [RGB8; 256]) {
-        while !self.controller.joystick_was_pressed() {
-            let x = self.prng.next_range(SCREEN_WIDTH as u8);
-            let y = self.prng.next_range(SCREEN_HEIGHT as u8);
-            let color = self.prng.next_range(COLORS.len() as u8);
-            self.screen.set(x as usize, y as usize, color);
+        reveal_score(
+            &mut *self.display,
+            &*self.controller,
+            self.timer,
+            self.score,
+        )
+        .await;
+
+        loop {
+            // With reduced motion the screen stays as it was, without the
+            // sparkles
+            if !self.config.motion.is_reduced() {
+                let x = self.prng.next_range(SCREEN_WIDTH as u8);
+                let y = self.prng.next_range(SCREEN_HEIGHT as u8);
+                let color = self.prng.next_range(COLORS.len() as u8);
+                self.screen.set(x as usize, y as usize, color);
+            }
             self.screen.render(&mut leds);
             self.display.write(&leds).await;
-            self.timer.sleep_millis(200).await;
+            if sleep_unless_pressed(&*self.controller, self.timer, 200).await {
+                break;
+            }
         }
     }
 }
 
-impl<'a, D: LedDisplay, C: GameController, T: Timer> Game for TanksGame<'a, D, C, T> {
+impl<'a, D: LedDisplay, C: GameController, T: Timer, R: Rng> Game for TanksGame<'a, D, C, T, R> {
     async fn run(&mut self)
     where
         D: LedDisplay,
@@ -489,13 +787,13 @@ impl<'a, D: LedDisplay, C: GameController, T: Timer> Game for TanksGame<'a, D, C
         T: Timer,
     {
         let mut leds: [RGB8; 256] = [RGB8::default(); 256];
-        let mut step = 10;
-        let round = 10;
+        let mut ai_cadence = Cadence::new(self.config.ai_period);
+        let mut player_cadence = Cadence::new(self.config.player_move_frames);
 
+        let mut frames = FrameLimit::new(self.config.max_frames);
         loop {
             self.screen.clear();
             self.draw_score();
-            self.draw_lives();
             self.draw_score_delimiter();
 
             if self.tank.is_dead() {
@@ -503,34 +801,311 @@ impl<'a, D: LedDisplay, C: GameController, T: Timer> Game for TanksGame<'a, D, C
                 return;
             }
 
-            if self.controller.joystick_was_pressed() {
-                self.tank.fire();
+            let pressed = self.controller.joystick_was_pressed();
+            let held = self.controller.joystick_held();
+            let shot = self.fire_button.update(pressed, held);
+            self.tick_powerups();
+            if let Some(shot) = shot {
+                self.tank.fire(shot);
             }
 
             let x_input = self.controller.read_x().await;
             let y_input = self.controller.read_y().await;
             let direction = Dot::new(x_input, y_input).to_direction();
 
-            self.move_player(direction);
+            if player_cadence.tick(1) {
+                self.move_player(direction);
+            }
             self.move_missiles();
             self.check_collisions();
 
+            self.draw_powerup();
             self.draw_player();
             for i in 0..self.enemy_count {
                 self.draw_enemy(i);
             }
             self.draw_player_missiles();
 
-            let speedup = self.score / 10;
-            if step >= round {
+            if ai_cadence.tick(self.config.ai_step(self.score)) {
                 self.ai();
-                step = 0;
             }
-            step += 1 + speedup;
 
-            self.screen.render(&mut leds);
+            let offset = self.shake.next_offset(&mut self.prng);
+            self.screen.render_offset(&mut leds, offset);
             self.display.write(&leds).await;
-            self.timer.sleep_millis(100).await;
+            self.timer.sleep_millis(self.config.frame_millis).await;
+            if frames.tick() {
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::{fade, BLACK_IDX};
+    use crate::testing::{CaptureDisplay, Runner, ScriptedController, VirtualTimer};
+
+    fn ai_decisions(config: &TanksConfig, score: u16, frames: usize) -> usize {
+        let mut cadence = Cadence::new(config.ai_period);
+        (0..frames)
+            .filter(|_| cadence.tick(config.ai_step(score)))
+            .count()
+    }
+
+    fn frame_sleeps(score: u16, frames: usize) -> usize {
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+
+        let mut game = TanksGame::new(Prng::new(7), &mut display, &mut controller, &timer);
+        game.score = score;
+        let mut runner = Runner::new(game.run());
+        runner.steps(frames);
+
+        let sleeps = timer.sleeps();
+        assert!(sleeps
+            .iter()
+            .all(|&ms| ms == TanksConfig::default().frame_millis));
+        sleeps.len()
+    }
+
+    #[test]
+    fn difficulty_speeds_up_ai_but_not_rendering() {
+        let config = TanksConfig::default();
+        let easy = ai_decisions(&config, 0, 100);
+        let hard = ai_decisions(&config, 30, 100);
+        assert_eq!(easy, 10);
+        assert!(hard > easy, "easy {} hard {}", easy, hard);
+
+        assert_eq!(frame_sleeps(0, 20), 20);
+        assert_eq!(frame_sleeps(30, 20), 20);
+    }
+
+    #[test]
+    fn score_and_lives_saturate_instead_of_wrapping() {
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let mut game = TanksGame::new(Prng::new(7), &mut display, &mut controller, &timer);
+
+        for _ in 0..300 {
+            game.enemies[0] = Tank::new(Dot::new(0, 8), 0, 1);
+            game.enemy_count = 1;
+            game.tank.missiles[0] = Missile::new(1, 9, 0, 0);
+            game.check_collisions();
+        }
+        assert_eq!(game.score, 300);
+
+        game.score = u16::MAX - 1;
+        game.add_score(5);
+        assert_eq!(game.score, u16::MAX);
+
+        for _ in 0..300 {
+            game.tank.hit();
+        }
+        assert_eq!(game.tank.lives, 0);
+        assert!(game.tank.is_dead());
+    }
+
+    #[test]
+    fn high_contrast_theme_colors_player_and_enemies() {
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let theme = TanksTheme::high_contrast();
+        let config = TanksConfig {
+            theme,
+            ..TanksConfig::default()
+        };
+        assert_ne!(theme.player, theme.enemy);
+
+        let mut game =
+            TanksGame::with_config(Prng::new(7), &mut display, &mut controller, &timer, config);
+        game.enemies[0] = Tank::new(Dot::new(0, 8), 0, 1);
+        game.enemy_count = 1;
+        game.draw_player();
+        game.draw_enemy(0);
+
+        let cells = (0..TANK.height())
+            .flat_map(|row| (0..TANK.width()).map(move |col| (col, row)))
+            .filter(|&(col, row)| TANK.get_bit(col, row))
+            .count();
+        let count = |color: u8| {
+            (0..SCREEN_HEIGHT)
+                .flat_map(|y| (0..SCREEN_WIDTH).map(move |x| (x, y)))
+                .filter(|&(x, y)| game.screen.get(x, y) == color)
+                .count()
+        };
+        assert_eq!(count(theme.player), cells);
+        assert_eq!(count(theme.enemy), cells);
+    }
+
+    #[test]
+    fn missile_trail_fades_behind_missile() {
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let capture = display.clone();
+        let input = controller.clone();
+        let config = TanksConfig {
+            missile_trails: true,
+            ..TanksConfig::default()
+        };
+        // Every theme has a missile color with a visible trail
+        for theme in [TanksTheme::default(), TanksTheme::high_contrast()] {
+            assert_ne!(fade(theme.player_missile), BLACK_IDX);
+        }
+
+        let mut game =
+            TanksGame::with_config(Prng::new(7), &mut display, &mut controller, &timer, config);
+        // Face up and fire, the missile leaves the barrel at (4, 15)
+        game.tank.rotation = 1;
+        input.press_joystick();
+        let mut runner = Runner::new(game.run());
+        while capture.frame_count() < 3 && runner.step() {}
+        drop(runner);
+
+        let screen = capture.frame_buffer(2);
+        let column: [u8; 3] = core::array::from_fn(|dy| screen.get(4, 12 + dy));
+        assert_eq!(column, [ORANGE_IDX, RED_IDX, BLACK_IDX]);
+        let brightness = |idx: u8| {
+            let color = COLORS[idx as usize];
+            color.r as u16 + color.g as u16 + color.b as u16
+        };
+        assert!(column
+            .windows(2)
+            .all(|pair| brightness(pair[0]) > brightness(pair[1])));
+    }
+
+    #[test]
+    fn spawning_stops_at_the_active_enemy_cap() {
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        assert_eq!(TanksConfig::default().max_active_enemies, 3);
+        for (max_active_enemies, expected) in [(1, 1), (3, 3), (4, 4), (9, 4)] {
+            let config = TanksConfig {
+                max_active_enemies,
+                ..TanksConfig::default()
+            };
+            let mut game =
+                TanksGame::with_config(Prng::new(1), &mut display, &mut controller, &timer, config);
+            for _ in 0..20 {
+                game.spawn_enemies();
+            }
+            assert_eq!(game.enemy_count, expected, "cap {}", max_active_enemies);
+
+            // Every active enemy has a corner of its own
+            let enemies = &game.enemies[..game.enemy_count];
+            for (i, enemy) in enemies.iter().enumerate() {
+                assert!(enemies[i + 1..].iter().all(|e| e.origin != enemy.origin));
+            }
+        }
+    }
+
+    #[test]
+    fn powerups_shield_a_hit_and_allow_rapid_fire() {
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let config = TanksConfig {
+            rapid_fire_frames: 3,
+            ..TanksConfig::default()
+        };
+        let mut game =
+            TanksGame::with_config(Prng::new(7), &mut display, &mut controller, &timer, config);
+        let lives = game.tank.lives;
+        // The first pixel of the player tank
+        let on_tank = (0..3)
+            .flat_map(|y| (0..3).map(move |x| Dot::new(x, y)))
+            .map(|d| game.tank.pos.move_by(d))
+            .find(|&pixel| game.tank.collides(pixel))
+            .unwrap();
+
+        game.powerup = Some((on_tank, PowerUp::Shield));
+        game.check_collisions();
+        assert!(game.powerup.is_none() && game.shield);
+
+        // The shield takes the first hit, the second costs a life
+        game.enemies[0] = Tank::new(Dot::new(0, 8), 0, 1);
+        game.enemy_count = 1;
+        for expected in [lives, lives - 1] {
+            game.enemies[0].missiles[0] = Missile::new(on_tank.x, on_tank.y, 0, 0);
+            game.check_collisions();
+            assert_eq!(game.tank.lives, expected);
+        }
+        assert!(!game.shield);
+
+        game.powerup = Some((on_tank, PowerUp::RapidFire));
+        game.check_collisions();
+        for _ in 0..MAX_MISSILES + 1 {
+            game.tank.fire(Shot::Normal);
+        }
+        let in_flight = |game: &TanksGame<'_, _, _, _>| {
+            game.tank.missiles.iter().filter(|m| m.visible()).count()
+        };
+        assert_eq!(in_flight(&game), MAX_MISSILES);
+
+        // Once rapid fire runs out only the normal slots refill
+        game.tank.missiles.iter_mut().for_each(Missile::hide);
+        for _ in 0..3 {
+            game.tick_powerups();
+        }
+        for _ in 0..MAX_MISSILES {
+            game.tank.fire(Shot::Normal);
+        }
+        assert_eq!(in_flight(&game), NORMAL_MISSILES);
+    }
+
+    fn fired_pierce(hold_frames: usize) -> u8 {
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let input = controller.clone();
+        let config = TanksConfig {
+            charge_frames: Some(10),
+            ..TanksConfig::default()
+        };
+        let mut game =
+            TanksGame::with_config(Prng::new(7), &mut display, &mut controller, &timer, config);
+
+        let mut runner = Runner::new(game.run());
+        input.press_joystick();
+        input.set_joystick_held(hold_frames > 0);
+        runner.steps(hold_frames);
+        input.set_joystick_held(false);
+        runner.step();
+        drop(runner);
+
+        let mut fired = game.tank.missiles.iter().filter(|m| m.visible());
+        let missile = fired.next().expect("a missile in flight");
+        assert!(fired.next().is_none());
+        missile.pierce
+    }
+
+    #[test]
+    fn long_hold_fires_piercing_missile_and_tap_a_normal_one() {
+        assert_eq!(fired_pierce(0), 0);
+        assert_eq!(fired_pierce(3), 0);
+        assert_eq!(fired_pierce(12), 1);
+
+        // A charged missile passes through its first enemy and stops at the
+        // second
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let mut game = TanksGame::new(Prng::new(7), &mut display, &mut controller, &timer);
+        game.enemy_count = 1;
+        let mut missile = Missile::new(1, 9, 0, 0);
+        missile.pierce = 1;
+        game.tank.missiles[0] = missile;
+        for visible in [true, false] {
+            game.enemies[0] = Tank::new(Dot::new(0, 8), 0, 1);
+            game.check_collisions();
+            assert_eq!(game.tank.missiles[0].visible(), visible);
         }
+        assert_eq!(game.score, 2);
     }
 }
@@ -1,110 +1,664 @@
 use smart_leds::RGB8;
 
 use crate::common::{
-    Dot, FrameBuffer, Game, GameController, LedDisplay, Prng, Timer, BLACK_IDX, BLUE_IDX,
-    BRICK_IDX, GREEN_IDX, LIGHT_BLUE_IDX, PINK_IDX, RED_IDX, SCREEN_HEIGHT, SCREEN_WIDTH,
-    YELLOW_IDX,
+    blink, hud_swap_frames, Background, Difficulty, Dot, FrameBuffer, FrameLimit, Game,
+    GameController, HudSeparator, LedDisplay, Motion, Prng, Rng, ScoreStore, StatusEvent,
+    StatusSink, Timer, BLACK_IDX, BLUE_IDX, BRICK_IDX, DARK_GREEN_IDX, GREEN_IDX, GREY_IDX,
+    HUD_HEART_SLOTS, HUD_LIVES_COLOR, HUD_SCORE_COLOR, HUD_SEPARATOR_Y, LIGHT_BLUE_IDX, ORANGE_IDX,
+    PINK_IDX, PURPLE_IDX, RED_IDX, SCREEN_HEIGHT, SCREEN_WIDTH, YELLOW_IDX,
 };
 use crate::figure::{Figure, TETRAMINO};
+use crate::games::prompt::{offer_continue, sleep_unless_pressed};
+use crate::games::reveal::reveal_and_record;
 
 use crate::digits::DIGITS;
 
-pub struct TetrisGame<'a, D, C, T> {
+/// Orientation applied to every newly spawned tetramino
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SpawnOrientation {
+    /// Pieces spawn as defined in `TETRAMINO`
+    Base,
+    /// Pieces spawn rotated clockwise the given number of times (0-3)
+    Fixed(u8),
+    /// Each piece spawns in a random orientation
+    Random,
+}
+
+/// How tetraminoes are colored
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PieceColors {
+    /// The original colors of this game
+    Classic,
+    /// Tetris Guideline colors: purple T and orange L
+    Guideline,
+}
+
+/// Which input rotates the falling piece
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RotateControl {
+    /// Joystick button press
+    Button,
+    /// Joystick button or a fresh push up, for hardware with a single button
+    ButtonOrUp,
+}
+
+/// How the next tetramino is picked
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Randomizer {
+    /// Any piece with equal chance every time, droughts can be long
+    Uniform,
+    /// Every piece once per shuffled round of seven, see `Bag7`
+    Bag7,
+}
+
+/// Practice puzzle: a starting board and the pieces to clear it with
+#[derive(Clone, Copy, Debug)]
+pub struct Puzzle {
+    /// Board rows packed as by `FrameBuffer::row_bits`, the last byte is
+    /// the bottom row. Capped like garbage so the spawn area stays free.
+    pub board: &'static [u8],
+    /// Tetramino indices in `TETRAMINO` order, dealt one after another
+    pub pieces: &'static [u8],
+}
+
+/// Why a Tetris game ended
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EndReason {
+    ToppedOut,
+    /// The puzzle board was cleared
+    Solved,
+    /// The puzzle pieces ran out with the board not cleared
+    OutOfPieces,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct TetrisConfig {
+    pub spawn_orientation: SpawnOrientation,
+    /// Top-left corner of the next piece preview, `None` hides it.
+    /// The preview is drawn upright so it fits the 2-pixel gap between
+    /// the score digits. The pieces queued after it show as one dot each
+    /// in their color, left to right in the row below.
+    pub next_preview: Option<Dot>,
+    /// Number of bottom rows pre-filled with garbage, each with a single gap.
+    /// Capped so the spawn area stays free.
+    pub garbage_rows: u8,
+    /// Delayed auto shift: frames a direction has to be held before the
+    /// piece starts repeating its move. The first move happens on press.
+    pub das_frames: u8,
+    /// Auto repeat rate: frames between repeated moves once DAS elapsed
+    pub arr_frames: u8,
+    pub background: Background,
+    pub piece_colors: PieceColors,
+    pub rotate_control: RotateControl,
+    /// Frames a landed piece may still slide before it locks, counting the
+    /// frame it landed on
+    pub lock_delay_frames: u8,
+    /// Soft-dropping onto the stack locks at once, skipping the lock delay
+    pub lock_on_soft_drop_floor: bool,
+    /// Level to start at for practice. Every 10 lines cleared add a level,
+    /// which sets the gravity steps per frame, see `GRAVITY`.
+    pub start_level: u8,
+    /// Score paid to continue from the current board after topping out,
+    /// `None` disables the offer
+    pub continue_cost: Option<u8>,
+    /// Chunky variant: every piece cell is a 2x2 block, so the well is
+    /// 4 cells wide and pieces only ever sit on even columns and rows
+    pub big_mode: bool,
+    /// How long full rows stay on screen before they clear, at gravity 1.
+    /// Divided by the current gravity so clears stay snappy at high levels.
+    /// Counted in whole frames, the frame time stays the same.
+    pub clear_step_ms: u64,
+    /// Sets the lives. Topping out with lives left costs one and clears
+    /// the rows below the spawn point, like a continue.
+    pub difficulty: Difficulty,
+    /// Every point scored counts this many times, e.g. to reward a harder
+    /// difficulty. Can be changed mid-game with `set_score_multiplier`.
+    pub score_multiplier: u8,
+    pub motion: Motion,
+    /// Return from `run` after this many frames, for tests and benchmarks.
+    /// `None` plays until game over.
+    pub max_frames: Option<u64>,
+    /// Start from a puzzle board with a fixed piece sequence instead of
+    /// random pieces. The game ends once the board is clear or the pieces
+    /// run out.
+    pub puzzle: Option<Puzzle>,
+    /// Show where the falling piece will land. Costs a collision scan
+    /// per frame, so slow targets may turn it off.
+    pub ghost_piece: bool,
+    pub hud_separator: HudSeparator,
+    pub randomizer: Randomizer,
+}
+
+const FRAME_MS: u64 = 50;
+const HUD_SWAP_FRAMES: u16 = hud_swap_frames(FRAME_MS);
+/// Top-left corners of the lives hearts beside the next piece preview,
+/// two in each digit slot. More lives show as a heart and the count.
+const PREVIEW_HEART_SLOTS: [(i8, i8); 4] = [(0, 0), (5, 0), (0, 3), (5, 3)];
+/// Pieces known ahead of the falling one
+const NEXT_QUEUE_LEN: usize = 3;
+// Rows from the top of the preview down to the queue dots, below the
+// tallest upright piece
+const QUEUE_DOTS_DY: i8 = 4;
+const INIT_X: i8 = 3;
+const INIT_Y: i8 = 6;
+const GARBAGE_COLOR: u8 = DARK_GREEN_IDX;
+// Dim and apart from every piece and the garbage, so the landing spot
+// doesn't read as part of the stack
+const GHOST_COLOR: u8 = GREY_IDX;
+/// Offsets in cells tried in order when a rotated piece doesn't fit:
+/// in place, one step away from either wall, then one row up off the stack
+const WALL_KICKS: [(i8, i8); 4] = [(0, 0), (-1, 0), (1, 0), (0, -1)];
+/// Points for clearing 0 to 4 lines at once, a tetris pays the most
+const LINE_CLEAR_POINTS: [u8; 5] = [0, 1, 3, 5, 8];
+const LINES_PER_LEVEL: u16 = 10;
+/// Gravity steps per frame by level, the piece drops a row once more than
+/// 10 steps accumulated. Levels past the end keep the last entry, one row
+/// every frame.
+const GRAVITY: [u8; 10] = [1, 2, 3, 4, 5, 6, 7, 8, 9, 11];
+/// A hard drop earns a point for every this many rows fallen, a part of it
+/// counts as a whole one
+const HARD_DROP_ROWS_PER_POINT: u8 = 8;
+const MAX_GARBAGE_ROWS: u8 = SCREEN_HEIGHT as u8 - INIT_Y as u8 - 4;
+// Rows below the spawn point cleared when the player continues
+const CONTINUE_CLEAR_ROWS: usize = 4;
+
+impl Default for TetrisConfig {
+    fn default() -> Self {
+        Self {
+            spawn_orientation: SpawnOrientation::Base,
+            next_preview: Some(Dot::new(3, 0)),
+            garbage_rows: 0,
+            das_frames: 3,
+            arr_frames: 2,
+            background: Background::None,
+            piece_colors: PieceColors::Classic,
+            rotate_control: RotateControl::Button,
+            lock_delay_frames: 0,
+            lock_on_soft_drop_floor: true,
+            start_level: 0,
+            continue_cost: None,
+            big_mode: false,
+            clear_step_ms: 2 * FRAME_MS,
+            difficulty: Difficulty::default(),
+            score_multiplier: 1,
+            motion: Motion::Full,
+            max_frames: None,
+            puzzle: None,
+            ghost_piece: true,
+            hud_separator: HudSeparator::default(),
+            randomizer: Randomizer::Bag7,
+        }
+    }
+}
+
+fn tetramino_color(colors: PieceColors, tetramino_idx: u8) -> u8 {
+    match (tetramino_idx, colors) {
+        (0, _) => LIGHT_BLUE_IDX,                  // I piece
+        (1, _) => YELLOW_IDX,                      // O piece
+        (2, PieceColors::Classic) => PINK_IDX,     // T piece
+        (2, PieceColors::Guideline) => PURPLE_IDX, // T piece
+        (3, _) => GREEN_IDX,                       // S piece
+        (4, _) => RED_IDX,                         // Z piece
+        (5, _) => BLUE_IDX,                        // J piece
+        (6, PieceColors::Classic) => BRICK_IDX,    // L piece
+        (6, PieceColors::Guideline) => ORANGE_IDX, // L piece
+        _ => RED_IDX,                              // Default
+    }
+}
+
+/// Deals the seven tetraminoes in shuffled rounds, each once per round,
+/// so no piece stays away for more than 12 draws
+#[derive(Clone, Copy, Debug)]
+pub struct Bag7 {
+    pieces: [u8; 7],
+    // Position of the next piece to deal, a full round when past the end
+    dealt: usize,
+}
+
+impl Bag7 {
+    pub const fn new() -> Self {
+        Self {
+            pieces: [0, 1, 2, 3, 4, 5, 6],
+            dealt: 7,
+        }
+    }
+
+    /// Next tetramino index, shuffling a new round when the bag is empty
+    pub fn next(&mut self, rng: &mut impl Rng) -> u8 {
+        if self.dealt == self.pieces.len() {
+            for i in 0..self.pieces.len() - 1 {
+                let j = i + rng.next_range((self.pieces.len() - i) as u8) as usize;
+                self.pieces.swap(i, j);
+            }
+            self.dealt = 0;
+        }
+        self.dealt += 1;
+        self.pieces[self.dealt - 1]
+    }
+}
+
+impl Default for Bag7 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Horizontal auto shift state, updated once per frame with the joystick x
+#[derive(Default)]
+struct AutoShift {
+    direction: i8,
+    held_frames: u8,
+}
+
+impl AutoShift {
+    fn update(&mut self, input: i8, das_frames: u8, arr_frames: u8) -> i8 {
+        if input == 0 {
+            *self = Self::default();
+            return 0;
+        }
+        if input != self.direction {
+            self.direction = input;
+            self.held_frames = 0;
+            return input;
+        }
+
+        self.held_frames = self.held_frames.saturating_add(1);
+        let repeating = self.held_frames >= das_frames
+            && (self.held_frames - das_frames).is_multiple_of(arr_frames.max(1));
+        if repeating {
+            input
+        } else {
+            0
+        }
+    }
+}
+
+pub struct TetrisGame<'a, D, C, T, R = Prng> {
     screen: FrameBuffer,
     concrete: FrameBuffer,
     display: &'a mut D,
     controller: &'a mut C,
     timer: &'a T,
-    score: u8,
-    prng: Prng,
+    score: u16,
+    // Lines cleared this game, every `LINES_PER_LEVEL` raise the level
+    lines: u16,
+    lives: u8,
+    hud_frames: u16,
+    // Ring buffer of the upcoming pieces, the next one at `queue_head`
+    next_queue: [u8; NEXT_QUEUE_LEN],
+    queue_head: usize,
+    // Pieces taken from the puzzle sequence so far, including the queued
+    dealt: usize,
+    bag: Bag7,
+    end_reason: Option<EndReason>,
+    prng: R,
+    config: TetrisConfig,
+    status: Option<&'a mut dyn StatusSink>,
+    scores: Option<&'a mut dyn ScoreStore>,
+    // Pixels per piece cell side, 2 in big mode
+    scale: i8,
 }
 
-impl<'a, D: LedDisplay, C: GameController, T: Timer> TetrisGame<'a, D, C, T> {
-    pub fn new(prng: Prng, display: &'a mut D, controller: &'a mut C, timer: &'a T) -> Self {
-        Self {
+impl<'a, D: LedDisplay, C: GameController, T: Timer, R: Rng> TetrisGame<'a, D, C, T, R> {
+    pub fn new(prng: R, display: &'a mut D, controller: &'a mut C, timer: &'a T) -> Self {
+        Self::with_config(prng, display, controller, timer, TetrisConfig::default())
+    }
+
+    pub fn with_config(
+        prng: R,
+        display: &'a mut D,
+        controller: &'a mut C,
+        timer: &'a T,
+        config: TetrisConfig,
+    ) -> Self {
+        let mut game = Self {
             screen: FrameBuffer::new(),
             concrete: FrameBuffer::new(),
             display,
             controller,
             timer,
             score: 0,
+            lines: 0,
+            lives: config.difficulty.lives(),
+            hud_frames: 0,
+            next_queue: [0; NEXT_QUEUE_LEN],
+            queue_head: 0,
+            dealt: 0,
+            bag: Bag7::new(),
+            end_reason: None,
             prng,
+            scale: if config.big_mode { 2 } else { 1 },
+            config,
+            status: None,
+            scores: None,
+        };
+        let queue = core::array::from_fn(|_| game.next_piece());
+        game.next_queue = queue;
+        game
+    }
+
+    /// Report score changes and game over to `sink`
+    pub fn with_status_sink(mut self, sink: &'a mut dyn StatusSink) -> Self {
+        self.status = Some(sink);
+        self
+    }
+
+    /// Record a new high score in `store` at game over
+    pub fn with_score_store(mut self, store: &'a mut dyn ScoreStore) -> Self {
+        self.scores = Some(store);
+        self
+    }
+
+    /// Why the last run ended, `None` while playing or after `max_frames`
+    pub fn end_reason(&self) -> Option<EndReason> {
+        self.end_reason
+    }
+
+    pub fn set_score_multiplier(&mut self, multiplier: u8) {
+        self.config.score_multiplier = multiplier;
+    }
+
+    fn add_score(&mut self, base: u8) {
+        let points = u16::from(base).saturating_mul(u16::from(self.config.score_multiplier));
+        self.score = self.score.saturating_add(points);
+    }
+
+    fn notify(&mut self, event: StatusEvent) {
+        if let Some(status) = self.status.as_mut() {
+            status.notify(event);
         }
     }
 
-    fn get_tetramino_color(&self, tetramino_idx: u8) -> u8 {
-        match tetramino_idx {
-            0 => LIGHT_BLUE_IDX, // I piece
-            1 => YELLOW_IDX,     // O piece
-            2 => PINK_IDX,       // T piece
-            3 => GREEN_IDX,      // S piece
-            4 => RED_IDX,        // Z piece
-            5 => BLUE_IDX,       // J piece
-            6 => BRICK_IDX,      // L piece
-            _ => RED_IDX,        // Default
+    /// Next tetramino, from the puzzle sequence when there is one
+    fn next_piece(&mut self) -> u8 {
+        let Some(puzzle) = self.config.puzzle else {
+            return match self.config.randomizer {
+                Randomizer::Uniform => self.prng.next_range(7),
+                Randomizer::Bag7 => self.bag.next(&mut self.prng),
+            };
+        };
+        let idx = puzzle.pieces.get(self.dealt).copied().unwrap_or_default();
+        self.dealt += 1;
+        idx
+    }
+
+    /// Take the next piece from the queue and queue a new one at its end
+    fn take_queued(&mut self) -> u8 {
+        let idx = self.next_queue[self.queue_head];
+        self.next_queue[self.queue_head] = self.next_piece();
+        self.queue_head = (self.queue_head + 1) % NEXT_QUEUE_LEN;
+        idx
+    }
+
+    /// Queued piece `n` places after the falling one, 0 is the next
+    fn queued(&self, n: usize) -> u8 {
+        self.next_queue[(self.queue_head + n) % NEXT_QUEUE_LEN]
+    }
+
+    /// The preview of queued piece `n` shows a piece that will actually
+    /// be played
+    fn has_queued(&self, n: usize) -> bool {
+        self.config
+            .puzzle
+            .is_none_or(|puzzle| self.dealt - NEXT_QUEUE_LEN + n < puzzle.pieces.len())
+    }
+
+    /// Every puzzle piece is already locked
+    fn out_of_pieces(&self) -> bool {
+        self.config
+            .puzzle
+            .is_some_and(|puzzle| self.dealt > puzzle.pieces.len() + NEXT_QUEUE_LEN)
+    }
+
+    fn load_puzzle_board(&mut self) {
+        let Some(puzzle) = self.config.puzzle else {
+            return;
+        };
+        let rows = (0..SCREEN_HEIGHT).rev().take(MAX_GARBAGE_ROWS as usize);
+        for (row, &bits) in rows.zip(puzzle.board.iter().rev()) {
+            self.concrete.set_row_bits(row, bits, GARBAGE_COLOR);
+        }
+    }
+
+    /// Outcome of the puzzle once line clears settled
+    fn puzzle_result(&self) -> Option<EndReason> {
+        self.config.puzzle?;
+        if self.has_full_row() {
+            return None;
+        }
+        if (INIT_Y as usize..SCREEN_HEIGHT).all(|row| self.concrete.row_is_empty(row)) {
+            Some(EndReason::Solved)
+        } else if self.out_of_pieces() {
+            Some(EndReason::OutOfPieces)
+        } else {
+            None
+        }
+    }
+
+    fn spawn_figure(&mut self, tetramino_idx: u8) -> Figure {
+        let rotations = match self.config.spawn_orientation {
+            SpawnOrientation::Base => 0,
+            SpawnOrientation::Fixed(rotations) => rotations % 4,
+            SpawnOrientation::Random => self.prng.next_range(4),
+        };
+        let mut figure = TETRAMINO.wrapping_at(tetramino_idx);
+        for _ in 0..rotations {
+            figure = figure.rotate();
+        }
+        figure
+    }
+
+    fn fill_garbage(&mut self) {
+        let scale = self.scale as usize;
+        let rows = (self.config.garbage_rows as usize * scale).min(MAX_GARBAGE_ROWS as usize);
+        for row in ((SCREEN_HEIGHT - rows)..SCREEN_HEIGHT).step_by(scale) {
+            let gap = self.prng.next_range((SCREEN_WIDTH / scale) as u8) as usize * scale;
+            for y in row..row + scale {
+                for x in 0..SCREEN_WIDTH {
+                    if !(gap..gap + scale).contains(&x) {
+                        self.concrete.set(x, y, GARBAGE_COLOR);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Spawn column, centered on the cell grid in big mode
+    fn spawn_x(&self, figure: &Figure) -> i8 {
+        if self.scale == 1 {
+            return INIT_X;
+        }
+        let cells = SCREEN_WIDTH as i8 / self.scale;
+        (cells - figure.width() as i8) / 2 * self.scale
+    }
+
+    /// Spawn row after the first piece, kept on even rows in big mode
+    fn spawn_y(&self) -> i8 {
+        if self.scale == 1 {
+            INIT_Y + 1
+        } else {
+            INIT_Y
         }
     }
 
+    fn collides(&self, x: i8, y: i8, figure: &Figure) -> bool {
+        self.concrete.collides_scaled(x, y, figure, self.scale)
+    }
+
+    /// Rotate the figure at `x`, `y` and try it at each of `WALL_KICKS` in
+    /// turn. Returns the rotated figure with the first position that fits,
+    /// `None` if the piece can't turn.
+    fn rotate_with_kicks(&self, x: i8, y: i8, figure: &Figure) -> Option<(Figure, Dot)> {
+        let scale = self.scale;
+        let rotated = figure.rotate();
+        // A piece turning upright in the last column keeps its right edge
+        let shift = if rotated.height() > rotated.width()
+            && x + rotated.width() as i8 * scale >= SCREEN_WIDTH as i8
+        {
+            (rotated.height() - rotated.width()) as i8 * scale
+        } else {
+            0
+        };
+        // A piece turning wider against the right wall is pulled back in
+        let x = (x - shift).min(SCREEN_WIDTH as i8 - rotated.width() as i8 * scale);
+
+        WALL_KICKS
+            .iter()
+            .map(|&(dx, dy)| Dot::new(x + dx * scale, y + dy * scale))
+            .find(|pos| !self.collides(pos.x, pos.y, &rotated))
+            .map(|pos| (rotated, pos))
+    }
+
+    /// Lowest row the figure can fall to from `y` without colliding
+    fn landing_y(&self, x: i8, y: i8, figure: &Figure) -> i8 {
+        let mut y = y;
+        while !self.collides(x, y + self.scale, figure) {
+            y += self.scale;
+        }
+        y
+    }
+
+    /// Outline the landing spot of the falling piece on the screen only
+    fn draw_ghost(&mut self, x: i8, y: i8, figure: &Figure) {
+        if !self.config.ghost_piece {
+            return;
+        }
+        let landing = self.landing_y(x, y, figure);
+        self.screen
+            .draw_figure_scaled(x, landing, figure, GHOST_COLOR, self.scale);
+    }
+
+    fn get_tetramino_color(&self, tetramino_idx: u8) -> u8 {
+        tetramino_color(self.config.piece_colors, tetramino_idx)
+    }
+
+    fn level(&self) -> u8 {
+        let gained = u8::try_from(self.lines / LINES_PER_LEVEL).unwrap_or(u8::MAX);
+        self.config.start_level.saturating_add(gained)
+    }
+
+    fn gravity(&self) -> u8 {
+        GRAVITY[(self.level() as usize).min(GRAVITY.len() - 1)]
+    }
+
+    fn clear_step_millis(&self) -> u64 {
+        self.config.clear_step_ms / self.gravity() as u64
+    }
+
     fn draw_score(&mut self) {
         self.score %= 100;
-        let speed = self.score / 10;
-        let score_digit = self.score % 10;
+        self.hud_frames = self.hud_frames.wrapping_add(1);
+        if !blink(self.hud_frames, HUD_SWAP_FRAMES) {
+            // Hearts take the digit slots, the preview keeps its gap
+            let slots: &[(i8, i8)] = match self.config.next_preview {
+                Some(_) => &PREVIEW_HEART_SLOTS,
+                None => &HUD_HEART_SLOTS,
+            };
+            self.screen
+                .draw_hud_lives(self.lives, slots, HUD_LIVES_COLOR);
+        } else {
+            let level_fig = DIGITS.wrapping_at(self.level() % 10);
+            let score_fig = DIGITS.wrapping_at((self.score % 10) as u8);
 
-        let speed_fig = DIGITS.wrapping_at(speed);
-        let score_fig = DIGITS.wrapping_at(score_digit);
+            self.screen.draw_figure(0, 0, level_fig, HUD_SCORE_COLOR);
+            self.screen.draw_figure(5, 0, score_fig, HUD_SCORE_COLOR);
+        }
 
-        self.screen.draw_figure(0, 0, speed_fig, GREEN_IDX);
-        self.screen.draw_figure(5, 0, score_fig, GREEN_IDX);
+        self.screen
+            .draw_hud_separator(HUD_SEPARATOR_Y, self.config.hud_separator);
+    }
 
-        // Draw horizontal line
-        for x in 0..SCREEN_WIDTH {
-            self.screen.set(x, 5, PINK_IDX);
+    /// Take a life, true if any are left to keep playing
+    fn lose_life(&mut self) -> bool {
+        self.lives = self.lives.saturating_sub(1);
+        self.lives > 0
+    }
+
+    /// Draw the next piece upright at the preview position and a dot for
+    /// each piece queued after it
+    fn draw_next_queue(&mut self, next: &Figure) {
+        let Some(pos) = self.config.next_preview else {
+            return;
+        };
+        if self.has_queued(0) {
+            let upright = if next.width() > next.height() {
+                next.rotate()
+            } else {
+                *next
+            };
+            let color = self.get_tetramino_color(self.queued(0));
+            self.screen.draw_figure(pos.x, pos.y, &upright, color);
+        }
+        for n in 1..NEXT_QUEUE_LEN {
+            let dot = Dot::new(pos.x + n as i8 - 1, pos.y + QUEUE_DOTS_DY);
+            if self.has_queued(n) && dot.in_bounds() {
+                let color = self.get_tetramino_color(self.queued(n));
+                self.screen.set(dot.x as usize, dot.y as usize, color);
+            }
         }
     }
 
-    fn reduce_concrete(&mut self) -> Option<usize> {
-        for row in (6..SCREEN_HEIGHT).rev() {
+    fn has_full_row(&self) -> bool {
+        (6..SCREEN_HEIGHT).any(|row| self.concrete.row_is_full(row))
+    }
+
+    /// Clear every full row, returning how many were cleared
+    fn reduce_concrete(&mut self) -> usize {
+        let mut cleared = 0;
+        for row in 6..SCREEN_HEIGHT {
             if self.concrete.row_is_full(row) {
                 self.concrete
                     .clear_range(row * SCREEN_WIDTH, (row + 1) * SCREEN_WIDTH);
-                return Some(row);
+                cleared += 1;
             }
         }
-        None
+        cleared
     }
 
-    fn shift_concrete(&mut self, cleared_row: usize) {
-        let mut to_idx = cleared_row;
-        let mut from_idx = cleared_row - 1;
-
-        while from_idx > 5 {
-            if self.concrete.row_is_empty(from_idx) {
-                if from_idx == 0 {
-                    break;
-                }
-                from_idx -= 1;
+    /// Move the rows left above cleared ones down, closing every gap
+    fn shift_concrete(&mut self) {
+        let mut to_row = SCREEN_HEIGHT;
+        for from_row in (6..SCREEN_HEIGHT).rev() {
+            if self.concrete.row_is_empty(from_row) {
                 continue;
             }
-
-            // Copy row
-            for x in 0..SCREEN_WIDTH {
-                let color = self.concrete.get(x, from_idx);
-                self.concrete.set(x, to_idx, color);
+            to_row -= 1;
+            if to_row != from_row {
+                for x in 0..SCREEN_WIDTH {
+                    let color = self.concrete.get(x, from_row);
+                    self.concrete.set(x, to_row, color);
+                }
             }
+        }
 
-            if from_idx == 0 {
-                break;
-            }
-            from_idx -= 1;
-            if to_idx == 0 {
-                break;
-            }
-            to_idx -= 1;
+        // Clear the rows the stack moved away from
+        for row in 6..to_row {
+            self.concrete
+                .clear_range(row * SCREEN_WIDTH, (row + 1) * SCREEN_WIDTH);
         }
+    }
 
-        // Clear the row that was just shifted
-        for x in 0..SCREEN_WIDTH {
-            self.concrete.set(x, to_idx, BLACK_IDX);
+    /// Offer to continue when enabled and affordable, paying for it on accept
+    async fn try_continue(&mut self) -> bool {
+        let Some(cost) = self.config.continue_cost else {
+            return false;
+        };
+        if self.score < u16::from(cost)
+            || !offer_continue(&mut *self.display, &*self.controller, self.timer).await
+        {
+            return false;
+        }
+        self.score -= u16::from(cost);
+        true
+    }
+
+    /// Clear `CONTINUE_CLEAR_ROWS` rows from the spawn row `y` down, so the
+    /// game can resume from the rest of the board
+    fn clear_spawn_rows(&mut self, y: i8) {
+        let top = y as usize;
+        for row in top..(top + CONTINUE_CLEAR_ROWS).min(SCREEN_HEIGHT) {
+            self.concrete
+                .clear_range(row * SCREEN_WIDTH, (row + 1) * SCREEN_WIDTH);
         }
     }
 
@@ -115,130 +669,1137 @@ impl<'a, D: LedDisplay, C: GameController, T: Timer> TetrisGame<'a, D, C, T> {
         last_figure: &Figure,
         last_color: u8,
     ) {
-        while !self.controller.joystick_was_pressed() {
+        reveal_and_record(
+            &mut *self.display,
+            &*self.controller,
+            self.timer,
+            self.score,
+            self.scores.as_deref_mut(),
+        )
+        .await;
+
+        loop {
             // Preserve the concrete blocks and score
             self.screen.copy_from(&self.concrete);
             self.draw_score();
 
             // Blink the last tetramino
-            self.screen
-                .draw_figure(last_pos.x, last_pos.y - 1, last_figure, last_color);
-            self.screen.render(&mut leds);
+            self.screen.draw_figure_scaled(
+                last_pos.x,
+                last_pos.y - self.scale,
+                last_figure,
+                last_color,
+                self.scale,
+            );
+            self.screen.render_over(&mut leds, self.config.background);
             self.display.write(&leds).await;
-            self.timer.sleep_millis(500).await;
+            if sleep_unless_pressed(&*self.controller, self.timer, 500).await {
+                break;
+            }
 
-            // Clear only the last tetramino
-            self.screen
-                .draw_figure(last_pos.x, last_pos.y - 1, last_figure, BLACK_IDX);
-            self.screen.render(&mut leds);
+            // Clear only the last tetramino, it stays on with reduced motion
+            if self.config.motion.is_reduced() {
+                continue;
+            }
+            self.screen.draw_figure_scaled(
+                last_pos.x,
+                last_pos.y - self.scale,
+                last_figure,
+                BLACK_IDX,
+                self.scale,
+            );
+            self.screen.render_over(&mut leds, self.config.background);
             self.display.write(&leds).await;
-            self.timer.sleep_millis(500).await;
+            if sleep_unless_pressed(&*self.controller, self.timer, 500).await {
+                break;
+            }
         }
     }
 }
 
-impl<'a, D: LedDisplay, C: GameController, T: Timer> Game for TetrisGame<'a, D, C, T> {
+impl<'a, D: LedDisplay, C: GameController, T: Timer, R: Rng> Game for TetrisGame<'a, D, C, T, R> {
     async fn run(&mut self) {
-        const INIT_X: i8 = 3;
-        const INIT_Y: i8 = 6;
-        const RESPAWN_THRESHOLD: i8 = 11;
-
-        let mut x = INIT_X;
+        let scale = self.scale;
         let mut y = INIT_Y;
         let mut ipass: i8 = 0;
-        let mut mpass: u8 = 0;
+        // Time the full rows have been shown, the clear waits on its own
+        // clock so the frame time never changes
+        let mut clear_wait_ms: u64 = 0;
+        let mut shift = AutoShift::default();
+        let mut last_y: i8 = 0;
+        // Frames the falling piece has been resting on the stack
+        let mut lock_frames: u8 = 0;
 
-        let mut curr_idx = self.prng.next_range(7);
-        let mut next_idx = self.prng.next_range(7);
-        let mut curr = TETRAMINO.wrapping_at(curr_idx);
-        let mut next = TETRAMINO.wrapping_at(next_idx);
+        let mut curr_idx = self.take_queued();
+        let mut curr = self.spawn_figure(curr_idx);
+        let mut next = self.spawn_figure(self.queued(0));
+        let mut x = self.spawn_x(&curr);
         let mut leds: [RGB8; 256] = [RGB8::default(); 256];
+        self.fill_garbage();
+        self.load_puzzle_board();
 
+        let mut frames = FrameLimit::new(self.config.max_frames);
         loop {
             if ipass > 10 {
                 ipass = 0;
-                y += 1;
+                y += scale;
             }
 
             // Read joystick
-            let x_diff = self.controller.read_x().await;
-            let mut new_x = x + x_diff;
-            if mpass % 2 == 0 {
-                new_x = x;
-            }
-            mpass = mpass.wrapping_add(1);
+            let x_input = self.controller.read_x().await;
+            let new_x =
+                x + shift.update(x_input, self.config.das_frames, self.config.arr_frames) * scale;
 
-            if new_x >= 0 && new_x < SCREEN_WIDTH as i8 && !self.concrete.collides(new_x, y, &curr)
-            {
+            if new_x >= 0 && new_x < SCREEN_WIDTH as i8 && !self.collides(new_x, y, &curr) {
                 x = new_x;
             }
 
-            if self.controller.joystick_was_pressed() {
-                let rotated = curr.rotate();
-                let shift = if rotated.height() > rotated.width()
-                    && x + rotated.width() as i8 >= SCREEN_WIDTH as i8
-                {
-                    (rotated.height() - rotated.width()) as i8
-                } else {
-                    0
-                };
+            // Only a push up from center rotates, holding up doesn't spin
+            let y_input = self.controller.read_y().await;
+            let up_pushed = y_input < 0 && last_y >= 0;
+            last_y = y_input;
 
-                if !self.concrete.collides(x - shift, y, &rotated) {
+            let up_rotates = self.config.rotate_control == RotateControl::ButtonOrUp && up_pushed;
+            if self.controller.joystick_was_pressed() | up_rotates {
+                if let Some((rotated, pos)) = self.rotate_with_kicks(x, y, &curr) {
                     curr = rotated;
-                    x -= shift;
+                    x = pos.x;
+                    y = pos.y;
                 }
             }
 
+            // A drops the piece onto the stack and locks it in this frame
+            let hard_drop = self.controller.a_was_pressed() && !self.out_of_pieces();
+            if hard_drop && !self.collides(x, y, &curr) {
+                let landing = self.landing_y(x, y, &curr);
+                let rows = ((landing - y) / scale) as u8;
+                if rows > 0 {
+                    self.add_score(rows.div_ceil(HARD_DROP_ROWS_PER_POINT));
+                    self.notify(StatusEvent::Score(self.score));
+                }
+                y = landing + scale;
+            }
+
             self.screen.copy_from(&self.concrete);
             self.draw_score();
 
             let curr_color = self.get_tetramino_color(curr_idx);
-            let next_color = self.get_tetramino_color(next_idx);
+            self.draw_next_queue(&next);
 
-            if y > RESPAWN_THRESHOLD {
-                self.screen.draw_figure(INIT_X, INIT_Y, &next, next_color);
-            }
-
-            if !self.concrete.collides(x, y, &curr) {
-                self.screen.draw_figure(x, y, &curr, curr_color);
+            let soft_drop_lock = self.config.lock_on_soft_drop_floor && y_input > 0;
+            if self.out_of_pieces() {
+                // Nothing left to drop, wait for the last rows to clear
+            } else if !self.collides(x, y, &curr) {
+                self.draw_ghost(x, y, &curr);
+                self.screen
+                    .draw_figure_scaled(x, y, &curr, curr_color, scale);
+            } else if !hard_drop && !soft_drop_lock && lock_frames < self.config.lock_delay_frames {
+                // Rest on the stack until the lock delay runs out
+                y -= scale;
+                self.screen
+                    .draw_figure_scaled(x, y, &curr, curr_color, scale);
             } else {
-                self.screen.draw_figure(x, y - 1, &curr, curr_color);
-                self.concrete.draw_figure(x, y - 1, &curr, curr_color);
+                self.screen
+                    .draw_figure_scaled(x, y - scale, &curr, curr_color, scale);
+                self.concrete
+                    .draw_figure_scaled(x, y - scale, &curr, curr_color, scale);
 
-                x = INIT_X;
-                y = INIT_Y + 1;
+                curr_idx = self.take_queued();
+                curr = next;
+                next = self.spawn_figure(self.queued(0));
 
-                if self.concrete.collides(x, y, &curr) {
-                    self.game_over(leds, Dot::new(x, y), &curr, curr_color)
-                        .await;
+                x = self.spawn_x(&curr);
+                y = self.spawn_y();
+
+                // Past the last puzzle piece nothing spawns that could top out
+                let topped_out = |game: &Self| !game.out_of_pieces() && game.collides(x, y, &curr);
+                if topped_out(self) && (self.lose_life() || self.try_continue().await) {
+                    self.clear_spawn_rows(y);
+                }
+                if topped_out(self) {
+                    let color = self.get_tetramino_color(curr_idx);
+                    self.end_reason = Some(EndReason::ToppedOut);
+                    self.notify(StatusEvent::GameOver);
+                    self.game_over(leds, Dot::new(x, y), &curr, color).await;
                     return;
                 }
-
-                curr_idx = next_idx;
-                next_idx = self.prng.next_range(7);
-                curr = TETRAMINO.wrapping_at(curr_idx);
-                next = TETRAMINO.wrapping_at(next_idx);
             }
-            if mpass % 2 == 0 {
-                if let Some(row) = self.reduce_concrete() {
-                    self.score += 1;
-                    self.shift_concrete(row);
+            if self.collides(x, y + scale, &curr) {
+                lock_frames = lock_frames.saturating_add(1);
+            } else {
+                lock_frames = 0;
+            }
+
+            // Full rows are cleared together once shown for the clear step,
+            // right away after a hard drop
+            if hard_drop || clear_wait_ms >= self.clear_step_millis() {
+                clear_wait_ms = 0;
+                let cleared = self.reduce_concrete();
+                if cleared > 0 {
+                    // Lines count in piece cells, so big mode pays the same
+                    let lines = cleared.div_ceil(scale as usize);
+                    let level = self.level();
+                    self.lines = self.lines.saturating_add(lines as u16);
+                    self.add_score(LINE_CLEAR_POINTS[lines.min(LINE_CLEAR_POINTS.len() - 1)]);
+                    self.shift_concrete();
+                    self.notify(StatusEvent::Score(self.score));
+                    if self.level() > level {
+                        self.notify(StatusEvent::LevelUp(self.level()));
+                    }
                 }
+            } else if self.has_full_row() {
+                clear_wait_ms += FRAME_MS;
             }
 
-            self.screen.render(&mut leds);
+            self.screen.render_over(&mut leds, self.config.background);
             self.display.write(&leds).await;
 
+            if let Some(reason) = self.puzzle_result() {
+                self.end_reason = Some(reason);
+                self.notify(StatusEvent::GameOver);
+                return;
+            }
+
             if self.score > 99 {
                 self.score = 0;
             }
 
-            let speed_bonus = (self.score / 2 / 10).max(1) as i8;
-            let y_input = self.controller.read_y().await;
+            let speed_bonus = self.gravity() as i8;
             let down_bonus: i8 = if y_input > 0 { 10 } else { 0 };
 
             ipass += speed_bonus + down_bonus;
-            self.timer.sleep_millis(50).await;
+            self.timer.sleep_millis(FRAME_MS).await;
+            if frames.tick() {
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{
+        CaptureDisplay, Runner, ScriptedController, StatusRecorder, VirtualTimer,
+    };
+
+    const SEED: u32 = 42;
+
+    fn run_frames(config: TetrisConfig, frames: usize) -> CaptureDisplay {
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let capture = display.clone();
+
+        let mut game = TetrisGame::with_config(
+            Prng::new(SEED),
+            &mut display,
+            &mut controller,
+            &timer,
+            config,
+        );
+        let mut runner = Runner::new(game.run());
+        runner.steps(frames);
+        capture
+    }
+
+    #[test]
+    fn fixed_spawn_orientation_rotates_first_piece() {
+        let config = TetrisConfig {
+            spawn_orientation: SpawnOrientation::Fixed(1),
+            next_preview: None,
+            ..TetrisConfig::default()
+        };
+        let frames = run_frames(config, 1);
+
+        let expected = TETRAMINO
+            .wrapping_at(Prng::new(SEED).next_range(7))
+            .rotate();
+        let screen = frames.frame_buffer(0);
+        for row in 0..4 {
+            for col in 0..(SCREEN_WIDTH as u8 - 3) {
+                let lit = screen.get(3 + col as usize, 6 + row as usize) != BLACK_IDX;
+                assert_eq!(
+                    lit,
+                    expected.get_bit(col, row),
+                    "mismatch at col {} row {}\nEXPECTED:\n{}",
+                    col,
+                    row,
+                    expected.str(),
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn next_queue_is_drawn_every_frame() {
+        let mut prng = Prng::new(SEED);
+        let mut bag = Bag7::new();
+        bag.next(&mut prng);
+        let queue: [u8; NEXT_QUEUE_LEN] = core::array::from_fn(|_| bag.next(&mut prng));
+        let next = TETRAMINO.wrapping_at(queue[0]);
+        let upright = if next.width() > next.height() {
+            next.rotate()
+        } else {
+            next
+        };
+
+        // Long enough for the active piece to fall well below the spawn point
+        let frames = run_frames(TetrisConfig::default(), 60);
+        for idx in 0..frames.frame_count() {
+            let screen = frames.frame_buffer(idx);
+            for row in 0..QUEUE_DOTS_DY as u8 {
+                for col in 0..2 {
+                    let lit = screen.get(3 + col as usize, row as usize) != BLACK_IDX;
+                    assert_eq!(lit, upright.get_bit(col, row), "frame {}", idx);
+                }
+            }
+            // The pieces after the next one as dots below it
+            for (n, &piece) in queue.iter().enumerate().skip(1) {
+                let color = tetramino_color(PieceColors::Classic, piece);
+                assert_eq!(screen.get(2 + n, 4), color, "frame {}", idx);
+            }
+        }
+    }
+
+    #[test]
+    fn next_queue_advances_through_puzzle_pieces() {
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let config = TetrisConfig {
+            puzzle: Some(Puzzle {
+                board: &[],
+                pieces: &[0, 1, 2, 3, 4],
+            }),
+            ..TetrisConfig::default()
+        };
+        let mut game =
+            TetrisGame::with_config(ZeroRng, &mut display, &mut controller, &timer, config);
+        let queue = |game: &TetrisGame<_, _, _, _>| {
+            core::array::from_fn::<_, NEXT_QUEUE_LEN, _>(|n| {
+                game.has_queued(n).then(|| game.queued(n))
+            })
+        };
+
+        assert_eq!(queue(&game), [Some(0), Some(1), Some(2)]);
+        assert_eq!(game.take_queued(), 0);
+        assert_eq!(queue(&game), [Some(1), Some(2), Some(3)]);
+        assert_eq!(game.take_queued(), 1);
+        assert_eq!(game.take_queued(), 2);
+        assert_eq!(queue(&game), [Some(3), Some(4), None]);
+        assert_eq!(game.take_queued(), 3);
+        assert_eq!(game.take_queued(), 4);
+        assert_eq!(queue(&game), [None; NEXT_QUEUE_LEN]);
+        assert!(!game.out_of_pieces());
+        game.take_queued();
+        assert!(game.out_of_pieces());
+    }
+
+    #[test]
+    fn hidden_next_preview_leaves_hud_gap_empty() {
+        let config = TetrisConfig {
+            next_preview: None,
+            ..TetrisConfig::default()
+        };
+        let frames = run_frames(config, 5);
+        let screen = frames.last_frame_buffer();
+        for row in 0..5 {
+            for col in 3..5 {
+                assert_eq!(screen.get(col, row), BLACK_IDX);
+            }
+        }
+    }
+
+    #[test]
+    fn garbage_rows_prefill_bottom_with_one_gap_each() {
+        let config = TetrisConfig {
+            garbage_rows: 4,
+            ghost_piece: false,
+            ..TetrisConfig::default()
+        };
+        let screen = run_frames(config, 1).frame_buffer(0);
+
+        // The falling piece spawns at row 6 and is at most 4 rows tall
+        let mut garbage = 0;
+        for row in (INIT_Y as usize + 4)..SCREEN_HEIGHT {
+            let lit = (0..SCREEN_WIDTH)
+                .filter(|&x| screen.get(x, row) != BLACK_IDX)
+                .count();
+            if lit > 0 {
+                assert!(row >= SCREEN_HEIGHT - 4, "unexpected blocks in row {}", row);
+                assert_eq!(lit, SCREEN_WIDTH - 1, "row {}", row);
+                garbage += 1;
+            }
+        }
+        assert_eq!(garbage, 4);
+    }
+
+    /// Leftmost column of the falling piece, below the HUD
+    fn piece_left(screen: &FrameBuffer) -> usize {
+        (0..SCREEN_WIDTH)
+            .find(|&x| (6..SCREEN_HEIGHT).any(|y| screen.get(x, y) != BLACK_IDX))
+            .expect("no piece on screen")
+    }
+
+    #[test]
+    fn single_press_moves_piece_exactly_one_cell() {
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let capture = display.clone();
+        let input = controller.clone();
+
+        let mut game = TetrisGame::new(Prng::new(SEED), &mut display, &mut controller, &timer);
+        let mut runner = Runner::new(game.run());
+
+        runner.step();
+        let start = piece_left(&capture.last_frame_buffer());
+
+        input.set_x(-1);
+        runner.step();
+        assert_eq!(piece_left(&capture.last_frame_buffer()), start - 1);
+
+        input.set_x(0);
+        runner.steps(5);
+        assert_eq!(piece_left(&capture.last_frame_buffer()), start - 1);
+    }
+
+    #[test]
+    fn held_direction_repeats_at_configured_rate() {
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let capture = display.clone();
+        controller.set_x(-1);
+
+        let config = TetrisConfig {
+            das_frames: 4,
+            arr_frames: 3,
+            ..TetrisConfig::default()
+        };
+        let mut game = TetrisGame::with_config(
+            Prng::new(SEED),
+            &mut display,
+            &mut controller,
+            &timer,
+            config,
+        );
+        let mut runner = Runner::new(game.run());
+        runner.steps(8);
+
+        // Moves on press, after DAS (frame 4) and then every ARR frames
+        let columns: [usize; 8] =
+            core::array::from_fn(|idx| piece_left(&capture.frame_buffer(idx)));
+        assert_eq!(columns, [2, 2, 2, 2, 1, 1, 1, 0]);
+    }
+
+    #[test]
+    fn background_shows_through_empty_cells() {
+        let config = TetrisConfig {
+            background: Background::Border(DARK_GREEN_IDX),
+            ..TetrisConfig::default()
+        };
+        let screen = run_frames(config, 1).frame_buffer(0);
+
+        // Bottom row of the empty well, where the ghost doesn't cover it,
+        // and its side walls
+        for x in 0..SCREEN_WIDTH {
+            let cell = screen.get(x, SCREEN_HEIGHT - 1);
+            assert!(cell == DARK_GREEN_IDX || cell == GHOST_COLOR, "x {}", x);
+        }
+        assert_eq!(screen.get(0, SCREEN_HEIGHT - 1), DARK_GREEN_IDX);
+        assert_eq!(screen.get(0, 20), DARK_GREEN_IDX);
+        assert_eq!(screen.get(4, 20), BLACK_IDX);
+        // Drawn content still wins over the backdrop
+        assert_eq!(screen.get(SCREEN_WIDTH - 1, 5), PINK_IDX);
+    }
+
+    /// Rng that always picks the first option
+    struct ZeroRng;
+
+    impl Rng for ZeroRng {
+        fn next_range(&mut self, _max: u8) -> u8 {
+            0
+        }
+
+        fn next_range_u16(&mut self, _max: u16) -> u16 {
+            0
+        }
+    }
+
+    #[test]
+    fn bag_deals_every_piece_once_per_round() {
+        let mut prng = Prng::new(SEED);
+        let mut bag = Bag7::new();
+        let mut counts = [0; 7];
+        for _ in 0..14 {
+            counts[bag.next(&mut prng) as usize] += 1;
+        }
+        assert_eq!(counts, [2; 7]);
+    }
+
+    #[test]
+    fn injected_rng_forces_i_piece() {
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let capture = display.clone();
+
+        let mut game = TetrisGame::new(ZeroRng, &mut display, &mut controller, &timer);
+        let mut runner = Runner::new(game.run());
+        runner.step();
+
+        let piece = TETRAMINO.wrapping_at(0);
+        let screen = capture.frame_buffer(0);
+        for row in 0..piece.height() {
+            for col in 0..piece.width() {
+                let cell = screen.get(
+                    INIT_X as usize + col as usize,
+                    INIT_Y as usize + row as usize,
+                );
+                let expected = if piece.get_bit(col, row) {
+                    LIGHT_BLUE_IDX
+                } else {
+                    BLACK_IDX
+                };
+                assert_eq!(cell, expected, "col {} row {}", col, row);
+            }
+        }
+    }
+
+    #[test]
+    fn guideline_colors_per_piece() {
+        // I, O, T, S, Z, J, L
+        let expected = [
+            LIGHT_BLUE_IDX,
+            YELLOW_IDX,
+            PURPLE_IDX,
+            GREEN_IDX,
+            RED_IDX,
+            BLUE_IDX,
+            ORANGE_IDX,
+        ];
+        for (idx, &color) in expected.iter().enumerate() {
+            assert_eq!(tetramino_color(PieceColors::Guideline, idx as u8), color);
+        }
+        assert_eq!(tetramino_color(PieceColors::Classic, 2), PINK_IDX);
+        assert_eq!(tetramino_color(PieceColors::Classic, 6), BRICK_IDX);
+    }
+
+    /// Columns and rows spanned by the falling piece, below the HUD
+    fn piece_bounds(screen: &FrameBuffer) -> (usize, usize, usize, usize) {
+        let lit = |x: usize, y: usize| screen.get(x, y) != BLACK_IDX;
+        let cols = (0..SCREEN_WIDTH).filter(|&x| (6..SCREEN_HEIGHT).any(|y| lit(x, y)));
+        let rows = (6..SCREEN_HEIGHT).filter(|&y| (0..SCREEN_WIDTH).any(|x| lit(x, y)));
+        let (left, right) = (cols.clone().min().unwrap(), cols.max().unwrap());
+        let (top, bottom) = (rows.clone().min().unwrap(), rows.max().unwrap());
+        (left, right - left + 1, top, bottom - top + 1)
+    }
+
+    #[test]
+    fn up_rotates_and_sideways_still_moves() {
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let capture = display.clone();
+        let input = controller.clone();
+
+        let config = TetrisConfig {
+            rotate_control: RotateControl::ButtonOrUp,
+            ghost_piece: false,
+            ..TetrisConfig::default()
+        };
+        let mut game =
+            TetrisGame::with_config(ZeroRng, &mut display, &mut controller, &timer, config);
+        let mut runner = Runner::new(game.run());
+
+        // The I piece spawns flat
+        runner.step();
+        let (_, width, _, height) = piece_bounds(&capture.last_frame_buffer());
+        assert_eq!((width, height), (4, 1));
+
+        input.set_y(-1);
+        runner.step();
+        let (_, width, _, height) = piece_bounds(&capture.last_frame_buffer());
+        assert_eq!((width, height), (1, 4));
+
+        // Holding up doesn't keep rotating
+        runner.step();
+        let (rotated_left, width, _, _) = piece_bounds(&capture.last_frame_buffer());
+        assert_eq!(width, 1);
+
+        input.set_y(0);
+        input.set_x(-1);
+        runner.step();
+        let (moved_left, width, _, _) = piece_bounds(&capture.last_frame_buffer());
+        assert_eq!(width, 1);
+        assert_eq!(moved_left, rotated_left - 1);
+    }
+
+    /// Frames the first I piece spends on the floor before the next one spawns
+    fn frames_on_floor(config: TetrisConfig) -> usize {
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let capture = display.clone();
+        controller.set_y(1);
+
+        let mut game =
+            TetrisGame::with_config(ZeroRng, &mut display, &mut controller, &timer, config);
+        let mut runner = Runner::new(game.run());
+        runner.steps(60);
+
+        (0..capture.frame_count())
+            .map(|idx| capture.frame_buffer(idx))
+            .filter(|screen| {
+                let on_floor = !screen.row_is_empty(SCREEN_HEIGHT - 1);
+                let spawned = (6..SCREEN_HEIGHT - 1).any(|row| !screen.row_is_empty(row));
+                on_floor && !spawned
+            })
+            .count()
+    }
+
+    #[test]
+    fn soft_drop_lock_is_instant_or_delayed() {
+        let instant = TetrisConfig {
+            lock_delay_frames: 5,
+            lock_on_soft_drop_floor: true,
+            next_preview: None,
+            ..TetrisConfig::default()
+        };
+        let delayed = TetrisConfig {
+            lock_on_soft_drop_floor: false,
+            ..instant
+        };
+        let instant_frames = frames_on_floor(instant);
+        assert_eq!(instant_frames, frames_on_floor(TetrisConfig::default()));
+        // The landing frame already counts towards the delay
+        assert_eq!(frames_on_floor(delayed), instant_frames + 5 - 1);
+    }
+
+    #[test]
+    fn start_level_applies_gravity_from_first_frame() {
+        let rows_fallen = |start_level: u8| {
+            let config = TetrisConfig {
+                start_level,
+                next_preview: None,
+                ..TetrisConfig::default()
+            };
+            let frames = run_frames(config, 12);
+            let (_, _, first_top, _) = piece_bounds(&frames.frame_buffer(0));
+            let (_, _, last_top, _) = piece_bounds(&frames.last_frame_buffer());
+            last_top - first_top
+        };
+
+        // Level 0 drops a row every 11 frames, level 5 every 2 frames
+        assert_eq!(rows_fallen(0), 1);
+        assert_eq!(rows_fallen(5), 5);
+    }
+
+    #[test]
+    fn level_rises_every_ten_lines_and_shows_in_hud() {
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let mut game = TetrisGame::new(ZeroRng, &mut display, &mut controller, &timer);
+
+        // (lines, level, gravity)
+        let table = [(0, 0, 1), (9, 0, 1), (10, 1, 2), (35, 3, 4), (95, 9, 11)];
+        for (lines, level, gravity) in table {
+            game.lines = lines;
+            assert_eq!(
+                (game.level(), game.gravity()),
+                (level, gravity),
+                "{}",
+                lines
+            );
+        }
+
+        // Far past the table the gravity stays at a row per frame
+        game.config.start_level = 250;
+        game.lines = u16::MAX;
+        assert_eq!((game.level(), game.gravity()), (u8::MAX, 11));
+
+        game.config.start_level = 0;
+        game.lines = 30;
+        game.screen.clear();
+        game.draw_score();
+        let mut expected = FrameBuffer::new();
+        expected.draw_figure(0, 0, DIGITS.wrapping_at(3), GREEN_IDX);
+        for y in 0..HUD_SEPARATOR_Y {
+            for x in 0..3 {
+                assert_eq!(game.screen.get(x, y), expected.get(x, y), "{} {}", x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn hud_shows_every_life_on_easy() {
+        let hud = |next_preview: Option<Dot>| {
+            let mut display = CaptureDisplay::new();
+            let mut controller = ScriptedController::new();
+            let timer = VirtualTimer::new();
+            let config = TetrisConfig {
+                difficulty: Difficulty::Easy,
+                next_preview,
+                ..TetrisConfig::default()
+            };
+            let mut game =
+                TetrisGame::with_config(ZeroRng, &mut display, &mut controller, &timer, config);
+            // The next frame shows the lives
+            game.hud_frames = HUD_SWAP_FRAMES;
+            game.draw_score();
+            game.screen
+        };
+
+        let mut hearts = FrameBuffer::new();
+        hearts.draw_hud_lives(5, &HUD_HEART_SLOTS, HUD_LIVES_COLOR);
+        hearts.draw_hud_separator(HUD_SEPARATOR_Y, TetrisConfig::default().hud_separator);
+        assert!(hud(None) == hearts);
+
+        // Five don't fit beside the preview, a heart and the count show
+        let screen = hud(TetrisConfig::default().next_preview);
+        assert_eq!(screen.get(0, 0), HUD_LIVES_COLOR);
+        let count = (0..5).flat_map(|y| (5..8).map(move |x| (x, y)));
+        assert!(count.clone().all(|(x, y)| {
+            let lit = screen.get(x, y) == HUD_LIVES_COLOR;
+            lit == DIGITS[5].get_bit((x - 5) as u8, y as u8)
+        }));
+    }
+
+    #[test]
+    fn line_clear_is_faster_at_higher_level() {
+        let clear_time = |start_level: u8| {
+            let mut display = CaptureDisplay::new();
+            let mut controller = ScriptedController::new();
+            let timer = VirtualTimer::new();
+            let capture = display.clone();
+            let config = TetrisConfig {
+                start_level,
+                next_preview: None,
+                ..TetrisConfig::default()
+            };
+
+            let mut game =
+                TetrisGame::with_config(ZeroRng, &mut display, &mut controller, &timer, config);
+            for y in SCREEN_HEIGHT - 4..SCREEN_HEIGHT {
+                for x in 0..SCREEN_WIDTH {
+                    game.concrete.set(x, y, GARBAGE_COLOR);
+                }
+            }
+            // The four rows are cleared at once, after a wait
+            let mut runner = Runner::new(game.run());
+            let mut cleared_at = None;
+            while capture.frame_count() < 8 && runner.step() {
+                if cleared_at.is_none()
+                    && capture.last_frame_buffer().row_is_empty(SCREEN_HEIGHT - 4)
+                {
+                    cleared_at = Some(capture.frame_count());
+                }
+            }
+            drop(runner);
+
+            assert_eq!(game.score, u16::from(LINE_CLEAR_POINTS[4]));
+            assert!(!game.has_full_row());
+            // Every frame takes the same time while the rows are shown
+            assert!(timer.sleeps().iter().all(|&ms| ms == FRAME_MS));
+            cleared_at.expect("rows cleared")
+        };
+
+        assert!(clear_time(5) < clear_time(0));
+    }
+
+    #[test]
+    fn four_rows_clear_at_once_for_a_tetris_bonus() {
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let capture = display.clone();
+        let mut game = TetrisGame::new(ZeroRng, &mut display, &mut controller, &timer);
+
+        // Four full rows interleaved with partial ones, a gap left in each
+        let bottom = SCREEN_HEIGHT - 1;
+        for row in [bottom, bottom - 2, bottom - 3, bottom - 5] {
+            for x in 0..SCREEN_WIDTH {
+                game.concrete.set(x, row, GARBAGE_COLOR);
+            }
+        }
+        game.concrete.set(1, bottom - 1, BLUE_IDX);
+        game.concrete.set(2, bottom - 4, RED_IDX);
+        game.concrete.set(3, bottom - 6, YELLOW_IDX);
+
+        let mut runner = Runner::new(game.run());
+        while capture.frame_count() < 3 && runner.step() {}
+        drop(runner);
+        assert_eq!(game.score, 8);
+
+        // The partial rows keep their order, stacked on the floor
+        assert_eq!(game.concrete.get(1, bottom), BLUE_IDX);
+        assert_eq!(game.concrete.get(2, bottom - 1), RED_IDX);
+        assert_eq!(game.concrete.get(3, bottom - 2), YELLOW_IDX);
+        for row in 6..bottom - 2 {
+            assert!(game.concrete.row_is_empty(row), "row {}", row);
+        }
+        for row in bottom - 2..=bottom {
+            assert_eq!(game.concrete.row_bits(row).count_ones(), 1, "row {}", row);
+        }
+    }
+
+    #[test]
+    fn score_multiplier_doubles_line_clear_points() {
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let capture = display.clone();
+        let config = TetrisConfig {
+            score_multiplier: 2,
+            next_preview: None,
+            ..TetrisConfig::default()
+        };
+
+        let mut game =
+            TetrisGame::with_config(ZeroRng, &mut display, &mut controller, &timer, config);
+        for x in 0..SCREEN_WIDTH {
+            game.concrete.set(x, SCREEN_HEIGHT - 1, GARBAGE_COLOR);
+        }
+        let mut runner = Runner::new(game.run());
+        while capture.frame_count() < 3 && runner.step() {}
+        drop(runner);
+
+        assert!(!game.has_full_row());
+        assert_eq!(game.score, 2);
+    }
+
+    #[test]
+    fn large_score_multiplier_saturates() {
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let config = TetrisConfig {
+            score_multiplier: u8::MAX,
+            ..TetrisConfig::default()
+        };
+        let mut game =
+            TetrisGame::with_config(ZeroRng, &mut display, &mut controller, &timer, config);
+
+        // A tetris scores 8 times the multiplier, past what a u8 holds
+        game.add_score(8);
+        assert_eq!(game.score, 8 * 255);
+        for _ in 0..100 {
+            game.add_score(u8::MAX);
+        }
+        assert_eq!(game.score, u16::MAX);
+    }
+
+    fn play_puzzle(pieces: &'static [u8]) -> Option<EndReason> {
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let input = controller.clone();
+        // The bottom row misses exactly the cells of an I at the spawn column
+        let config = TetrisConfig {
+            puzzle: Some(Puzzle {
+                board: &[0b1110_0001],
+                pieces,
+            }),
+            ..TetrisConfig::default()
+        };
+
+        let mut game =
+            TetrisGame::with_config(ZeroRng, &mut display, &mut controller, &timer, config);
+        input.set_y(1);
+        let mut runner = Runner::new(game.run());
+        runner.steps(1000);
+        assert!(runner.is_finished());
+        drop(runner);
+
+        game.end_reason()
+    }
+
+    #[test]
+    fn puzzle_reports_solved_or_out_of_pieces() {
+        assert_eq!(play_puzzle(&[0]), Some(EndReason::Solved));
+        assert_eq!(play_puzzle(&[1]), Some(EndReason::OutOfPieces));
+    }
+
+    #[test]
+    fn ghost_marks_landing_spot_without_touching_concrete() {
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let capture = display.clone();
+        let config = TetrisConfig {
+            next_preview: None,
+            ..TetrisConfig::default()
+        };
+
+        let mut game =
+            TetrisGame::with_config(ZeroRng, &mut display, &mut controller, &timer, config);
+        for x in 0..SCREEN_WIDTH {
+            game.concrete.set(x, SCREEN_HEIGHT - 1, GARBAGE_COLOR);
+        }
+        game.concrete.set(0, SCREEN_HEIGHT - 1, BLACK_IDX);
+        let mut runner = Runner::new(game.run());
+        runner.step();
+        drop(runner);
+
+        assert_ne!(GHOST_COLOR, GARBAGE_COLOR);
+        for colors in [PieceColors::Classic, PieceColors::Guideline] {
+            assert!((0..7).all(|idx| tetramino_color(colors, idx) != GHOST_COLOR));
+        }
+        // The I piece spawns flat at INIT_X and lands on the garbage row
+        let screen = capture.frame_buffer(0);
+        let landing = SCREEN_HEIGHT - 2;
+        for x in 0..SCREEN_WIDTH {
+            let ghost = (INIT_X as usize..INIT_X as usize + 4).contains(&x);
+            let expected = if ghost { GHOST_COLOR } else { BLACK_IDX };
+            assert_eq!(screen.get(x, landing), expected, "x {}", x);
+        }
+        assert_eq!(screen.get(INIT_X as usize, INIT_Y as usize), LIGHT_BLUE_IDX);
+        assert!(game.concrete.row_is_empty(landing));
+    }
+
+    #[test]
+    fn rotation_kicks_off_walls_and_stack_in_order() {
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let mut game = TetrisGame::new(ZeroRng, &mut display, &mut controller, &timer);
+        let flat = Figure::from_ascii("####");
+        let upright = Figure::from_ascii("#\n#\n#\n#");
+        let kicked = |game: &TetrisGame<_, _, _, _>, x, y, figure| {
+            game.rotate_with_kicks(x, y, figure).map(|(_, pos)| pos)
+        };
+
+        // Flush against the right wall the flat piece is pulled back in
+        assert_eq!(kicked(&game, 7, 10, &upright), Some(Dot::new(4, 10)));
+
+        // Blocked in place, the first free side wins: left before right
+        game.concrete.set(3, 12, GARBAGE_COLOR);
+        assert_eq!(kicked(&game, 3, 10, &flat), Some(Dot::new(2, 10)));
+        game.concrete.set(2, 12, GARBAGE_COLOR);
+        assert_eq!(kicked(&game, 3, 10, &flat), Some(Dot::new(4, 10)));
+        // The left wall counts as blocked too
+        game.concrete.set(0, 12, GARBAGE_COLOR);
+        assert_eq!(kicked(&game, 0, 10, &flat), Some(Dot::new(1, 10)));
+
+        // Resting on a full row it kicks one row up
+        for x in 0..SCREEN_WIDTH {
+            game.concrete.set(x, 23, GARBAGE_COLOR);
+        }
+        assert_eq!(kicked(&game, 5, 20, &flat), Some(Dot::new(5, 19)));
+        // Two rows too short and it doesn't turn
+        assert_eq!(kicked(&game, 5, 21, &flat), None);
+    }
+
+    #[test]
+    fn short_hard_drop_still_scores() {
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let input = controller.clone();
+        let config = TetrisConfig {
+            next_preview: None,
+            randomizer: Randomizer::Uniform,
+            ..TetrisConfig::default()
+        };
+
+        let mut game =
+            TetrisGame::with_config(ZeroRng, &mut display, &mut controller, &timer, config);
+        // Only a couple of rows to fall
+        let roof = INIT_Y as usize + 3;
+        for x in INIT_X as usize..SCREEN_WIDTH {
+            game.concrete.set(x, roof, GARBAGE_COLOR);
+        }
+        input.press_a();
+        let mut runner = Runner::new(game.run());
+        runner.steps(2);
+        drop(runner);
+
+        assert_eq!(game.concrete.get(INIT_X as usize, roof - 1), LIGHT_BLUE_IDX);
+        assert_eq!(game.score, 1);
+    }
+
+    #[test]
+    fn hard_drop_locks_on_overhang_and_spawns_next() {
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let capture = display.clone();
+        let input = controller.clone();
+        let config = TetrisConfig {
+            next_preview: None,
+            ghost_piece: false,
+            randomizer: Randomizer::Uniform,
+            ..TetrisConfig::default()
+        };
+
+        let mut game =
+            TetrisGame::with_config(ZeroRng, &mut display, &mut controller, &timer, config);
+        // A roof with open space below it
+        let roof = 20;
+        for x in INIT_X as usize..SCREEN_WIDTH {
+            game.concrete.set(x, roof, GARBAGE_COLOR);
+        }
+        input.press_a();
+        let mut runner = Runner::new(game.run());
+        runner.steps(2);
+        drop(runner);
+
+        // The flat I piece rests on the roof, nothing fell through
+        for x in 0..SCREEN_WIDTH {
+            let piece = (INIT_X as usize..INIT_X as usize + 4).contains(&x);
+            let expected = if piece { LIGHT_BLUE_IDX } else { BLACK_IDX };
+            assert_eq!(game.concrete.get(x, roof - 1), expected, "x {}", x);
+        }
+        assert!((roof + 1..SCREEN_HEIGHT).all(|row| game.concrete.row_is_empty(row)));
+        // 13 rows fallen, the part past the first 8 earns a point too
+        assert_eq!(roof - 1 - INIT_Y as usize, 13);
+        assert_eq!(game.score, 2);
+
+        // The next piece appears at the spawn point on the following frame
+        let screen = capture.frame_buffer(1);
+        let spawn_y = INIT_Y as usize + 1;
+        for x in INIT_X as usize..INIT_X as usize + 4 {
+            assert_eq!(screen.get(x, spawn_y), LIGHT_BLUE_IDX, "x {}", x);
+        }
+    }
+
+    #[test]
+    fn max_frames_returns_after_exactly_that_many_frames() {
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let capture = display.clone();
+        let config = TetrisConfig {
+            max_frames: Some(100),
+            ..TetrisConfig::default()
+        };
+
+        let mut game =
+            TetrisGame::with_config(ZeroRng, &mut display, &mut controller, &timer, config);
+        let mut runner = Runner::new(game.run());
+        runner.steps(1000);
+        assert!(runner.is_finished());
+        drop(runner);
+
+        assert_eq!(capture.frame_count(), 100);
+        assert_eq!(timer.sleeps().len(), 100);
+    }
+
+    #[test]
+    fn status_sink_receives_score_level_and_game_over() {
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let mut status = StatusRecorder::new();
+        let input = controller.clone();
+        let config = TetrisConfig {
+            garbage_rows: 4,
+            next_preview: None,
+            ..TetrisConfig::default()
+        };
+
+        let mut game =
+            TetrisGame::with_config(ZeroRng, &mut display, &mut controller, &timer, config)
+                .with_status_sink(&mut status);
+        // Any line clear reaches the next level
+        game.lines = LINES_PER_LEVEL - 1;
+        // Drop vertical I pieces into the left column until the stack tops out
+        input.press_joystick();
+        input.set_x(-1);
+        input.set_y(1);
+        let mut runner = Runner::new(game.run());
+        while runner.step() {
+            input.press_b();
+        }
+        drop(runner);
+
+        assert_eq!(
+            &status.events[..2],
+            &[StatusEvent::Score(8), StatusEvent::LevelUp(1)]
+        );
+        assert_eq!(status.events.last(), Some(&StatusEvent::GameOver));
+    }
+
+    #[test]
+    fn continue_keeps_board_and_pays_cost() {
+        use crate::games::prompt::{countdown_frame, CONTINUE_SECONDS};
+
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let capture = display.clone();
+        let input = controller.clone();
+        let config = TetrisConfig {
+            continue_cost: Some(2),
+            ..TetrisConfig::default()
+        };
+
+        let mut game =
+            TetrisGame::with_config(ZeroRng, &mut display, &mut controller, &timer, config);
+        game.score = 5;
+        // Stack I pieces on the left until the spawn row is blocked
+        input.set_x(-1);
+        input.set_y(1);
+        let mut runner = Runner::new(game.run());
+        for _ in 0..2000 {
+            runner.step();
+            if capture.last_frame_buffer() == countdown_frame(CONTINUE_SECONDS) {
+                break;
+            }
+        }
+        assert!(capture.last_frame_buffer() == countdown_frame(CONTINUE_SECONDS));
+        let board = capture.frame_buffer(capture.frame_count() - 2);
+
+        input.set_x(0);
+        input.set_y(0);
+        input.press_b();
+        runner.steps(5);
+        assert!(!runner.is_finished());
+        drop(runner);
+
+        assert_eq!(game.score, 3);
+        let spawn_row = (INIT_Y + 1) as usize;
+        for y in spawn_row..spawn_row + CONTINUE_CLEAR_ROWS {
+            assert!(game.concrete.row_is_empty(y), "row {}", y);
+        }
+        for y in spawn_row + CONTINUE_CLEAR_ROWS..SCREEN_HEIGHT {
+            for x in 0..SCREEN_WIDTH {
+                assert_eq!(game.concrete.get(x, y), board.get(x, y), "({}, {})", x, y);
+            }
+        }
+        assert!(!game.concrete.row_is_empty(SCREEN_HEIGHT - 1));
+    }
+
+    #[test]
+    fn big_mode_draws_each_cell_as_2x2_block() {
+        let config = TetrisConfig {
+            big_mode: true,
+            next_preview: None,
+            ghost_piece: false,
+            ..TetrisConfig::default()
+        };
+        let mut display = CaptureDisplay::new();
+        let mut controller = ScriptedController::new();
+        let timer = VirtualTimer::new();
+        let capture = display.clone();
+
+        let mut game =
+            TetrisGame::with_config(ZeroRng, &mut display, &mut controller, &timer, config);
+        let mut runner = Runner::new(game.run());
+        runner.step();
+
+        // The 4-cell I piece spans the whole width, centered on the cell grid
+        let screen = capture.frame_buffer(0);
+        assert_eq!(piece_bounds(&screen), (0, 8, INIT_Y as usize, 2));
+        let piece = TETRAMINO.wrapping_at(0);
+        for col in 0..piece.width() as usize {
+            for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                let cell = screen.get(2 * col + dx, INIT_Y as usize + dy);
+                assert_eq!(cell, LIGHT_BLUE_IDX, "col {} ({}, {})", col, dx, dy);
+            }
         }
     }
 }
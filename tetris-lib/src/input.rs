@@ -0,0 +1,259 @@
+// Input helpers shared by the platform controllers
+
+/// Button press latch with a timestamp.
+///
+/// A press stays available until it is taken or until it is older than
+/// `hold_ms`, so a press that arrives during a long display write is still
+/// seen by the next frame while a forgotten one doesn't fire much later.
+#[derive(Clone, Copy, Debug)]
+pub struct PressLatch {
+    pressed_at: Option<u64>,
+    hold_ms: u64,
+}
+
+impl PressLatch {
+    pub const fn new(hold_ms: u64) -> Self {
+        Self {
+            pressed_at: None,
+            hold_ms,
+        }
+    }
+
+    /// Record a press at `now_ms`. A newer press replaces a pending one.
+    pub fn press(&mut self, now_ms: u64) {
+        self.pressed_at = Some(now_ms);
+    }
+
+    /// Consume the pending press, true if it is still fresh at `now_ms`
+    pub fn take(&mut self, now_ms: u64) -> bool {
+        match self.pressed_at.take() {
+            Some(at) => now_ms.saturating_sub(at) <= self.hold_ms,
+            None => false,
+        }
+    }
+
+    /// Drop any pending press, e.g. between games
+    pub fn flush(&mut self) {
+        self.pressed_at = None;
+    }
+}
+
+/// Joystick position sampled off the game loop.
+///
+/// A background task stores every sample and the game reads the latest one.
+/// A sample older than `max_age_ms` reads as centered, so a stalled sampler
+/// can't leave the stick stuck in one direction.
+#[derive(Clone, Copy, Debug)]
+pub struct JoystickCache {
+    sample: Option<(i8, i8, u64)>,
+    max_age_ms: u64,
+}
+
+impl JoystickCache {
+    pub const fn new(max_age_ms: u64) -> Self {
+        Self {
+            sample: None,
+            max_age_ms,
+        }
+    }
+
+    /// Record the position sampled at `now_ms`
+    pub fn store(&mut self, x: i8, y: i8, now_ms: u64) {
+        self.sample = Some((x, y, now_ms));
+    }
+
+    /// Latest position if it is still fresh at `now_ms`, centered otherwise
+    pub fn read(&self, now_ms: u64) -> (i8, i8) {
+        match self.sample {
+            Some((x, y, at)) if now_ms.saturating_sub(at) <= self.max_age_ms => (x, y),
+            _ => (0, 0),
+        }
+    }
+}
+
+/// Projectile fired by `ChargeShot`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Shot {
+    Normal,
+    Charged,
+}
+
+/// Hold-to-charge fire button.
+///
+/// Holding the button charges a shot that is fired on release, charged once
+/// it was held for `charge_frames`. A press released before any frame saw
+/// it held fires a normal shot right away.
+#[derive(Clone, Copy, Debug)]
+pub struct ChargeShot {
+    held_frames: u16,
+    charge_frames: u16,
+}
+
+impl ChargeShot {
+    pub const fn new(charge_frames: u16) -> Self {
+        Self {
+            held_frames: 0,
+            charge_frames,
+        }
+    }
+
+    /// Feed the button state once per frame, returns the shot to fire
+    pub fn update(&mut self, pressed: bool, held: bool) -> Option<Shot> {
+        if held {
+            self.held_frames = self.held_frames.saturating_add(1);
+            return None;
+        }
+        match core::mem::take(&mut self.held_frames) {
+            0 if pressed => Some(Shot::Normal),
+            0 => None,
+            held_frames if held_frames >= self.charge_frames => Some(Shot::Charged),
+            _ => Some(Shot::Normal),
+        }
+    }
+}
+
+/// Fire button with auto-fire.
+///
+/// A press fires right away and holding the button keeps firing every
+/// `interval_frames`. Caps on live projectiles stay with the game.
+#[derive(Clone, Copy, Debug)]
+pub struct AutoFire {
+    cooldown: u16,
+    interval_frames: u16,
+}
+
+impl AutoFire {
+    pub const fn new(interval_frames: u16) -> Self {
+        Self {
+            cooldown: 0,
+            interval_frames,
+        }
+    }
+
+    /// Feed the button state once per frame, true when a shot is due
+    pub fn update(&mut self, pressed: bool, held: bool) -> bool {
+        if pressed {
+            self.cooldown = self.interval_frames;
+            return true;
+        }
+        if !held {
+            self.cooldown = 0;
+            return false;
+        }
+        self.cooldown = self.cooldown.saturating_sub(1);
+        if self.cooldown == 0 {
+            self.cooldown = self.interval_frames;
+            return true;
+        }
+        false
+    }
+}
+
+/// Fire button of the shooting games.
+///
+/// Charging takes over from auto-fire when both are set, with neither a
+/// press fires one normal shot.
+#[derive(Clone, Copy, Debug)]
+pub struct FireButton {
+    charge: Option<ChargeShot>,
+    auto_fire: Option<AutoFire>,
+}
+
+impl FireButton {
+    pub fn new(charge_frames: Option<u16>, auto_fire_frames: Option<u16>) -> Self {
+        Self {
+            charge: charge_frames.map(ChargeShot::new),
+            auto_fire: auto_fire_frames.map(AutoFire::new),
+        }
+    }
+
+    /// Feed the button state once per frame, returns the shot to fire
+    pub fn update(&mut self, pressed: bool, held: bool) -> Option<Shot> {
+        match (self.charge.as_mut(), self.auto_fire.as_mut()) {
+            (Some(charge), _) => charge.update(pressed, held),
+            (None, Some(auto)) => auto.update(pressed, held).then_some(Shot::Normal),
+            (None, None) => pressed.then_some(Shot::Normal),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn press_is_taken_once_while_fresh() {
+        let mut latch = PressLatch::new(100);
+        assert!(!latch.take(0));
+
+        latch.press(10);
+        assert!(latch.take(110));
+        assert!(!latch.take(110));
+    }
+
+    #[test]
+    fn stale_and_flushed_presses_are_dropped() {
+        let mut latch = PressLatch::new(100);
+        latch.press(10);
+        assert!(!latch.take(111));
+
+        latch.press(200);
+        latch.flush();
+        assert!(!latch.take(200));
+    }
+
+    #[test]
+    fn joystick_cache_returns_latest_fresh_sample() {
+        let mut cache = JoystickCache::new(50);
+        assert_eq!(cache.read(0), (0, 0));
+
+        cache.store(1, 0, 10);
+        cache.store(-1, 1, 20);
+        // Reads don't consume the sample
+        assert_eq!(cache.read(30), (-1, 1));
+        assert_eq!(cache.read(70), (-1, 1));
+        assert_eq!(cache.read(71), (0, 0));
+    }
+
+    #[test]
+    fn charge_shot_fires_on_release() {
+        let mut charge = ChargeShot::new(3);
+        assert_eq!(charge.update(false, false), None);
+
+        // A tap between two frames
+        assert_eq!(charge.update(true, false), Some(Shot::Normal));
+
+        assert_eq!(charge.update(true, true), None);
+        assert_eq!(charge.update(false, true), None);
+        assert_eq!(charge.update(false, false), Some(Shot::Normal));
+
+        for _ in 0..3 {
+            assert_eq!(charge.update(false, true), None);
+        }
+        assert_eq!(charge.update(false, false), Some(Shot::Charged));
+        assert_eq!(charge.update(false, false), None);
+    }
+
+    #[test]
+    fn auto_fire_repeats_while_held() {
+        let mut auto = AutoFire::new(3);
+        let shots: [bool; 8] = core::array::from_fn(|frame| auto.update(frame == 0, true));
+        assert_eq!(shots, [true, false, false, true, false, false, true, false]);
+
+        // Releasing resets the cooldown, the next press fires at once
+        assert!(!auto.update(false, false));
+        assert!(auto.update(true, true));
+    }
+
+    #[test]
+    fn fire_button_prefers_charging_over_auto_fire() {
+        let mut plain = FireButton::new(None, None);
+        assert_eq!(plain.update(true, true), Some(Shot::Normal));
+        assert_eq!(plain.update(false, true), None);
+
+        let mut button = FireButton::new(Some(2), Some(1));
+        assert_eq!(button.update(true, true), None);
+        assert_eq!(button.update(false, true), None);
+        assert_eq!(button.update(false, false), Some(Shot::Charged));
+    }
+}
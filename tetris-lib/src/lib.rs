@@ -2,7 +2,14 @@
 #![allow(async_fn_in_trait)]
 
 pub mod common;
+pub mod coords;
 pub mod digits;
 pub mod figure;
+pub mod font;
 pub mod games;
+pub mod input;
 pub mod log;
+#[cfg(feature = "frame-observer")]
+pub mod observer;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
@@ -0,0 +1,50 @@
+use core::cell::Cell;
+
+use smart_leds::RGB8;
+
+use crate::common::LedDisplay;
+
+/// Receives a compact signature of every frame written to the display,
+/// e.g. to record session metadata without capturing full frames
+pub trait FrameObserver {
+    /// `game` is the index into `GAME_TITLES` of the running game, `None`
+    /// while the menu is shown. `frame` counts every write of the session.
+    fn on_frame(&mut self, game: Option<u8>, frame: u32, checksum: u32);
+}
+
+/// FNV-1a hash of the written colors, same scheme as `FrameBuffer::checksum`
+pub fn leds_checksum(leds: &[RGB8; 256]) -> u32 {
+    leds.iter().fold(0x811c_9dc5, |hash: u32, led| {
+        [led.r, led.g, led.b].iter().fold(hash, |hash, &byte| {
+            (hash ^ byte as u32).wrapping_mul(0x0100_0193)
+        })
+    })
+}
+
+/// Display wrapper that reports each write to a `FrameObserver`
+pub struct ObservedDisplay<'a, D, O> {
+    inner: &'a mut D,
+    observer: &'a mut O,
+    game: &'a Cell<Option<u8>>,
+    frame: u32,
+}
+
+impl<'a, D: LedDisplay, O: FrameObserver> ObservedDisplay<'a, D, O> {
+    pub fn new(inner: &'a mut D, observer: &'a mut O, game: &'a Cell<Option<u8>>) -> Self {
+        Self {
+            inner,
+            observer,
+            game,
+            frame: 0,
+        }
+    }
+}
+
+impl<D: LedDisplay, O: FrameObserver> LedDisplay for ObservedDisplay<'_, D, O> {
+    async fn write(&mut self, leds: &[RGB8; 256]) {
+        self.inner.write(leds).await;
+        self.observer
+            .on_frame(self.game.get(), self.frame, leds_checksum(leds));
+        self.frame = self.frame.wrapping_add(1);
+    }
+}
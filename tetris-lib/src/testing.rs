@@ -0,0 +1,321 @@
+// Test doubles for driving games without real hardware.
+//
+// Games are plain async loops, so a test wraps `game.run()` in a `Runner`
+// and advances it one `Timer::sleep_millis` call at a time. The display,
+// controller and timer below are cheap handles that can be cloned before the
+// game borrows them, letting the test inspect frames and change inputs
+// between steps.
+
+extern crate std;
+
+use core::cell::{Cell, RefCell};
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll, Waker};
+use std::boxed::Box;
+use std::rc::Rc;
+use std::vec::Vec;
+
+use smart_leds::RGB8;
+
+use crate::common::{
+    get_pixel, FrameBuffer, GameController, LedDisplay, Prng, ScoreStore, StatusEvent, StatusSink,
+    Timer,
+};
+use crate::common::{SCREEN_HEIGHT, SCREEN_WIDTH};
+
+/// Display that records every written frame
+#[derive(Clone, Default)]
+pub struct CaptureDisplay {
+    frames: Rc<RefCell<Vec<[RGB8; 256]>>>,
+}
+
+impl CaptureDisplay {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.frames.borrow().len()
+    }
+
+    pub fn frame(&self, idx: usize) -> [RGB8; 256] {
+        self.frames.borrow()[idx]
+    }
+
+    pub fn last_frame(&self) -> [RGB8; 256] {
+        *self.frames.borrow().last().expect("no frames written")
+    }
+
+    /// Convert a captured frame back into palette indices
+    pub fn frame_buffer(&self, idx: usize) -> FrameBuffer {
+        to_frame_buffer(&self.frame(idx))
+    }
+
+    pub fn last_frame_buffer(&self) -> FrameBuffer {
+        to_frame_buffer(&self.last_frame())
+    }
+}
+
+impl LedDisplay for CaptureDisplay {
+    async fn write(&mut self, leds: &[RGB8; 256]) {
+        self.frames.borrow_mut().push(*leds);
+    }
+}
+
+fn to_frame_buffer(leds: &[RGB8; 256]) -> FrameBuffer {
+    let mut buffer = FrameBuffer::new();
+    for y in 0..SCREEN_HEIGHT {
+        for x in 0..SCREEN_WIDTH {
+            buffer.set(x, y, get_pixel(leds, x, y));
+        }
+    }
+    buffer
+}
+
+#[derive(Default)]
+struct ControllerState {
+    x: Cell<i8>,
+    y: Cell<i8>,
+    joystick: Cell<bool>,
+    joystick_held: Cell<bool>,
+    a: Cell<bool>,
+    b: Cell<bool>,
+}
+
+/// Controller whose inputs are set by the test.
+///
+/// Axis values and the joystick held state stay until changed, button
+/// presses are one-shot and are consumed by the first `*_was_pressed` call.
+#[derive(Clone, Default)]
+pub struct ScriptedController {
+    state: Rc<ControllerState>,
+}
+
+impl ScriptedController {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_x(&self, x: i8) {
+        self.state.x.set(x);
+    }
+
+    pub fn set_y(&self, y: i8) {
+        self.state.y.set(y);
+    }
+
+    pub fn press_joystick(&self) {
+        self.state.joystick.set(true);
+    }
+
+    pub fn set_joystick_held(&self, held: bool) {
+        self.state.joystick_held.set(held);
+    }
+
+    pub fn press_a(&self) {
+        self.state.a.set(true);
+    }
+
+    pub fn press_b(&self) {
+        self.state.b.set(true);
+    }
+}
+
+impl GameController for ScriptedController {
+    async fn read_x(&mut self) -> i8 {
+        self.state.x.get()
+    }
+
+    async fn read_y(&mut self) -> i8 {
+        self.state.y.get()
+    }
+
+    fn joystick_was_pressed(&self) -> bool {
+        self.state.joystick.replace(false)
+    }
+
+    fn joystick_held(&self) -> bool {
+        self.state.joystick_held.get()
+    }
+
+    fn a_was_pressed(&self) -> bool {
+        self.state.a.replace(false)
+    }
+
+    fn b_was_pressed(&self) -> bool {
+        self.state.b.replace(false)
+    }
+}
+
+/// Controller that makes up inputs from a seed, for fuzzing games.
+///
+/// Every axis read picks left, center or right anew, and each button reports
+/// a press one time in `RANDOM_PRESS_ODDS`.
+pub struct RandomController {
+    prng: RefCell<Prng>,
+}
+
+/// Inverse chance of a `RandomController` button press per query
+pub const RANDOM_PRESS_ODDS: u8 = 8;
+
+impl RandomController {
+    pub fn new(seed: u32) -> Self {
+        Self {
+            prng: RefCell::new(Prng::new(seed)),
+        }
+    }
+
+    fn axis(&self) -> i8 {
+        self.prng.borrow_mut().next_range(3) as i8 - 1
+    }
+
+    fn press(&self) -> bool {
+        self.prng.borrow_mut().next_range(RANDOM_PRESS_ODDS) == 0
+    }
+}
+
+impl GameController for RandomController {
+    async fn read_x(&mut self) -> i8 {
+        self.axis()
+    }
+
+    async fn read_y(&mut self) -> i8 {
+        self.axis()
+    }
+
+    fn joystick_was_pressed(&self) -> bool {
+        self.press()
+    }
+
+    fn joystick_held(&self) -> bool {
+        self.prng.borrow_mut().next_range(2) == 0
+    }
+
+    fn a_was_pressed(&self) -> bool {
+        self.press()
+    }
+
+    fn b_was_pressed(&self) -> bool {
+        self.press()
+    }
+}
+
+/// Timer that only accumulates virtual time and yields back to the `Runner`
+#[derive(Default)]
+pub struct VirtualTimer {
+    elapsed: Cell<u64>,
+    sleeps: RefCell<Vec<u64>>,
+}
+
+impl VirtualTimer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Total virtual milliseconds slept so far
+    pub fn elapsed(&self) -> u64 {
+        self.elapsed.get()
+    }
+
+    /// Every requested sleep duration, in call order
+    pub fn sleeps(&self) -> Vec<u64> {
+        self.sleeps.borrow().clone()
+    }
+}
+
+impl Timer for VirtualTimer {
+    async fn sleep_millis(&self, millis: u64) {
+        self.elapsed.set(self.elapsed.get() + millis);
+        self.sleeps.borrow_mut().push(millis);
+        YieldOnce(false).await;
+    }
+}
+
+struct YieldOnce(bool);
+
+impl Future for YieldOnce {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            Poll::Ready(())
+        } else {
+            self.0 = true;
+            Poll::Pending
+        }
+    }
+}
+
+/// Status sink that records every event
+#[derive(Default)]
+pub struct StatusRecorder {
+    pub events: Vec<StatusEvent>,
+}
+
+impl StatusRecorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl StatusSink for StatusRecorder {
+    fn notify(&mut self, event: StatusEvent) {
+        self.events.push(event);
+    }
+}
+
+/// Score store holding a single high score the test can read back
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BestScore(pub u16);
+
+impl ScoreStore for BestScore {
+    fn high_score(&self) -> u16 {
+        self.0
+    }
+
+    fn set_high_score(&mut self, score: u16) {
+        self.0 = score;
+    }
+}
+
+/// Drives a game future one timer sleep at a time
+pub struct Runner<'a> {
+    future: Pin<Box<dyn Future<Output = ()> + 'a>>,
+    finished: bool,
+}
+
+impl<'a> Runner<'a> {
+    pub fn new(future: impl Future<Output = ()> + 'a) -> Self {
+        Self {
+            future: Box::pin(future),
+            finished: false,
+        }
+    }
+
+    /// Run until the next timer sleep. Returns false once the future completed.
+    pub fn step(&mut self) -> bool {
+        if self.finished {
+            return false;
+        }
+        let mut cx = Context::from_waker(Waker::noop());
+        if self.future.as_mut().poll(&mut cx).is_ready() {
+            self.finished = true;
+        }
+        !self.finished
+    }
+
+    /// Run at most `count` steps. Returns false if the future completed.
+    pub fn steps(&mut self, count: usize) -> bool {
+        for _ in 0..count {
+            if !self.step() {
+                return false;
+            }
+        }
+        true
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+}
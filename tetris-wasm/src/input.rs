@@ -0,0 +1,154 @@
+// Keyboard state reconciled from keydown/keyup events.
+//
+// Axis values are derived from the set of currently held keys on every read,
+// so releasing one of two opposite keys falls back to the one still held
+// instead of leaving a stale value behind. Button presses are latched on
+// keydown and cleared once the game reads them, so a press that is released
+// before the next frame is still seen exactly once.
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Key {
+    Left,
+    Right,
+    Up,
+    Down,
+    Joystick,
+    A,
+    B,
+}
+
+impl Key {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "ArrowLeft" | "a" | "A" => Some(Key::Left),
+            "ArrowRight" | "d" | "D" => Some(Key::Right),
+            "ArrowUp" | "w" | "W" => Some(Key::Up),
+            "ArrowDown" | "s" | "S" => Some(Key::Down),
+            "Enter" | " " => Some(Key::Joystick),
+            "q" | "Q" => Some(Key::A),
+            "e" | "E" => Some(Key::B),
+            _ => None,
+        }
+    }
+
+    const fn bit(self) -> u8 {
+        1 << self as u8
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct KeyState {
+    held: u8,
+    pressed: u8,
+    last_x: i8,
+    last_y: i8,
+}
+
+impl KeyState {
+    pub const fn new() -> Self {
+        Self {
+            held: 0,
+            pressed: 0,
+            last_x: 0,
+            last_y: 0,
+        }
+    }
+
+    pub fn key_down(&mut self, key: Key) {
+        // Auto-repeated keydown events must not latch another press
+        if !self.is_held(key) {
+            self.pressed |= key.bit();
+        }
+        self.held |= key.bit();
+        match key {
+            Key::Left => self.last_x = -1,
+            Key::Right => self.last_x = 1,
+            Key::Up => self.last_y = -1,
+            Key::Down => self.last_y = 1,
+            _ => {}
+        }
+    }
+
+    pub fn key_up(&mut self, key: Key) {
+        self.held &= !key.bit();
+    }
+
+    /// Release everything, e.g. when the page loses focus and keyup events
+    /// will never arrive
+    pub fn release_all(&mut self) {
+        *self = Self::new();
+    }
+
+    pub fn is_held(&self, key: Key) -> bool {
+        self.held & key.bit() != 0
+    }
+
+    pub fn x(&self) -> i8 {
+        Self::axis(
+            self.is_held(Key::Left),
+            self.is_held(Key::Right),
+            self.last_x,
+        )
+    }
+
+    pub fn y(&self) -> i8 {
+        Self::axis(self.is_held(Key::Up), self.is_held(Key::Down), self.last_y)
+    }
+
+    /// Consume the latched press of `key`
+    pub fn take_pressed(&mut self, key: Key) -> bool {
+        let pressed = self.pressed & key.bit() != 0;
+        self.pressed &= !key.bit();
+        pressed
+    }
+
+    // When both directions are held the most recently pressed one wins
+    fn axis(negative: bool, positive: bool, last: i8) -> i8 {
+        match (negative, positive) {
+            (true, true) => last,
+            (true, false) => -1,
+            (false, true) => 1,
+            (false, false) => 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn released_key_returns_axis_to_zero() {
+        let mut keys = KeyState::new();
+        keys.key_down(Key::from_name("ArrowLeft").unwrap());
+        assert_eq!(keys.x(), -1);
+        keys.key_up(Key::from_name("a").unwrap());
+        assert_eq!(keys.x(), 0);
+
+        keys.key_down(Key::Down);
+        assert_eq!(keys.y(), 1);
+        keys.key_up(Key::Down);
+        assert_eq!(keys.y(), 0);
+    }
+
+    #[test]
+    fn releasing_one_of_two_opposite_keys_keeps_the_other() {
+        let mut keys = KeyState::new();
+        keys.key_down(Key::Left);
+        keys.key_down(Key::Right);
+        assert_eq!(keys.x(), 1);
+        keys.key_up(Key::Right);
+        assert_eq!(keys.x(), -1);
+    }
+
+    #[test]
+    fn press_is_seen_once_even_if_released_before_read() {
+        let mut keys = KeyState::new();
+        keys.key_down(Key::A);
+        keys.key_down(Key::A); // auto-repeat
+        keys.key_up(Key::A);
+        assert!(keys.take_pressed(Key::A));
+        assert!(!keys.take_pressed(Key::A));
+        assert!(!keys.take_pressed(Key::B));
+    }
+}
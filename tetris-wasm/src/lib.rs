@@ -1,12 +1,20 @@
 use smart_leds::RGB8;
-use std::sync::atomic::{AtomicBool, AtomicI8, Ordering};
+use std::sync::Mutex;
 use tetris_lib::{
-    common::{GameController, LedDisplay, Timer, SCREEN_HEIGHT, SCREEN_WIDTH},
+    common::{
+        Focus, GameController, LedDisplay, MinFrameTimer, PauseOnFocusLoss, Timer, SCREEN_HEIGHT,
+        SCREEN_WIDTH,
+    },
+    coords::led_index,
     games::run_game_menu,
 };
 use wasm_bindgen::prelude::*;
 use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement, ImageData, KeyboardEvent};
 
+mod input;
+
+use input::{Key, KeyState};
+
 // Console logging macro (currently unused but may be useful for debugging)
 #[allow(unused_macros)]
 macro_rules! log {
@@ -61,13 +69,7 @@ impl LedDisplay for WasmDisplay {
 
         for y in 0..height {
             for x in 0..width {
-                let led_idx = if y % 2 == 0 {
-                    y * width + (width - 1 - x)
-                } else {
-                    y * width + x
-                } as usize;
-
-                let led = leds[led_idx];
+                let led = leds[led_index(x as usize, y as usize)];
                 data.push(led.r * 8); // Scale up from 0-31 to 0-248
                 data.push(led.g * 8);
                 data.push(led.b * 8);
@@ -131,29 +133,16 @@ impl LedDisplay for WasmDisplay {
     }
 }
 
-// Global input state using a struct with atomic fields
-#[derive(Default)]
-struct InputState {
-    x_input: AtomicI8,
-    y_input: AtomicI8,
-    joystick_pressed: AtomicBool,
-    a_pressed: AtomicBool,
-    b_pressed: AtomicBool,
-    prev_joystick_pressed: AtomicBool,
-    prev_a_pressed: AtomicBool,
-    prev_b_pressed: AtomicBool,
-}
+// Page visibility, the game loop holds while the tab is hidden
+static FOCUS: Focus = Focus::new();
 
-static INPUT_STATE: InputState = InputState {
-    x_input: AtomicI8::new(0),
-    y_input: AtomicI8::new(0),
-    joystick_pressed: AtomicBool::new(false),
-    a_pressed: AtomicBool::new(false),
-    b_pressed: AtomicBool::new(false),
-    prev_joystick_pressed: AtomicBool::new(false),
-    prev_a_pressed: AtomicBool::new(false),
-    prev_b_pressed: AtomicBool::new(false),
-};
+// Global keyboard state, updated by keydown/keyup and read by the game
+static KEY_STATE: Mutex<KeyState> = Mutex::new(KeyState::new());
+
+fn with_keys<T>(f: impl FnOnce(&mut KeyState) -> T) -> T {
+    let mut keys = KEY_STATE.lock().unwrap_or_else(|e| e.into_inner());
+    f(&mut keys)
+}
 
 // Controller implementation for WASM
 pub struct WasmController;
@@ -164,32 +153,20 @@ impl WasmController {
     }
 
     pub fn handle_key_down(event: &KeyboardEvent) {
-        match event.key().as_str() {
-            "ArrowLeft" | "a" | "A" => INPUT_STATE.x_input.store(-1, Ordering::Relaxed),
-            "ArrowRight" | "d" | "D" => INPUT_STATE.x_input.store(1, Ordering::Relaxed),
-            "ArrowUp" | "w" | "W" => INPUT_STATE.y_input.store(-1, Ordering::Relaxed),
-            "ArrowDown" | "s" | "S" => INPUT_STATE.y_input.store(1, Ordering::Relaxed),
-            "Enter" | " " => INPUT_STATE.joystick_pressed.store(true, Ordering::Relaxed),
-            "q" | "Q" => INPUT_STATE.a_pressed.store(true, Ordering::Relaxed),
-            "e" | "E" => INPUT_STATE.b_pressed.store(true, Ordering::Relaxed),
-            _ => {}
+        if let Some(key) = Key::from_name(&event.key()) {
+            with_keys(|keys| keys.key_down(key));
         }
     }
 
     pub fn handle_key_up(event: &KeyboardEvent) {
-        match event.key().as_str() {
-            "ArrowLeft" | "ArrowRight" | "a" | "A" | "d" | "D" => {
-                INPUT_STATE.x_input.store(0, Ordering::Relaxed)
-            }
-            "ArrowUp" | "ArrowDown" | "w" | "W" | "s" | "S" => {
-                INPUT_STATE.y_input.store(0, Ordering::Relaxed)
-            }
-            "Enter" | " " => INPUT_STATE.joystick_pressed.store(false, Ordering::Relaxed),
-            "q" | "Q" => INPUT_STATE.a_pressed.store(false, Ordering::Relaxed),
-            "e" | "E" => INPUT_STATE.b_pressed.store(false, Ordering::Relaxed),
-            _ => {}
+        if let Some(key) = Key::from_name(&event.key()) {
+            with_keys(|keys| keys.key_up(key));
         }
     }
+
+    pub fn release_all_keys() {
+        with_keys(KeyState::release_all);
+    }
 }
 
 impl Default for WasmController {
@@ -200,31 +177,27 @@ impl Default for WasmController {
 
 impl GameController for WasmController {
     async fn read_x(&mut self) -> i8 {
-        INPUT_STATE.x_input.load(Ordering::Relaxed)
+        with_keys(|keys| keys.x())
     }
 
     async fn read_y(&mut self) -> i8 {
-        INPUT_STATE.y_input.load(Ordering::Relaxed)
+        with_keys(|keys| keys.y())
     }
 
     fn joystick_was_pressed(&self) -> bool {
-        let current = INPUT_STATE.joystick_pressed.load(Ordering::Relaxed);
-        let prev = INPUT_STATE
-            .prev_joystick_pressed
-            .swap(current, Ordering::Relaxed);
-        current && !prev
+        with_keys(|keys| keys.take_pressed(Key::Joystick))
+    }
+
+    fn joystick_held(&self) -> bool {
+        with_keys(|keys| keys.is_held(Key::Joystick))
     }
 
     fn a_was_pressed(&self) -> bool {
-        let current = INPUT_STATE.a_pressed.load(Ordering::Relaxed);
-        let prev = INPUT_STATE.prev_a_pressed.swap(current, Ordering::Relaxed);
-        current && !prev
+        with_keys(|keys| keys.take_pressed(Key::A))
     }
 
     fn b_was_pressed(&self) -> bool {
-        let current = INPUT_STATE.b_pressed.load(Ordering::Relaxed);
-        let prev = INPUT_STATE.prev_b_pressed.swap(current, Ordering::Relaxed);
-        current && !prev
+        with_keys(|keys| keys.take_pressed(Key::B))
     }
 }
 
@@ -244,7 +217,7 @@ pub async fn start_game(canvas: HtmlCanvasElement, pixel_size: f64) -> Result<()
 
     // Create controller and timer
     let mut controller = WasmController::new();
-    let timer = WasmTimer;
+    let timer = PauseOnFocusLoss::new(MinFrameTimer::new(WasmTimer), &FOCUS);
 
     // Seed function using current timestamp
     let seed_fn = || js_sys::Date::now() as u32;
@@ -265,3 +238,15 @@ pub fn handle_key_down(event: KeyboardEvent) {
 pub fn handle_key_up(event: KeyboardEvent) {
     WasmController::handle_key_up(&event);
 }
+
+// Call on window blur: keyup events for keys held at that moment are lost
+#[wasm_bindgen]
+pub fn release_all_keys() {
+    WasmController::release_all_keys();
+}
+
+// Call on visibilitychange with `!document.hidden`
+#[wasm_bindgen]
+pub fn set_focused(focused: bool) {
+    FOCUS.set_focused(focused);
+}